@@ -130,3 +130,75 @@ resource "aws_db_instance" "mysql_instance" {
   delete_automated_backups = var.delete_automated_backups
 
 }
+
+{%- if read_replicas_enabled %}
+# Read replicas, RDS doesn't provide a reader endpoint for non Aurora instances
+# so the read fqdn targets the first replica
+resource "aws_db_instance" "mysql_read_replica" {
+  count = var.read_replicas_count
+  identifier = "${var.mysql_identifier}-read-${count.index}"
+  replicate_source_db = aws_db_instance.mysql_instance.identifier
+
+  tags = local.mysql_database_tags
+
+  instance_class = var.instance_class
+  port = var.port
+  timeouts {
+    create = "60m"
+    update = "120m"
+    delete = "60m"
+  }
+  storage_encrypted = var.encrypt_disk
+
+  # Network
+  vpc_security_group_ids = data.aws_security_group.selected.*.id
+  publicly_accessible = var.publicly_accessible
+
+  # Maintenance and upgrades
+  apply_immediately = var.apply_changes_now
+  auto_minor_version_upgrade = var.auto_minor_version_upgrade
+  maintenance_window = var.preferred_maintenance_window
+
+  # Monitoring
+  monitoring_interval = 10
+  monitoring_role_arn = data.aws_iam_role.rds_enhanced_monitoring.arn
+
+  # Backups are handled by the primary instance
+  backup_retention_period = 0
+  skip_final_snapshot = true
+  copy_tags_to_snapshot = true
+
+  depends_on = [
+    aws_db_instance.mysql_instance
+  ]
+}
+
+resource "helm_release" "mysql_read_replica_external_name" {
+  name = "${aws_db_instance.mysql_instance.id}-read-externalname"
+  chart = "external-name-svc"
+  namespace = "{{namespace}}"
+  atomic = true
+  max_history = 50
+
+  set {
+    name = "target_hostname"
+    value = aws_db_instance.mysql_read_replica[0].address
+  }
+  set {
+    name = "source_fqdn"
+    value = "{{database_read_fqdn}}"
+  }
+  set {
+    name = "app_id"
+    value = "{{database_id}}"
+  }
+  set {
+    name = "service_name"
+    value = "{{service_name}}-read"
+  }
+
+  depends_on = [
+    aws_db_instance.mysql_read_replica
+  ]
+}
+{%- endif %}
@@ -64,4 +64,10 @@ variable "database_name" {
   description = "The name of the database to create when the DB instance is created. If this parameter is not specified, no database is created in the DB instance"
   default = "{{ database_name }}"
   type = string
-}
\ No newline at end of file
+}
+
+variable "read_replicas_count" {
+  description = "Number of read replicas"
+  default = {{ read_replicas_count }}
+  type = number
+}
@@ -0,0 +1,35 @@
+terraform {
+  required_providers {
+    digitalocean = {
+      source = "digitalocean/digitalocean"
+      version = "~> 2.11.0"
+    }
+    local = {
+      source = "hashicorp/local"
+      version = "~> 1.4"
+    }
+    null = {
+      source = "hashicorp/null"
+      version = "~> 2.1"
+    }
+    time = {
+      source  = "hashicorp/time"
+      version = "~> 0.3"
+    }
+  }
+  required_version = ">= 0.14"
+}
+
+provider "digitalocean" {
+  token = "{{ digital_ocean_token }}"
+}
+
+data "digitalocean_kubernetes_cluster" "kubernetes_cluster" {
+  name = "qovery-{{kubernetes_cluster_id}}"
+}
+
+provider "helm" {
+  kubernetes {
+    config_path = "{{ kubeconfig_path }}"
+  }
+}
@@ -0,0 +1,100 @@
+locals {
+  tags_mongodb = {
+    cluster_name = var.cluster_name
+    cluster_id = var.kubernetes_cluster_id
+    region = var.region
+    q_client_id = var.q_customer_id
+    q_environment_id = var.q_environment_id
+    q_project_id = var.q_project_id
+    database_identifier = var.mongodb_identifier
+    {% if resource_expiration_in_seconds is defined %}ttl = var.resource_expiration_in_seconds{% endif %}
+  }
+  tags_mongodb_list = [for i, v in local.tags_mongodb : "${i}:${v}"] # NOTE: DigitalOcean doesn't support KV style tags
+}
+
+resource "digitalocean_database_cluster" "mongodb_instance" {
+  name       = var.mongodb_identifier
+  engine     = "mongodb"
+  version    = var.mongodb_version
+  size       = var.instance_class
+  region     = var.region
+  node_count = var.activate_high_availability ? 2 : 1
+
+  # Attach the database to the Kubernetes cluster VPC so applications reach it through the private network
+  private_network_uuid = data.digitalocean_kubernetes_cluster.kubernetes_cluster.vpc_uuid
+
+  tags = local.tags_mongodb_list
+}
+
+resource "digitalocean_database_db" "mongodb_main" {
+  cluster_id = digitalocean_database_cluster.mongodb_instance.id
+  name       = var.database_name
+}
+
+resource "digitalocean_database_user" "mongodb_user" {
+  cluster_id = digitalocean_database_cluster.mongodb_instance.id
+  name       = var.username
+}
+
+# DigitalOcean generates users passwords, the requested one is set from a client pod running inside the cluster VPC
+resource "null_resource" "mongodb_user_password" {
+  triggers = {
+    user_id  = digitalocean_database_user.mongodb_user.id
+    password = sha256(var.password)
+  }
+
+  provisioner "local-exec" {
+    command = "printf '%s\\n' \"$STATEMENT\" | kubectl --kubeconfig {{ kubeconfig_path }} -n {{ namespace }} run {{ fqdn_id }}-set-password --rm -i --quiet --restart=Never --image=mongo:4.4 --env=ADMIN_URI=\"$ADMIN_URI\" --command -- sh -c 'exec mongo \"$ADMIN_URI\" --quiet'"
+    environment = {
+      # credentials are stripped from the uri, they are only sent through stdin
+      ADMIN_URI      = replace(digitalocean_database_cluster.mongodb_instance.private_uri, "${digitalocean_database_cluster.mongodb_instance.user}:${digitalocean_database_cluster.mongodb_instance.password}@", "")
+      STATEMENT      = "try { if (!db.getSiblingDB('admin').auth(${jsonencode(digitalocean_database_cluster.mongodb_instance.user)}, ${jsonencode(digitalocean_database_cluster.mongodb_instance.password)})) { quit(1); } db.getSiblingDB('admin').updateUser(${jsonencode(var.username)}, { pwd: ${jsonencode(var.password)} }); } catch (e) { print(e); quit(1); }"
+    }
+  }
+
+  depends_on = [
+    digitalocean_database_user.mongodb_user,
+    digitalocean_database_db.mongodb_main
+  ]
+}
+
+{%- if publicly_accessible != true %}
+# Only the Kubernetes cluster is a trusted source when the database is private
+resource "digitalocean_database_firewall" "mongodb_firewall" {
+  cluster_id = digitalocean_database_cluster.mongodb_instance.id
+
+  rule {
+    type  = "k8s"
+    value = data.digitalocean_kubernetes_cluster.kubernetes_cluster.id
+  }
+}
+{% endif %}
+
+resource "helm_release" "mongodb_instance_external_name" {
+  name = "${digitalocean_database_cluster.mongodb_instance.id}-externalname"
+  chart = "external-name-svc"
+  namespace = "{{namespace}}"
+  atomic = true
+  max_history = 50
+
+  set {
+    name = "target_hostname"
+    value = var.publicly_accessible ? digitalocean_database_cluster.mongodb_instance.host : digitalocean_database_cluster.mongodb_instance.private_host
+  }
+  set {
+    name = "source_fqdn"
+    value = "{{database_fqdn}}"
+  }
+  set {
+    name = "app_id"
+    value = "{{database_id}}"
+  }
+  set {
+    name = "service_name"
+    value = "{{service_name}}"
+  }
+
+  depends_on = [
+    digitalocean_database_cluster.mongodb_instance
+  ]
+}
@@ -0,0 +1,149 @@
+locals {
+  tags_mysql = {
+    cluster_name = var.cluster_name
+    cluster_id = var.kubernetes_cluster_id
+    region = var.region
+    q_client_id = var.q_customer_id
+    q_environment_id = var.q_environment_id
+    q_project_id = var.q_project_id
+    database_identifier = var.mysql_identifier
+    {% if resource_expiration_in_seconds is defined %}ttl = var.resource_expiration_in_seconds{% endif %}
+  }
+  tags_mysql_list = [for i, v in local.tags_mysql : "${i}:${v}"] # NOTE: DigitalOcean doesn't support KV style tags
+}
+
+resource "digitalocean_database_cluster" "mysql_instance" {
+  name       = var.mysql_identifier
+  engine     = "mysql"
+  version    = var.mysql_version
+  size       = var.instance_class
+  region     = var.region
+  node_count = var.activate_high_availability ? 2 : 1
+
+  # Attach the database to the Kubernetes cluster VPC so applications reach it through the private network
+  private_network_uuid = data.digitalocean_kubernetes_cluster.kubernetes_cluster.vpc_uuid
+
+  tags = local.tags_mysql_list
+}
+
+resource "digitalocean_database_db" "mysql_main" {
+  cluster_id = digitalocean_database_cluster.mysql_instance.id
+  name       = var.database_name
+}
+
+resource "digitalocean_database_user" "mysql_user" {
+  cluster_id = digitalocean_database_cluster.mysql_instance.id
+  name       = var.username
+}
+
+# DigitalOcean generates users passwords, the requested one is set from a client pod running inside the cluster VPC
+resource "null_resource" "mysql_user_password" {
+  triggers = {
+    user_id  = digitalocean_database_user.mysql_user.id
+    password = sha256(var.password)
+  }
+
+  provisioner "local-exec" {
+    command = "printf '%s\\n%s\\n' \"$ADMIN_PASSWORD\" \"$STATEMENT\" | kubectl --kubeconfig {{ kubeconfig_path }} -n {{ namespace }} run {{ fqdn_id }}-set-password --rm -i --quiet --restart=Never --image=mysql:8.0 --env=MYSQL_HOST=\"$DB_HOST\" --env=MYSQL_TCP_PORT=\"$DB_PORT\" --env=MYSQL_USER=\"$ADMIN_USER\" --command -- sh -c 'IFS= read -r MYSQL_PWD && export MYSQL_PWD && exec mysql -u \"$MYSQL_USER\" --ssl-mode=REQUIRED'"
+    environment = {
+      ADMIN_USER     = digitalocean_database_cluster.mysql_instance.user
+      ADMIN_PASSWORD = digitalocean_database_cluster.mysql_instance.password
+      DB_HOST        = digitalocean_database_cluster.mysql_instance.private_host
+      DB_PORT        = digitalocean_database_cluster.mysql_instance.port
+      STATEMENT      = "ALTER USER '${var.username}'@'%' IDENTIFIED BY '${replace(replace(var.password, "\\", "\\\\"), "'", "''")}';"
+    }
+  }
+
+  depends_on = [
+    digitalocean_database_user.mysql_user,
+    digitalocean_database_db.mysql_main
+  ]
+}
+
+{%- if publicly_accessible != true %}
+# Only the Kubernetes cluster is a trusted source when the database is private
+resource "digitalocean_database_firewall" "mysql_firewall" {
+  cluster_id = digitalocean_database_cluster.mysql_instance.id
+
+  rule {
+    type  = "k8s"
+    value = data.digitalocean_kubernetes_cluster.kubernetes_cluster.id
+  }
+}
+{% endif %}
+
+resource "helm_release" "mysql_instance_external_name" {
+  name = "${digitalocean_database_cluster.mysql_instance.id}-externalname"
+  chart = "external-name-svc"
+  namespace = "{{namespace}}"
+  atomic = true
+  max_history = 50
+
+  set {
+    name = "target_hostname"
+    value = var.publicly_accessible ? digitalocean_database_cluster.mysql_instance.host : digitalocean_database_cluster.mysql_instance.private_host
+  }
+  set {
+    name = "source_fqdn"
+    value = "{{database_fqdn}}"
+  }
+  set {
+    name = "app_id"
+    value = "{{database_id}}"
+  }
+  set {
+    name = "service_name"
+    value = "{{service_name}}"
+  }
+
+  depends_on = [
+    digitalocean_database_cluster.mysql_instance
+  ]
+}
+
+{%- if read_replicas_enabled %}
+resource "digitalocean_database_replica" "mysql_read_replica" {
+  count      = var.read_replicas_count
+  cluster_id = digitalocean_database_cluster.mysql_instance.id
+  name       = "${var.mysql_identifier}-read-${count.index}"
+  size       = var.instance_class
+  region     = var.region
+
+  private_network_uuid = data.digitalocean_kubernetes_cluster.kubernetes_cluster.vpc_uuid
+
+  tags = local.tags_mysql_list
+
+  depends_on = [
+    digitalocean_database_db.mysql_main
+  ]
+}
+
+resource "helm_release" "mysql_read_replica_external_name" {
+  name = "${digitalocean_database_cluster.mysql_instance.id}-read-externalname"
+  chart = "external-name-svc"
+  namespace = "{{namespace}}"
+  atomic = true
+  max_history = 50
+
+  set {
+    name = "target_hostname"
+    value = var.publicly_accessible ? digitalocean_database_replica.mysql_read_replica[0].host : digitalocean_database_replica.mysql_read_replica[0].private_host
+  }
+  set {
+    name = "source_fqdn"
+    value = "{{database_read_fqdn}}"
+  }
+  set {
+    name = "app_id"
+    value = "{{database_id}}"
+  }
+  set {
+    name = "service_name"
+    value = "{{service_name}}-read"
+  }
+
+  depends_on = [
+    digitalocean_database_replica.mysql_read_replica
+  ]
+}
+{%- endif %}
@@ -0,0 +1,149 @@
+locals {
+  tags_postgresql = {
+    cluster_name = var.cluster_name
+    cluster_id = var.kubernetes_cluster_id
+    region = var.region
+    q_client_id = var.q_customer_id
+    q_environment_id = var.q_environment_id
+    q_project_id = var.q_project_id
+    database_identifier = var.postgresql_identifier
+    {% if resource_expiration_in_seconds is defined %}ttl = var.resource_expiration_in_seconds{% endif %}
+  }
+  tags_postgresql_list = [for i, v in local.tags_postgresql : "${i}:${v}"] # NOTE: DigitalOcean doesn't support KV style tags
+}
+
+resource "digitalocean_database_cluster" "postgresql_instance" {
+  name       = var.postgresql_identifier
+  engine     = "pg"
+  version    = var.postgresql_version
+  size       = var.instance_class
+  region     = var.region
+  node_count = var.activate_high_availability ? 2 : 1
+
+  # Attach the database to the Kubernetes cluster VPC so applications reach it through the private network
+  private_network_uuid = data.digitalocean_kubernetes_cluster.kubernetes_cluster.vpc_uuid
+
+  tags = local.tags_postgresql_list
+}
+
+resource "digitalocean_database_db" "postgresql_main" {
+  cluster_id = digitalocean_database_cluster.postgresql_instance.id
+  name       = var.database_name
+}
+
+resource "digitalocean_database_user" "postgresql_user" {
+  cluster_id = digitalocean_database_cluster.postgresql_instance.id
+  name       = var.username
+}
+
+# DigitalOcean generates users passwords, the requested one is set from a client pod running inside the cluster VPC
+resource "null_resource" "postgresql_user_password" {
+  triggers = {
+    user_id  = digitalocean_database_user.postgresql_user.id
+    password = sha256(var.password)
+  }
+
+  provisioner "local-exec" {
+    command = "printf '%s\\n%s\\n' \"$ADMIN_PASSWORD\" \"$STATEMENT\" | kubectl --kubeconfig {{ kubeconfig_path }} -n {{ namespace }} run {{ fqdn_id }}-set-password --rm -i --quiet --restart=Never --image=postgres:13-alpine --env=PGHOST=\"$DB_HOST\" --env=PGPORT=\"$DB_PORT\" --env=PGUSER=\"$ADMIN_USER\" --env=PGDATABASE=defaultdb --env=PGSSLMODE=require --command -- sh -c 'IFS= read -r PGPASSWORD && export PGPASSWORD && exec psql -v ON_ERROR_STOP=1'"
+    environment = {
+      ADMIN_USER     = digitalocean_database_cluster.postgresql_instance.user
+      ADMIN_PASSWORD = digitalocean_database_cluster.postgresql_instance.password
+      DB_HOST        = digitalocean_database_cluster.postgresql_instance.private_host
+      DB_PORT        = digitalocean_database_cluster.postgresql_instance.port
+      STATEMENT      = "ALTER ROLE \"${var.username}\" WITH PASSWORD '${replace(var.password, "'", "''")}';"
+    }
+  }
+
+  depends_on = [
+    digitalocean_database_user.postgresql_user,
+    digitalocean_database_db.postgresql_main
+  ]
+}
+
+{%- if publicly_accessible != true %}
+# Only the Kubernetes cluster is a trusted source when the database is private
+resource "digitalocean_database_firewall" "postgresql_firewall" {
+  cluster_id = digitalocean_database_cluster.postgresql_instance.id
+
+  rule {
+    type  = "k8s"
+    value = data.digitalocean_kubernetes_cluster.kubernetes_cluster.id
+  }
+}
+{% endif %}
+
+resource "helm_release" "postgresql_instance_external_name" {
+  name = "${digitalocean_database_cluster.postgresql_instance.id}-externalname"
+  chart = "external-name-svc"
+  namespace = "{{namespace}}"
+  atomic = true
+  max_history = 50
+
+  set {
+    name = "target_hostname"
+    value = var.publicly_accessible ? digitalocean_database_cluster.postgresql_instance.host : digitalocean_database_cluster.postgresql_instance.private_host
+  }
+  set {
+    name = "source_fqdn"
+    value = "{{database_fqdn}}"
+  }
+  set {
+    name = "app_id"
+    value = "{{database_id}}"
+  }
+  set {
+    name = "service_name"
+    value = "{{service_name}}"
+  }
+
+  depends_on = [
+    digitalocean_database_cluster.postgresql_instance
+  ]
+}
+
+{%- if read_replicas_enabled %}
+resource "digitalocean_database_replica" "postgresql_read_replica" {
+  count      = var.read_replicas_count
+  cluster_id = digitalocean_database_cluster.postgresql_instance.id
+  name       = "${var.postgresql_identifier}-read-${count.index}"
+  size       = var.instance_class
+  region     = var.region
+
+  private_network_uuid = data.digitalocean_kubernetes_cluster.kubernetes_cluster.vpc_uuid
+
+  tags = local.tags_postgresql_list
+
+  depends_on = [
+    digitalocean_database_db.postgresql_main
+  ]
+}
+
+resource "helm_release" "postgresql_read_replica_external_name" {
+  name = "${digitalocean_database_cluster.postgresql_instance.id}-read-externalname"
+  chart = "external-name-svc"
+  namespace = "{{namespace}}"
+  atomic = true
+  max_history = 50
+
+  set {
+    name = "target_hostname"
+    value = var.publicly_accessible ? digitalocean_database_replica.postgresql_read_replica[0].host : digitalocean_database_replica.postgresql_read_replica[0].private_host
+  }
+  set {
+    name = "source_fqdn"
+    value = "{{database_read_fqdn}}"
+  }
+  set {
+    name = "app_id"
+    value = "{{database_id}}"
+  }
+  set {
+    name = "service_name"
+    value = "{{service_name}}-read"
+  }
+
+  depends_on = [
+    digitalocean_database_replica.postgresql_read_replica
+  ]
+}
+{%- endif %}
@@ -0,0 +1,107 @@
+# Qovery
+
+variable "cluster_name" {
+  description = "Kubernetes cluster name"
+  default     = "{{ cluster_name }}"
+  type        = string
+}
+
+variable "region" {
+  description = "DigitalOcean region where the database cluster is created"
+  default     = "{{ region }}"
+  type        = string
+}
+
+variable "kubernetes_cluster_id" {
+  description = "Kubernetes cluster id"
+  default     = "{{ kubernetes_cluster_id }}"
+  type        = string
+}
+
+variable "q_project_id" {
+  description = "Qovery project ID"
+  default     = "{{ project_id }}"
+  type        = string
+}
+
+variable "q_customer_id" {
+  description = "Qovery customer ID"
+  default     = "{{ owner_id }}"
+  type        = string
+}
+
+variable "q_environment_id" {
+  description = "Qovery client environment"
+  default     = "{{ environment_id }}"
+  type        = string
+}
+
+# PostgreSQL instance basics
+
+variable "postgresql_identifier" {
+  description = "PostgreSQL instance name (DB identifier)"
+  default = "{{ fqdn_id }}"
+  type = string
+}
+
+variable "postgresql_version" {
+  description = "PostgreSQL major version"
+  default = "{{ version }}"
+  type = string
+}
+
+variable "instance_class" {
+  description = "Type of instance: https://docs.digitalocean.com/products/databases/"
+  default = "{{database_instance_type}}"
+  type = string
+}
+
+variable "username" {
+  description = "Username for the DB user"
+  default = "{{ database_login }}"
+  type = string
+}
+
+variable "password" {
+  description = "Password for the DB user"
+  default = "{{ database_password }}"
+  sensitive = true
+  type = string
+}
+
+variable "database_name" {
+  description = "The name of the database to create when the DB cluster is created"
+  default = "{{ database_name }}"
+  type = string
+}
+
+# Network
+
+variable "publicly_accessible" {
+  description = "Instance publicly accessible"
+  default = {{ publicly_accessible }}
+  type = bool
+}
+
+{%- if resource_expiration_in_seconds is defined %}
+# Pleco ttl
+variable "resource_expiration_in_seconds" {
+  description = "Resource expiration in seconds"
+  default = {{ resource_expiration_in_seconds }}
+  type = number
+}
+{% endif %}
+
+# Clustering
+variable "activate_high_availability" {
+  description = "Define if DB should be in cluster mode"
+  default = {{ activate_high_availability }}
+  type = bool
+}
+
+# Read replicas
+variable "read_replicas_count" {
+  description = "Number of read replicas"
+  default = {{ read_replicas_count }}
+  type = number
+}
@@ -1,3 +1,10 @@
+# nodes join it, managed services attached to it are reachable from every node, including autoscaled ones
+resource "scaleway_vpc_private_network" "kubernetes_cluster" {
+  name   = var.kubernetes_cluster_name
+  region = var.region
+  tags   = local.tags_ks_list
+}
+
 resource "scaleway_k8s_cluster" "kubernetes_cluster"  {
   name    = var.kubernetes_cluster_name
   version = var.scaleway_ks_version
@@ -5,6 +12,8 @@ resource "scaleway_k8s_cluster" "kubernetes_cluster"  {
 
   region  = var.region
 
+  private_network_id = scaleway_vpc_private_network.kubernetes_cluster.id
+
   tags    = local.tags_ks_list
 
   autoscaler_config {
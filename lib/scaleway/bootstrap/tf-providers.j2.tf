@@ -9,7 +9,7 @@ terraform {
   required_providers {
     scaleway = {
       source = "scaleway/scaleway"
-      version = "~> 2.2.0"
+      version = "~> 2.31"
     }
     aws = {
       source = "hashicorp/aws"
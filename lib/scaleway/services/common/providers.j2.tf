@@ -2,7 +2,7 @@ terraform {
   required_providers {
     scaleway = {
       source = "scaleway/scaleway"
-      version = "~> 2.1.0"
+      version = "~> 2.31"
     }
     local = {
       source = "hashicorp/local"
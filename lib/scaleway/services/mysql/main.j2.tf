@@ -86,3 +86,55 @@ resource "scaleway_rdb_database" "mysql_main" {
   instance_id    = scaleway_rdb_instance.mysql_instance.id
   name           = var.database_name
 }
+
+{%- if read_replicas_enabled %}
+resource "scaleway_rdb_read_replica" "mysql_read_replica" {
+  count       = var.read_replicas_count
+  instance_id = scaleway_rdb_instance.mysql_instance.id
+  region      = var.region
+
+  direct_access {}
+
+  depends_on = [
+    scaleway_rdb_database.mysql_main
+  ]
+}
+
+# Replicas are only reachable through their direct access endpoint, exposed in the cluster with the read fqdn
+resource "helm_release" "mysql_read_replica_external_name" {
+  name = "${scaleway_rdb_instance.mysql_instance.id}-read-externalname"
+  chart = "external-name-svc"
+  namespace = "{{namespace}}"
+  atomic = true
+  max_history = 50
+
+  set {
+    name = "target_ip"
+    value = scaleway_rdb_read_replica.mysql_read_replica[0].direct_access[0].ip
+  }
+  set {
+    name = "target_port"
+    value = scaleway_rdb_read_replica.mysql_read_replica[0].direct_access[0].port
+  }
+  set {
+    name = "port"
+    value = var.port
+  }
+  set {
+    name = "source_fqdn"
+    value = "{{database_read_fqdn}}"
+  }
+  set {
+    name = "app_id"
+    value = "{{database_id}}"
+  }
+  set {
+    name = "service_name"
+    value = "{{service_name}}-read"
+  }
+
+  depends_on = [
+    scaleway_rdb_read_replica.mysql_read_replica
+  ]
+}
+{%- endif %}
@@ -199,4 +199,11 @@ variable "activate_high_availability" {
   description = "Define if DB should be in cluster mode"
   default = {{ activate_high_availability }}
   type = bool
-}
\ No newline at end of file
+}
+
+# Read replicas
+variable "read_replicas_count" {
+  description = "Number of read replicas"
+  default = {{ read_replicas_count }}
+  type = number
+}
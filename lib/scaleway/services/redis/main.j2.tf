@@ -0,0 +1,80 @@
+locals {
+  tags_redis = {
+    cluster_name = var.cluster_name
+    cluster_id = var.kubernetes_cluster_id
+    region = var.region
+    q_client_id = var.q_customer_id
+    q_environment_id = var.q_environment_id
+    q_project_id = var.q_project_id
+    database_identifier = var.redis_identifier
+    {% if resource_expiration_in_seconds is defined %}ttl = var.resource_expiration_in_seconds{% endif %}
+  }
+  tags_redis_list = [for i, v in local.tags_redis : "${i}=${v}"] # NOTE: Scaleway doesn't support KV style tags
+}
+
+resource "scaleway_redis_cluster" "redis_cluster" {
+  name         = var.database_name
+  version      = var.redis_version
+  node_type    = var.instance_class
+  cluster_size = var.activate_high_availability ? 3 : 1
+  tls_enabled  = true
+
+  user_name    = var.username
+  password     = var.password
+
+  zone         = var.zone
+
+  tags         = local.tags_redis_list
+
+  {%- if publicly_accessible %}
+  acl {
+    ip          = "0.0.0.0/0"
+    description = "accessible from any host"
+  }
+  {%- else %}
+  # the cluster nodes reach it through the cluster private network, whatever the nodes are
+  private_network {
+    id = data.scaleway_k8s_cluster.kubernetes_cluster.private_network_id
+  }
+  {%- endif %}
+}
+
+{%- if not publicly_accessible %}
+# The private endpoint only has an ip, exposed in the cluster with the fqdn
+resource "helm_release" "redis_cluster_external_name" {
+  name = "${scaleway_redis_cluster.redis_cluster.id}-externalname"
+  chart = "external-name-svc"
+  namespace = "{{namespace}}"
+  atomic = true
+  max_history = 50
+
+  set {
+    name = "target_ip"
+    value = split("/", tolist(scaleway_redis_cluster.redis_cluster.private_network)[0].service_ips[0])[0]
+  }
+  set {
+    name = "target_port"
+    value = var.port
+  }
+  set {
+    name = "port"
+    value = var.port
+  }
+  set {
+    name = "source_fqdn"
+    value = "{{database_fqdn}}"
+  }
+  set {
+    name = "app_id"
+    value = "{{database_id}}"
+  }
+  set {
+    name = "service_name"
+    value = "{{service_name}}"
+  }
+
+  depends_on = [
+    scaleway_redis_cluster.redis_cluster
+  ]
+}
+{%- endif %}
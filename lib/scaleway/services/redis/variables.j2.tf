@@ -0,0 +1,117 @@
+# Qovery
+
+variable "cluster_name" {
+  description = "Kubernetes cluster name"
+  default     = "{{ cluster_name }}"
+  type        = string
+}
+
+variable "region" {
+  description = "SCW region to store terraform state and lock"
+  default     = "{{ region }}"
+  type        = string
+}
+
+variable "zone" {
+  description = "SCW zone to store terraform state and lock"
+  default     = "{{ zone }}"
+  type        = string
+}
+
+variable "kubernetes_cluster_id" {
+  description = "Kubernetes cluster name with region"
+  default     = "{{ kubernetes_cluster_id }}"
+  type        = string
+}
+
+variable "region_cluster_name" {
+  description = "SCW region to store terraform state and lock"
+  default     = "{{ region }}-{{ cluster_name }}"
+  type        = string
+}
+
+variable "q_project_id" {
+  description = "Qovery project ID"
+  default     = "{{ project_id }}"
+  type        = string
+}
+
+variable "q_customer_id" {
+  description = "Qovery customer ID"
+  default     = "{{ owner_id }}"
+  type        = string
+}
+
+variable "q_environment_id" {
+  description = "Qovery client environment"
+  default     = "{{ environment_id }}"
+  type        = string
+}
+
+# Redis instance basics
+
+variable "redis_identifier" {
+  description = "Redis cluster name (Cluster identifier)"
+  default = "{{ fqdn_id }}"
+  type = string
+}
+
+variable "port" {
+  description = "Redis cluster port"
+  default = {{ database_port }}
+  type = number
+}
+
+variable "redis_version" {
+  description = "Redis version"
+  default = "{{ version }}"
+  type = string
+}
+
+variable "instance_class" {
+  description = "Type of instance: https://www.scaleway.com/fr/tarifs/"
+  default = "{{database_instance_type}}"
+  type = string
+}
+
+variable "username" {
+  description = "Admin username for the Redis cluster"
+  default = "{{ database_login }}"
+  type = string
+}
+
+variable "password" {
+  description = "Admin password for the Redis cluster"
+  default = "{{ database_password }}"
+  type = string
+}
+
+variable "database_name" {
+  description = "Name of the Redis cluster"
+  default = "{{ database_name }}"
+  type = string
+}
+
+# Network
+
+variable "publicly_accessible" {
+  description = "Instance publicly accessible"
+  default = {{ publicly_accessible }}
+  type = bool
+}
+
+{%- if resource_expiration_in_seconds is defined %}
+# Pleco ttl
+variable "resource_expiration_in_seconds" {
+  description = "Resource expiration in seconds"
+  default = {{ resource_expiration_in_seconds }}
+  type = number
+}
+{% endif %}
+
+# Clustering
+variable "activate_high_availability" {
+  description = "Define if Redis should be in cluster mode"
+  default = {{ activate_high_availability }}
+  type = bool
+}
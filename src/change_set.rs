@@ -0,0 +1,196 @@
+use crate::cmd::terraform::{TerraformPlanOutput, TerraformResourceChange};
+use serde::Serialize;
+
+/// Everything a transaction would change, computed without mutating anything
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct TransactionChangeSet {
+    pub environments: Vec<EnvironmentChangeSet>,
+}
+
+impl TransactionChangeSet {
+    pub fn environment_mut(&mut self, environment_id: &str) -> &mut EnvironmentChangeSet {
+        let idx = match self
+            .environments
+            .iter()
+            .position(|env| env.environment_id == environment_id)
+        {
+            Some(idx) => idx,
+            None => {
+                self.environments.push(EnvironmentChangeSet {
+                    environment_id: environment_id.to_string(),
+                    ..Default::default()
+                });
+                self.environments.len() - 1
+            }
+        };
+
+        &mut self.environments[idx]
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.environments.iter().any(|env| env.has_changes())
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct EnvironmentChangeSet {
+    pub environment_id: String,
+    pub namespaces: Vec<NamespaceChange>,
+    pub images_to_build: Vec<ImageToBuild>,
+    pub services: Vec<ServiceChangeSet>,
+}
+
+impl EnvironmentChangeSet {
+    pub fn has_changes(&self) -> bool {
+        self.namespaces.iter().any(|ns| ns.action != ChangeAction::NoOp)
+            || !self.images_to_build.is_empty()
+            || self.services.iter().any(|service| service.action != ChangeAction::NoOp)
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Pause,
+    Delete,
+    NoOp,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct NamespaceChange {
+    pub name: String,
+    pub action: ChangeAction,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ImageToBuild {
+    pub application_id: String,
+    pub image_name: String,
+    pub reason: ImageBuildReason,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageBuildReason {
+    ForceBuild,
+    NotInRegistry,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ServiceChangeSet {
+    pub service_id: String,
+    pub service_name: String,
+    pub service_type: String,
+    pub action: ChangeAction,
+    pub helm_release: Option<HelmReleaseDiff>,
+    pub terraform_plan: Option<TerraformPlan>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HelmReleaseDiff {
+    pub release_name: String,
+    pub namespace: String,
+    /// output of `helm diff upgrade`, empty if the release is up to date
+    pub diff: String,
+}
+
+impl HelmReleaseDiff {
+    pub fn has_changes(&self) -> bool {
+        !self.diff.trim().is_empty()
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TerraformPlan {
+    pub resource_changes: Vec<TerraformResourceChange>,
+}
+
+impl TerraformPlan {
+    pub fn has_changes(&self) -> bool {
+        self.resource_changes.iter().any(|change| !change.is_no_op())
+    }
+
+    /// Action the whole plan stands for: nothing, a creation from scratch, a destruction or an update
+    pub fn change_action(&self) -> ChangeAction {
+        let changes = self
+            .resource_changes
+            .iter()
+            .filter(|change| !change.is_no_op())
+            .collect::<Vec<_>>();
+
+        if changes.is_empty() {
+            return ChangeAction::NoOp;
+        }
+
+        let only = |action: &str| {
+            changes
+                .iter()
+                .all(|change| change.change.actions.len() == 1 && change.change.actions[0] == action)
+        };
+
+        if only("delete") {
+            ChangeAction::Delete
+        } else if only("create") && changes.len() == self.resource_changes.len() {
+            ChangeAction::Create
+        } else {
+            ChangeAction::Update
+        }
+    }
+}
+
+impl From<TerraformPlanOutput> for TerraformPlan {
+    fn from(output: TerraformPlanOutput) -> Self {
+        TerraformPlan {
+            resource_changes: output.resource_changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change_set::{ChangeAction, TerraformPlan};
+    use crate::cmd::terraform::{TerraformChange, TerraformResourceChange};
+
+    fn resource_change(name: &str, actions: &[&str]) -> TerraformResourceChange {
+        TerraformResourceChange {
+            address: format!("digitalocean_database_cluster.{}", name),
+            resource_type: "digitalocean_database_cluster".to_string(),
+            name: name.to_string(),
+            change: TerraformChange {
+                actions: actions.iter().map(|action| action.to_string()).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_terraform_plan_change_action() {
+        let plan = |changes: Vec<TerraformResourceChange>| TerraformPlan {
+            resource_changes: changes,
+        };
+
+        assert_eq!(plan(vec![]).change_action(), ChangeAction::NoOp);
+        assert_eq!(
+            plan(vec![resource_change("a", &["no-op"]), resource_change("b", &["read"])]).change_action(),
+            ChangeAction::NoOp
+        );
+        assert_eq!(
+            plan(vec![resource_change("a", &["create"]), resource_change("b", &["create"])]).change_action(),
+            ChangeAction::Create
+        );
+        assert_eq!(
+            plan(vec![resource_change("a", &["no-op"]), resource_change("b", &["create"])]).change_action(),
+            ChangeAction::Update
+        );
+        assert_eq!(
+            plan(vec![resource_change("a", &["delete", "create"])]).change_action(),
+            ChangeAction::Update
+        );
+        assert_eq!(
+            plan(vec![resource_change("a", &["delete"]), resource_change("b", &["delete"])]).change_action(),
+            ChangeAction::Delete
+        );
+        assert!(!plan(vec![resource_change("a", &["no-op"])]).has_changes());
+    }
+}
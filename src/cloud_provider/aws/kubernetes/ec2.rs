@@ -8,6 +8,7 @@ use crate::cloud_provider::kubernetes::{send_progress_on_long_task, Kind, Kubern
 use crate::cloud_provider::models::{InstanceEc2, NodeGroups};
 use crate::cloud_provider::utilities::print_action;
 use crate::cloud_provider::CloudProvider;
+use crate::cmd::terraform::TerraformDrift;
 use crate::dns_provider::DnsProvider;
 use crate::errors::EngineError;
 use crate::events::{EngineEvent, EnvironmentStep, InfrastructureStep, Stage};
@@ -401,6 +402,16 @@ impl Kubernetes for EC2 {
         );
         Ok(())
     }
+
+    fn detect_drift(&self) -> Result<Vec<TerraformDrift>, EngineError> {
+        kubernetes::detect_drift(
+            self,
+            self.template_directory.as_str(),
+            &self.zones,
+            &[self.node_group_from_instance_type()],
+            &self.options,
+        )
+    }
 }
 
 impl Listen for EC2 {
@@ -263,6 +263,15 @@ pub fn ec2_aws_helm_charts(
         },
     };
 
+    // controller of internal routers, behind a load balancer only reachable from inside the VPC
+    let nginx_ingress_internal = CommonChart {
+        chart_info: ChartInfo {
+            name: "nginx-ingress-internal".to_string(),
+            values_files: vec![chart_path("chart_values/nginx-ingress-internal.yaml")],
+            ..nginx_ingress.chart_info.clone()
+        },
+    };
+
     let cluster_agent_context = ClusterAgentContext {
         api_url: &chart_config_prerequisites.infra_options.qovery_api_url,
         api_token: &chart_config_prerequisites.infra_options.agent_version_controller_token,
@@ -378,7 +387,7 @@ pub fn ec2_aws_helm_charts(
 
     let level_5: Vec<Box<dyn HelmChart>> = vec![Box::new(external_dns), Box::new(metrics_server)];
 
-    let level_6: Vec<Box<dyn HelmChart>> = vec![Box::new(nginx_ingress)];
+    let level_6: Vec<Box<dyn HelmChart>> = vec![Box::new(nginx_ingress), Box::new(nginx_ingress_internal)];
 
     let level_7: Vec<Box<dyn HelmChart>> = vec![
         Box::new(cert_manager_config),
@@ -11,7 +11,7 @@ use crate::cloud_provider::models::NodeGroups;
 use crate::cloud_provider::utilities::print_action;
 use crate::cloud_provider::CloudProvider;
 use crate::cmd::kubectl::{kubectl_exec_scale_replicas, ScalingKind};
-use crate::cmd::terraform::terraform_init_validate_plan_apply;
+use crate::cmd::terraform::{terraform_init_validate_plan_apply, TerraformDrift};
 use crate::dns_provider::DnsProvider;
 use crate::errors::EngineError;
 use crate::events::Stage::Infrastructure;
@@ -696,6 +696,16 @@ impl Kubernetes for EKS {
         );
         Ok(())
     }
+
+    fn detect_drift(&self) -> Result<Vec<TerraformDrift>, EngineError> {
+        kubernetes::detect_drift(
+            self,
+            self.template_directory.as_str(),
+            &self.zones,
+            &self.nodes_groups,
+            &self.options,
+        )
+    }
 }
 
 impl Listen for EKS {
@@ -847,6 +847,15 @@ datasources:
         },
     };
 
+    // controller of internal routers, behind a load balancer only reachable from inside the VPC
+    let nginx_ingress_internal = CommonChart {
+        chart_info: ChartInfo {
+            name: "nginx-ingress-internal".to_string(),
+            values_files: vec![chart_path("chart_values/nginx-ingress-internal.yaml")],
+            ..nginx_ingress.chart_info.clone()
+        },
+    };
+
     let pleco = CommonChart {
         chart_info: ChartInfo {
             name: "pleco".to_string(),
@@ -1176,7 +1185,7 @@ datasources:
         Box::new(external_dns),
     ];
 
-    let mut level_6: Vec<Box<dyn HelmChart>> = vec![Box::new(nginx_ingress)];
+    let mut level_6: Vec<Box<dyn HelmChart>> = vec![Box::new(nginx_ingress), Box::new(nginx_ingress_internal)];
 
     let mut level_7: Vec<Box<dyn HelmChart>> = vec![
         Box::new(cert_manager_config),
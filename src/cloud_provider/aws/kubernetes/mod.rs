@@ -16,7 +16,7 @@ use crate::cloud_provider::aws::kubernetes::roles::get_default_roles_to_create;
 use crate::cloud_provider::aws::regions::{AwsRegion, AwsZones};
 use crate::cloud_provider::helm::{deploy_charts_levels, ChartInfo};
 use crate::cloud_provider::kubernetes::{
-    is_kubernetes_upgrade_required, uninstall_cert_manager, Kind, Kubernetes, ProviderOptions,
+    detect_terraform_drift, is_kubernetes_upgrade_required, uninstall_cert_manager, Kind, Kubernetes, ProviderOptions,
 };
 use crate::cloud_provider::models::{NodeGroups, NodeGroupsFormat};
 use crate::cloud_provider::qovery::EngineLocation;
@@ -25,7 +25,10 @@ use crate::cloud_provider::CloudProvider;
 use crate::cmd;
 use crate::cmd::helm::{to_engine_error, Helm};
 use crate::cmd::kubectl::{kubectl_exec_api_custom_metrics, kubectl_exec_get_all_namespaces, kubectl_exec_get_events};
-use crate::cmd::terraform::{terraform_exec, terraform_init_validate_plan_apply, terraform_init_validate_state_list};
+use crate::cmd::terraform::{
+    terraform_exec, terraform_init_validate_plan_apply_with_progress, terraform_init_validate_state_list,
+    TerraformDrift,
+};
 use crate::deletion_utilities::{get_firsts_namespaces_to_delete, get_qovery_managed_namespaces};
 use crate::dns_provider::DnsProvider;
 use crate::errors::{CommandError, EngineError, ErrorMessageVerbosity};
@@ -616,7 +619,11 @@ fn create(
     );
 
     // terraform deployment dedicated to cloud resources
-    if let Err(e) = terraform_init_validate_plan_apply(temp_dir.as_str(), kubernetes.context().is_dry_run_deploy()) {
+    if let Err(e) = terraform_init_validate_plan_apply_with_progress(
+        temp_dir.as_str(),
+        kubernetes.context().is_dry_run_deploy(),
+        &|applied_changes| kubernetes.send_terraform_progress(applied_changes, &listeners_helper),
+    ) {
         return Err(EngineError::new_terraform_error_while_executing_pipeline(event_details, e));
     }
 
@@ -761,6 +768,17 @@ fn create(
     .map_err(|e| EngineError::new_helm_charts_deploy_error(event_details.clone(), e))
 }
 
+fn detect_drift(
+    kubernetes: &dyn Kubernetes,
+    template_directory: &str,
+    aws_zones: &[AwsZones],
+    node_groups: &[NodeGroups],
+    options: &Options,
+) -> Result<Vec<TerraformDrift>, EngineError> {
+    let context = tera_context(kubernetes, aws_zones, node_groups, options)?;
+    detect_terraform_drift(kubernetes, template_directory, context)
+}
+
 fn create_error(kubernetes: &dyn Kubernetes) -> Result<(), EngineError> {
     let event_details = kubernetes.get_event_details(Stage::Infrastructure(InfrastructureStep::Create));
     let (kubeconfig_path, _) = kubernetes.get_kubeconfig_file()?;
@@ -1310,7 +1328,7 @@ fn delete(
         )),
         Err(retry::Error::Internal(msg)) => Err(EngineError::new_terraform_error_while_executing_destroy_pipeline(
             event_details,
-            CommandError::new("Error while trying to perform Terraform destroy".to_string(), Some(msg), None),
+            CommandError::new("Error while trying to perform Terraform destroy".to_string(), Some(msg), None).into(),
         )),
     }
 }
@@ -660,6 +660,15 @@ datasources:
         },
     };
 
+    // controller of internal routers, behind a load balancer only reachable from inside the VPC
+    let nginx_ingress_internal = CommonChart {
+        chart_info: ChartInfo {
+            name: "nginx-ingress-internal".to_string(),
+            values_files: vec![chart_path("chart_values/nginx-ingress-internal.yaml")],
+            ..nginx_ingress.chart_info.clone()
+        },
+    };
+
     let digital_mobius = CommonChart {
         chart_info: ChartInfo {
             name: "digital-mobius".to_string(),
@@ -1011,7 +1020,7 @@ datasources:
 
     let mut level_4: Vec<Box<dyn HelmChart>> = vec![Box::new(metrics_server), Box::new(external_dns)];
 
-    let mut level_5: Vec<Box<dyn HelmChart>> = vec![Box::new(nginx_ingress)];
+    let mut level_5: Vec<Box<dyn HelmChart>> = vec![Box::new(nginx_ingress), Box::new(nginx_ingress_internal)];
 
     let mut level_6: Vec<Box<dyn HelmChart>> = vec![
         Box::new(cert_manager_config),
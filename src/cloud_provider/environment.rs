@@ -1,6 +1,7 @@
 use crate::cloud_provider::service::{Action, DatabaseService, RouterService, StatefulService, StatelessService};
 use crate::models::application::ApplicationService;
 use crate::utilities::to_short_id;
+use std::rc::Rc;
 use uuid::Uuid;
 
 pub struct Environment {
@@ -16,6 +17,7 @@ pub struct Environment {
     pub applications: Vec<Box<dyn ApplicationService>>,
     pub routers: Vec<Box<dyn RouterService>>,
     pub databases: Vec<Box<dyn DatabaseService>>,
+    is_canceled: Rc<dyn Fn() -> bool>,
 }
 
 impl Environment {
@@ -43,9 +45,19 @@ impl Environment {
             applications,
             routers,
             databases,
+            is_canceled: Rc::new(|| false),
         }
     }
 
+    /// Long waits of services (i.e: a release analysis) stop once the deployment is canceled
+    pub fn set_is_canceled(&mut self, is_canceled: Rc<dyn Fn() -> bool>) {
+        self.is_canceled = is_canceled;
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        (self.is_canceled)()
+    }
+
     pub fn stateless_services(&self) -> Vec<&dyn StatelessService> {
         let mut stateless_services: Vec<&dyn StatelessService> =
             Vec::with_capacity(self.applications.len() + self.routers.len());
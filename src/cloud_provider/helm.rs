@@ -958,6 +958,16 @@ pub fn get_chart_for_cert_manager_config(
             })
         }
         DnsProviderConfiguration::QoveryDns(_) => {}
+        DnsProviderConfiguration::Route53(x) => {
+            cert_manager_config.chart_info.values.push(ChartSetValue {
+                key: "provider.route53.accessKeyId".to_string(),
+                value: x.access_key_id.clone(),
+            });
+            cert_manager_config.chart_info.values.push(ChartSetValue {
+                key: "provider.route53.secretAccessKey".to_string(),
+                value: x.secret_access_key.clone(),
+            })
+        }
     };
 
     cert_manager_config
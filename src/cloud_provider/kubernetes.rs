@@ -13,12 +13,14 @@ use retry::delay::{Fibonacci, Fixed};
 use retry::Error::Operation;
 use retry::OperationResult;
 use serde::{Deserialize, Serialize};
+use tera::Context as TeraContext;
 use tokio::fs;
 
+use crate::change_set::{ChangeAction, EnvironmentChangeSet, NamespaceChange};
 use crate::cloud_provider::aws::regions::AwsZones;
 use crate::cloud_provider::environment::Environment;
 use crate::cloud_provider::models::{CpuLimits, InstanceEc2, NodeGroups};
-use crate::cloud_provider::service::CheckAction;
+use crate::cloud_provider::service::{CheckAction, Service};
 use crate::cloud_provider::{service, CloudProvider, DeploymentTarget};
 use crate::cmd::kubectl;
 use crate::cmd::kubectl::{
@@ -27,6 +29,7 @@ use crate::cmd::kubectl::{
     kubernetes_get_all_pdbs,
 };
 use crate::cmd::structs::KubernetesNodeCondition;
+use crate::cmd::terraform::{terraform_init_validate_detect_drift, TerraformDrift};
 use crate::dns_provider::DnsProvider;
 use crate::errors::{CommandError, EngineError, ErrorMessageVerbosity};
 use crate::events::Stage::Infrastructure;
@@ -34,7 +37,8 @@ use crate::events::{EngineEvent, EventDetails, EventMessage, GeneralStep, Infras
 use crate::fs::workspace_directory;
 use crate::io_models::ProgressLevel::Info;
 use crate::io_models::{
-    Action, Context, Listen, ListenersHelper, ProgressInfo, ProgressLevel, ProgressScope, QoveryIdentifier, StringPath,
+    Action, Context, Listen, ListenersHelper, ProgressCounter, ProgressInfo, ProgressLevel, ProgressScope,
+    ProgressSubTask, QoveryIdentifier, StringPath,
 };
 use crate::logger::Logger;
 use crate::models::types::VersionsNumber;
@@ -84,9 +88,13 @@ pub trait Kubernetes: Listen {
         format!("{}.yaml", self.id())
     }
 
+    fn kubeconfig_bucket_name(&self) -> String {
+        format!("qovery-kubeconfigs-{}", self.id())
+    }
+
     fn get_kubeconfig_file(&self) -> Result<(String, File), EngineError> {
         let event_details = self.get_event_details(Infrastructure(InfrastructureStep::LoadConfiguration));
-        let bucket_name = format!("qovery-kubeconfigs-{}", self.id());
+        let bucket_name = self.kubeconfig_bucket_name();
         let object_key = self.get_kubeconfig_filename();
         let stage = Stage::General(GeneralStep::RetrieveClusterConfig);
 
@@ -303,6 +311,8 @@ pub trait Kubernetes: Listen {
     fn pause_environment_error(&self, environment: &Environment) -> Result<(), EngineError>;
     fn delete_environment(&self, environment: &Environment) -> Result<(), EngineError>;
     fn delete_environment_error(&self, environment: &Environment) -> Result<(), EngineError>;
+    /// Report cluster cloud resources which drifted from their Terraform templates, without applying anything
+    fn detect_drift(&self) -> Result<Vec<TerraformDrift>, EngineError>;
 
     fn send_to_customer(&self, message: &str, listeners_helper: &ListenersHelper) {
         listeners_helper.upgrade_in_progress(ProgressInfo::new(
@@ -315,6 +325,20 @@ pub trait Kubernetes: Listen {
         ))
     }
 
+    fn send_terraform_progress(&self, applied_changes: ProgressCounter, listeners_helper: &ListenersHelper) {
+        listeners_helper.upgrade_in_progress(
+            ProgressInfo::new(
+                ProgressScope::Infrastructure {
+                    execution_id: self.context().execution_id().to_string(),
+                },
+                ProgressLevel::Info,
+                Some(format!("Terraform: {} resources", applied_changes)),
+                self.context().execution_id(),
+            )
+            .with_sub_task(ProgressSubTask::new("terraform", Some(applied_changes))),
+        )
+    }
+
     fn get_temp_dir(&self, event_details: EventDetails) -> Result<String, EngineError> {
         workspace_directory(
             self.context().workspace_root_dir(),
@@ -441,6 +465,7 @@ pub fn deploy_environment(
 
     // create all stateful services (database)
     for service in environment.stateful_services() {
+        let _span = service_action_span(service, "deploy").entered();
         let _ = service::check_kubernetes_service_error(
             service.exec_action(&stateful_deployment_target),
             kubernetes,
@@ -478,6 +503,7 @@ pub fn deploy_environment(
 
     // create all stateless services (router, application...)
     for service in environment.stateless_services() {
+        let _span = service_action_span(service, "deploy").entered();
         let _ = service::check_kubernetes_service_error(
             service.exec_action(&stateless_deployment_target),
             kubernetes,
@@ -496,6 +522,7 @@ pub fn deploy_environment(
 
     // check all deployed services
     for service in environment.stateful_services() {
+        let _span = service_action_span(service, "check_deploy").entered();
         let _ = service::check_kubernetes_service_error(
             service.exec_check_action(),
             kubernetes,
@@ -513,6 +540,7 @@ pub fn deploy_environment(
     thread::sleep(std::time::Duration::from_millis(100));
 
     for service in environment.stateless_services() {
+        let _span = service_action_span(service, "check_deploy").entered();
         let _ = service::check_kubernetes_service_error(
             service.exec_check_action(),
             kubernetes,
@@ -529,6 +557,139 @@ pub fn deploy_environment(
     Ok(())
 }
 
+/// span grouping everything happening while running an action on a single service
+fn service_action_span<T: Service + ?Sized>(service: &T, action: &str) -> tracing::Span {
+    info_span!(
+        "service_action",
+        service_id = service.id(),
+        service_name = service.name(),
+        service_type = service.service_type().name().as_str(),
+        action = action,
+    )
+}
+
+/// Render the cluster Terraform templates into a temporary directory and report the resources which drifted from them
+pub fn detect_terraform_drift(
+    kubernetes: &dyn Kubernetes,
+    template_directory: &str,
+    context: TeraContext,
+) -> Result<Vec<TerraformDrift>, EngineError> {
+    let event_details = kubernetes.get_event_details(Infrastructure(InfrastructureStep::DetectDrift));
+    let temp_dir = kubernetes.get_temp_dir(event_details.clone())?;
+
+    if let Err(e) =
+        crate::template::generate_and_copy_all_files_into_dir(template_directory, temp_dir.as_str(), context)
+    {
+        return Err(EngineError::new_cannot_copy_files_from_one_directory_to_another(
+            event_details,
+            template_directory.to_string(),
+            temp_dir,
+            e,
+        ));
+    }
+
+    // bootstrap charts are required by the templates, see cluster creation
+    let bootstrap_charts_dir = format!("{}/common/bootstrap/charts", kubernetes.context().lib_root_dir());
+    let common_charts_temp_dir = format!("{}/common/charts", temp_dir.as_str());
+    if let Err(e) = crate::template::copy_non_template_files(&bootstrap_charts_dir, common_charts_temp_dir.as_str()) {
+        return Err(EngineError::new_cannot_copy_files_from_one_directory_to_another(
+            event_details,
+            bootstrap_charts_dir,
+            common_charts_temp_dir,
+            e,
+        ));
+    }
+
+    let drifts = terraform_init_validate_detect_drift(temp_dir.as_str())
+        .map_err(|e| EngineError::new_terraform_error_while_executing_pipeline(event_details.clone(), e))?;
+    log_terraform_drifts(&drifts, kubernetes.name_with_id().as_str(), event_details, kubernetes.logger());
+
+    Ok(drifts)
+}
+
+/// Log one warning per drifted resource
+pub fn log_terraform_drifts(
+    drifts: &[TerraformDrift],
+    target_name: &str,
+    event_details: EventDetails,
+    logger: &dyn Logger,
+) {
+    if drifts.is_empty() {
+        logger.log(EngineEvent::Info(
+            event_details,
+            EventMessage::new_from_safe(format!("No drift detected on {}", target_name)),
+        ));
+        return;
+    }
+
+    for drift in drifts {
+        logger.log(EngineEvent::Warning(
+            event_details.clone(),
+            EventMessage::new_from_safe(format!(
+                "Drift detected on {}: resource `{}` ({}) has been changed outside of Qovery ({})",
+                target_name, drift.address, drift.resource_type, drift.action
+            )),
+        ));
+    }
+}
+
+/// Compute what deploying, pausing or deleting the environment would change, without changing anything
+pub fn plan_environment(
+    kubernetes: &dyn Kubernetes,
+    environment: &Environment,
+    event_details: EventDetails,
+    logger: &dyn Logger,
+) -> Result<EnvironmentChangeSet, EngineError> {
+    let deployment_target = DeploymentTarget {
+        kubernetes,
+        environment,
+    };
+
+    let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+    let namespace_exists = kubectl::kubectl_exec_is_namespace_present(
+        kubernetes_config_file_path.as_str(),
+        environment.namespace(),
+        kubernetes.cloud_provider().credentials_environment_variables(),
+    );
+    let namespace_action = match (&environment.action, namespace_exists) {
+        (service::Action::Create, false) => ChangeAction::Create,
+        (service::Action::Delete, true) => ChangeAction::Delete,
+        _ => ChangeAction::NoOp,
+    };
+
+    let mut change_set = EnvironmentChangeSet {
+        environment_id: environment.id.clone(),
+        namespaces: vec![NamespaceChange {
+            name: environment.namespace().to_string(),
+            action: namespace_action,
+        }],
+        ..Default::default()
+    };
+
+    for service in environment.stateful_services() {
+        change_set.services.push(service.plan_action(&deployment_target)?);
+    }
+
+    for service in environment.stateless_services() {
+        change_set.services.push(service.plan_action(&deployment_target)?);
+    }
+
+    logger.log(EngineEvent::Info(
+        event_details,
+        EventMessage::new_from_safe(format!(
+            "Environment {} plan: {} service(s) to change",
+            environment.id,
+            change_set
+                .services
+                .iter()
+                .filter(|service| service.action != ChangeAction::NoOp)
+                .count()
+        )),
+    ));
+
+    Ok(change_set)
+}
+
 /// common function to react to an error when a environment deployment goes wrong
 pub fn deploy_environment_error(
     kubernetes: &dyn Kubernetes,
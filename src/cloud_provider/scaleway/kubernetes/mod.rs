@@ -5,8 +5,8 @@ use crate::cloud_provider::aws::regions::AwsZones;
 use crate::cloud_provider::environment::Environment;
 use crate::cloud_provider::helm::{deploy_charts_levels, ChartInfo};
 use crate::cloud_provider::kubernetes::{
-    is_kubernetes_upgrade_required, send_progress_on_long_task, uninstall_cert_manager, Kind, Kubernetes,
-    KubernetesUpgradeStatus, ProviderOptions,
+    detect_terraform_drift, is_kubernetes_upgrade_required, send_progress_on_long_task, uninstall_cert_manager, Kind,
+    Kubernetes, KubernetesUpgradeStatus, ProviderOptions,
 };
 use crate::cloud_provider::models::{NodeGroups, NodeGroupsFormat};
 use crate::cloud_provider::qovery::EngineLocation;
@@ -17,7 +17,10 @@ use crate::cloud_provider::{kubernetes, CloudProvider};
 use crate::cmd;
 use crate::cmd::helm::{to_engine_error, Helm};
 use crate::cmd::kubectl::{kubectl_exec_api_custom_metrics, kubectl_exec_get_all_namespaces, kubectl_exec_get_events};
-use crate::cmd::terraform::{terraform_exec, terraform_init_validate_plan_apply, terraform_init_validate_state_list};
+use crate::cmd::terraform::{
+    terraform_exec, terraform_init_validate_plan_apply, terraform_init_validate_plan_apply_with_progress,
+    terraform_init_validate_state_list, TerraformDrift,
+};
 use crate::deletion_utilities::{get_firsts_namespaces_to_delete, get_qovery_managed_namespaces};
 use crate::dns_provider::DnsProvider;
 use crate::errors::{CommandError, EngineError, ErrorMessageVerbosity};
@@ -412,10 +415,6 @@ impl Kapsule {
         Ok(())
     }
 
-    fn kubeconfig_bucket_name(&self) -> String {
-        format!("qovery-kubeconfigs-{}", self.id())
-    }
-
     fn get_engine_location(&self) -> EngineLocation {
         self.options.qovery_engine_location.clone()
     }
@@ -719,7 +718,11 @@ impl Kapsule {
         }
 
         // terraform deployment dedicated to cloud resources
-        if let Err(e) = terraform_init_validate_plan_apply(temp_dir.as_str(), self.context.is_dry_run_deploy()) {
+        if let Err(e) = terraform_init_validate_plan_apply_with_progress(
+            temp_dir.as_str(),
+            self.context.is_dry_run_deploy(),
+            &|applied_changes| self.send_terraform_progress(applied_changes, &listeners_helper),
+        ) {
             return Err(EngineError::new_terraform_error_while_executing_pipeline(event_details, e));
         }
 
@@ -1486,7 +1489,7 @@ impl Kapsule {
             )),
             Err(retry::Error::Internal(msg)) => Err(EngineError::new_terraform_error_while_executing_destroy_pipeline(
                 event_details,
-                CommandError::new("Error while performing Terraform destroy.".to_string(), Some(msg), None),
+                CommandError::new("Error while performing Terraform destroy.".to_string(), Some(msg), None).into(),
             )),
         }
     }
@@ -1898,6 +1901,11 @@ impl Kubernetes for Kapsule {
         );
         Ok(())
     }
+
+    fn detect_drift(&self) -> Result<Vec<TerraformDrift>, EngineError> {
+        let context = self.tera_context()?;
+        detect_terraform_drift(self, self.template_directory.as_str(), context)
+    }
 }
 
 impl Listen for Kapsule {
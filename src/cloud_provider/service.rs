@@ -8,9 +8,11 @@ use std::time::Duration;
 use tera::Context as TeraContext;
 use uuid::Uuid;
 
+use crate::change_set::{ChangeAction, HelmReleaseDiff, ServiceChangeSet, TerraformPlan};
 use crate::cloud_provider::environment::Environment;
 use crate::cloud_provider::helm::ChartInfo;
-use crate::cloud_provider::kubernetes::Kubernetes;
+use crate::cloud_provider::kubernetes::{log_terraform_drifts, Kubernetes};
+use crate::cloud_provider::models::{DatabaseConnectionInfos, Route};
 use crate::cloud_provider::utilities::check_domain_for;
 use crate::cloud_provider::DeploymentTarget;
 use crate::cmd;
@@ -18,12 +20,13 @@ use crate::cmd::helm;
 use crate::cmd::kubectl::ScalingKind::Statefulset;
 use crate::cmd::kubectl::{kubectl_exec_delete_secret, kubectl_exec_scale_replicas_by_selector, ScalingKind};
 use crate::cmd::structs::LabelsContent;
+use crate::cmd::terraform::TerraformDrift;
 use crate::errors::{CommandError, EngineError};
 use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, Stage, ToTransmitter};
 use crate::io_models::ProgressLevel::Info;
 use crate::io_models::{
-    Context, DatabaseMode, Listen, Listeners, ListenersHelper, ProgressInfo, ProgressLevel, ProgressScope,
-    QoveryIdentifier,
+    Context, CustomDomainStatus, DatabaseMode, Listen, Listeners, ListenersHelper, ProgressInfo, ProgressLevel,
+    ProgressScope, QoveryIdentifier,
 };
 use crate::logger::Logger;
 use crate::models::types::VersionsNumber;
@@ -119,6 +122,8 @@ pub trait Service: ToTransmitter {
 
 pub trait StatelessService: Service + Create + Pause + Delete {
     fn as_stateless_service(&self) -> &dyn StatelessService;
+    /// What `exec_action` would change, without changing anything
+    fn plan_action(&self, deployment_target: &DeploymentTarget) -> Result<ServiceChangeSet, EngineError>;
     fn exec_action(&self, deployment_target: &DeploymentTarget) -> Result<(), EngineError> {
         match self.action() {
             crate::cloud_provider::service::Action::Create => self.on_create(deployment_target),
@@ -140,6 +145,8 @@ pub trait StatelessService: Service + Create + Pause + Delete {
 
 pub trait StatefulService: Service + Create + Pause + Delete {
     fn as_stateful_service(&self) -> &dyn StatefulService;
+    /// What `exec_action` would change, without changing anything
+    fn plan_action(&self, deployment_target: &DeploymentTarget) -> Result<ServiceChangeSet, EngineError>;
     fn exec_action(&self, deployment_target: &DeploymentTarget) -> Result<(), EngineError> {
         match self.action() {
             crate::cloud_provider::service::Action::Create => self.on_create(deployment_target),
@@ -163,7 +170,12 @@ pub trait StatefulService: Service + Create + Pause + Delete {
 
 pub trait RouterService: StatelessService + Listen + Helm {
     fn domains(&self) -> Vec<&str>;
+    fn routes(&self) -> &[Route];
+    /// Class of the Ingresses of the router, selecting the ingress controller serving them
+    fn ingress_class(&self) -> &str;
     fn has_custom_domains(&self) -> bool;
+    /// Verification status of every custom domain, from its CNAME and the certificate served for it
+    fn custom_domains_status(&self, target: &DeploymentTarget) -> Result<Vec<CustomDomainStatus>, EngineError>;
     fn check_domains(&self, event_details: EventDetails, logger: &dyn Logger) -> Result<(), EngineError> {
         check_domain_for(
             ListenersHelper::new(self.listeners()),
@@ -178,6 +190,9 @@ pub trait RouterService: StatelessService + Listen + Helm {
 }
 
 pub trait DatabaseService: StatefulService {
+    fn connection_infos(&self, target: &DeploymentTarget) -> DatabaseConnectionInfos;
+    /// Managed database resources which drifted from their Terraform templates, always empty for containers
+    fn detect_drift(&self, target: &DeploymentTarget) -> Result<Vec<TerraformDrift>, EngineError>;
     fn check_domains(
         &self,
         listeners: Listeners,
@@ -250,10 +265,12 @@ pub struct DatabaseOptions {
     pub encrypt_disk: bool,
     pub activate_high_availability: bool,
     pub activate_backups: bool,
+    pub read_replicas_count: u32,
+    pub read_host: Option<String>,
     pub publicly_accessible: bool,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum DatabaseType {
     PostgreSQL,
     MongoDB,
@@ -370,13 +387,34 @@ where
 
 /// deploy a stateless service (app, router, database...) on Kubernetes
 pub fn deploy_stateless_service<T>(target: &DeploymentTarget, service: &T) -> Result<(), EngineError>
+where
+    T: Service + Helm,
+{
+    deploy_stateless_service_release(
+        target,
+        service,
+        service.helm_release_name(),
+        service.workspace_directory(),
+        service.tera_context(target)?,
+        service.selector(),
+    )
+}
+
+/// deploy one helm release of a stateless service and wait for the pods matching `selector` to be ready,
+/// a service can be made of several releases rendered from different contexts
+pub fn deploy_stateless_service_release<T>(
+    target: &DeploymentTarget,
+    service: &T,
+    helm_release_name: String,
+    workspace_dir: String,
+    tera_context: TeraContext,
+    selector: Option<String>,
+) -> Result<(), EngineError>
 where
     T: Service + Helm,
 {
     let kubernetes = target.kubernetes;
     let environment = target.environment;
-    let workspace_dir = service.workspace_directory();
-    let tera_context = service.tera_context(target)?;
     let event_details = service.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
 
     if let Err(e) = crate::template::generate_and_copy_all_files_into_dir(
@@ -392,7 +430,6 @@ where
         ));
     }
 
-    let helm_release_name = service.helm_release_name();
     let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
 
     // define labels to add to namespace
@@ -432,7 +469,7 @@ where
             _ => vec![],
         },
         false,
-        service.selector(),
+        selector.clone(),
     );
 
     helm.upgrade(&chart, &[])
@@ -441,13 +478,13 @@ where
     crate::cmd::kubectl::kubectl_exec_is_pod_ready_with_retry(
         kubernetes_config_file_path.as_str(),
         environment.namespace(),
-        service.selector().unwrap_or_default().as_str(),
+        selector.clone().unwrap_or_default().as_str(),
         kubernetes.cloud_provider().credentials_environment_variables(),
     )
     .map_err(|e| {
         EngineError::new_k8s_pod_not_ready(
             event_details.clone(),
-            service.selector().unwrap_or_default(),
+            selector.unwrap_or_default(),
             environment.namespace().to_string(),
             e,
         )
@@ -456,6 +493,204 @@ where
     Ok(())
 }
 
+fn service_change_set<T>(
+    service: &T,
+    action: ChangeAction,
+    helm_release: Option<HelmReleaseDiff>,
+    terraform_plan: Option<TerraformPlan>,
+) -> ServiceChangeSet
+where
+    T: Service + ?Sized,
+{
+    ServiceChangeSet {
+        service_id: service.id().to_string(),
+        service_name: service.name().to_string(),
+        service_type: service.service_type().name(),
+        action,
+        helm_release,
+        terraform_plan,
+    }
+}
+
+/// plan a service deployed with helm: render its charts into the workspace and diff them against the release
+pub fn plan_helm_service<T>(
+    target: &DeploymentTarget,
+    service: &T,
+    chart_dirs: &[String],
+    event_details: EventDetails,
+) -> Result<ServiceChangeSet, EngineError>
+where
+    T: Service + Helm,
+{
+    plan_helm_service_release(
+        target,
+        service,
+        service.helm_release_name(),
+        service.workspace_directory(),
+        chart_dirs,
+        &|| service.tera_context(target),
+        event_details,
+    )
+}
+
+/// plan one helm release of a service, `tera_context` renders the release charts when the service is deployed
+pub fn plan_helm_service_release<T>(
+    target: &DeploymentTarget,
+    service: &T,
+    helm_release_name: String,
+    workspace_dir: String,
+    chart_dirs: &[String],
+    tera_context: &dyn Fn() -> Result<TeraContext, EngineError>,
+    event_details: EventDetails,
+) -> Result<ServiceChangeSet, EngineError>
+where
+    T: Service + Helm,
+{
+    let kubernetes = target.kubernetes;
+    let environment = target.environment;
+    let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+
+    let helm = helm::Helm::new(
+        &kubernetes_config_file_path,
+        &kubernetes.cloud_provider().credentials_environment_variables(),
+    )
+    .map_err(|e| helm::to_engine_error(&event_details, e))?;
+    let chart = ChartInfo::new_from_custom_namespace(
+        helm_release_name,
+        workspace_dir.clone(),
+        environment.namespace().to_string(),
+        600_i64,
+        match service.service_type() {
+            ServiceType::Database(_) => vec![format!("{}/q-values.yaml", &workspace_dir)],
+            _ => vec![],
+        },
+        false,
+        service.selector(),
+    );
+    let release_exists = match helm.check_release_exist(&chart, &[]) {
+        Ok(_) => true,
+        Err(helm::HelmError::ReleaseDoesNotExist(_)) => false,
+        Err(e) => return Err(helm::to_engine_error(&event_details, e)),
+    };
+
+    let action = match service.action() {
+        Action::Nothing => ChangeAction::NoOp,
+        Action::Pause if release_exists => ChangeAction::Pause,
+        Action::Delete if release_exists => ChangeAction::Delete,
+        Action::Pause | Action::Delete => ChangeAction::NoOp,
+        Action::Create => {
+            let tera_context = tera_context()?;
+            for chart_dir in chart_dirs {
+                if let Err(e) = crate::template::generate_and_copy_all_files_into_dir(
+                    chart_dir,
+                    workspace_dir.as_str(),
+                    tera_context.clone(),
+                ) {
+                    return Err(EngineError::new_cannot_copy_files_from_one_directory_to_another(
+                        event_details,
+                        chart_dir.to_string(),
+                        workspace_dir,
+                        e,
+                    ));
+                }
+            }
+
+            let diff = HelmReleaseDiff {
+                release_name: chart.name.clone(),
+                namespace: environment.namespace().to_string(),
+                diff: helm
+                    .upgrade_diff(&chart, &[])
+                    .map_err(|e| helm::to_engine_error(&event_details, e))?,
+            };
+
+            let action = match (release_exists, diff.has_changes()) {
+                (false, _) => ChangeAction::Create,
+                (true, true) => ChangeAction::Update,
+                (true, false) => ChangeAction::NoOp,
+            };
+
+            return Ok(service_change_set(service, action, Some(diff), None));
+        }
+    };
+
+    Ok(service_change_set(service, action, None, None))
+}
+
+/// render managed stateful service terraform files into its workspace and return the workspace directory
+fn generate_managed_service_terraform_files<T>(
+    target: &DeploymentTarget,
+    service: &T,
+    event_details: EventDetails,
+) -> Result<String, EngineError>
+where
+    T: StatefulService + Helm + Terraform,
+{
+    let workspace_dir = service.workspace_directory();
+    let context = service.tera_context(target)?;
+    let external_svc_dir = format!("{}/{}", workspace_dir, "external-name-svc");
+    for (from_dir, to_dir) in [
+        (service.terraform_common_resource_dir_path(), workspace_dir.clone()),
+        (service.terraform_resource_dir_path(), workspace_dir.clone()),
+        (service.helm_chart_external_name_service_dir(), external_svc_dir),
+    ] {
+        if let Err(e) = crate::template::generate_and_copy_all_files_into_dir(&from_dir, &to_dir, context.clone()) {
+            return Err(EngineError::new_cannot_copy_files_from_one_directory_to_another(
+                event_details,
+                from_dir,
+                to_dir,
+                e,
+            ));
+        }
+    }
+
+    Ok(workspace_dir)
+}
+
+/// report managed stateful service resources which drifted from their terraform templates, without applying anything
+pub fn detect_managed_stateful_service_drift<T>(
+    target: &DeploymentTarget,
+    service: &T,
+    logger: &dyn Logger,
+) -> Result<Vec<TerraformDrift>, EngineError>
+where
+    T: StatefulService + Helm + Terraform,
+{
+    let event_details = service.get_event_details(Stage::Environment(EnvironmentStep::DetectDrift));
+    let workspace_dir = generate_managed_service_terraform_files(target, service, event_details.clone())?;
+
+    let drifts = cmd::terraform::terraform_init_validate_detect_drift(workspace_dir.as_str())
+        .map_err(|e| EngineError::new_terraform_error_while_executing_pipeline(event_details.clone(), e))?;
+    log_terraform_drifts(&drifts, service.name_with_id().as_str(), event_details, logger);
+
+    Ok(drifts)
+}
+
+/// plan a managed stateful service: render its terraform files into the workspace and plan them
+pub fn plan_managed_stateful_service<T>(
+    target: &DeploymentTarget,
+    service: &T,
+    event_details: EventDetails,
+) -> Result<ServiceChangeSet, EngineError>
+where
+    T: StatefulService + Helm + Terraform,
+{
+    // managed services are not paused
+    let destroy = match service.action() {
+        Action::Create => false,
+        Action::Delete => true,
+        Action::Pause | Action::Nothing => {
+            return Ok(service_change_set(service, ChangeAction::NoOp, None, None));
+        }
+    };
+
+    let workspace_dir = generate_managed_service_terraform_files(target, service, event_details.clone())?;
+    let plan: TerraformPlan = cmd::terraform::terraform_init_validate_plan_show(workspace_dir.as_str(), destroy)
+        .map_err(|e| EngineError::new_terraform_error_while_executing_pipeline(event_details.clone(), e))?
+        .into();
+
+    Ok(service_change_set(service, plan.change_action(), None, Some(plan)))
+}
+
 /// do specific operations on a stateless service deployment error
 pub fn deploy_stateless_service_error<T>(_target: &DeploymentTarget, _service: &T) -> Result<(), EngineError>
 where
@@ -468,7 +703,7 @@ where
 
 pub fn scale_down_database(
     target: &DeploymentTarget,
-    service: &impl DatabaseService,
+    service: &impl StatefulService,
     replicas_count: usize,
 ) -> Result<(), EngineError> {
     if service.is_managed_service() {
@@ -549,6 +784,20 @@ where
     Ok(())
 }
 
+/// terraform states are stored in the namespace, it is created before deploying rather than while rendering templates
+/// so planning an environment has no side effect
+fn create_namespace_without_labels(target: &DeploymentTarget) -> Result<(), EngineError> {
+    let kubernetes = target.kubernetes;
+    let kube_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+    cmd::kubectl::kubectl_exec_create_namespace_without_labels(
+        target.environment.namespace(),
+        kube_config_file_path.as_str(),
+        kubernetes.cloud_provider().credentials_environment_variables(),
+    );
+
+    Ok(())
+}
+
 pub fn deploy_stateful_service<T>(
     target: &DeploymentTarget,
     service: &T,
@@ -562,6 +811,8 @@ where
     let kubernetes = target.kubernetes;
     let environment = target.environment;
 
+    create_namespace_without_labels(target)?;
+
     if service.is_managed_service() {
         logger.log(EngineEvent::Info(
             event_details.clone(),
@@ -741,6 +992,7 @@ where
     let kubernetes = target.kubernetes;
     let environment = target.environment;
     if service.is_managed_service() {
+        create_namespace_without_labels(target)?;
         let workspace_dir = service.workspace_directory();
         let tera_context = service.tera_context(target)?;
 
@@ -57,6 +57,14 @@ fn get_cname_record_value(resolver: &Resolver, cname: &str) -> Option<String> {
         .next() // Can only have one domain behind a CNAME
 }
 
+/// Domain targeted by the CNAME, trying each resolver once
+pub fn resolve_cname(cname: &str) -> Option<String> {
+    dns_resolvers()
+        .iter()
+        .find_map(|resolver| get_cname_record_value(resolver, cname))
+}
+
+/// Waits for the CNAME to resolve and returns the domain it targets
 pub fn check_cname_for(
     scope: ProgressScope,
     listeners: &Listeners,
@@ -111,9 +119,11 @@ pub fn check_cname_for(
         }
     });
 
+    // do not exit / rollback if domain is not ready, simply warn the user about it
     match check_result {
         Ok(domain) => {
             send_deployment_progress(format!("Resolution of CNAME {} found to {}", cname_to_check, domain).as_str());
+            Ok(domain)
         }
         Err(_) => {
             let msg = format!(
@@ -121,11 +131,9 @@ pub fn check_cname_for(
                 cname_to_check
             );
             send_deployment_progress_warn(msg.as_str());
+            Err(msg)
         }
     }
-
-    // do not exit / rollback if domain is not ready, simply warn the user about it
-    Ok(cname_to_check.to_string())
 }
 
 pub fn check_domain_for(
@@ -1,4 +1,4 @@
-use crate::cmd::command::{CommandError, CommandKiller, QoveryCommand};
+use crate::cmd::command::{CommandError, CommandKiller, CommandOutputCapture, QoveryCommand};
 use lazy_static::lazy_static;
 use std::fmt::{Display, Formatter};
 use std::path::Path;
@@ -14,8 +14,8 @@ pub enum DockerError {
     #[error("Docker terminated with an unknown error: {0}")]
     ExecutionError(#[from] std::io::Error),
 
-    #[error("Docker terminated with a non success exit status code: {0}")]
-    ExitStatusError(ExitStatus),
+    #[error("Docker terminated with a non success exit status code: {0}\n{1}")]
+    ExitStatusError(ExitStatus, String),
 
     #[error("Docker aborted due to user cancel request: {0}")]
     Aborted(String),
@@ -82,6 +82,17 @@ impl BuildResult {
         self.pushed = pushed;
         self
     }
+
+    /// What has been reused from cache: the whole image, only its layers, or nothing
+    pub fn cache_status(&self) -> &'static str {
+        if self.image_exists_remotely {
+            "image"
+        } else if self.cached_image_pulled {
+            "layers"
+        } else {
+            "miss"
+        }
+    }
 }
 
 impl Default for BuildResult {
@@ -308,7 +319,7 @@ impl Docker {
 
         match ret {
             Ok(_) => Ok(true),
-            Err(DockerError::ExitStatusError(_)) => Ok(false),
+            Err(DockerError::ExitStatusError(_, _)) => Ok(false),
             Err(err) => Err(err),
         }
     }
@@ -606,13 +617,21 @@ where
     X: FnMut(String),
 {
     let mut cmd = QoveryCommand::new("docker", args, envs);
-    let ret = cmd.exec_with_abort(stdout_output, stderr_output, cmd_killer);
+    let mut stderr = CommandOutputCapture::new_for_envs(envs);
+    let ret = cmd.exec_with_abort(
+        stdout_output,
+        &mut |line| {
+            stderr.push(&line);
+            stderr_output(line)
+        },
+        cmd_killer,
+    );
 
     match ret {
         Ok(_) => Ok(()),
         Err(CommandError::TimeoutError(msg)) => Err(DockerError::Timeout(msg)),
         Err(CommandError::Killed(msg)) => Err(DockerError::Aborted(msg)),
-        Err(CommandError::ExitStatusError(err)) => Err(DockerError::ExitStatusError(err)),
+        Err(CommandError::ExitStatusError(err)) => Err(DockerError::ExitStatusError(err, stderr.output("\n"))),
         Err(CommandError::ExecutionError(err)) => Err(DockerError::ExecutionError(err)),
     }
 }
@@ -4,16 +4,19 @@ use std::path::{Path, PathBuf};
 use tracing::{error, info};
 
 use crate::cloud_provider::helm::ChartInfo;
-use crate::cmd::command::QoveryCommand;
+use crate::cmd::command::{CommandOutputCapture, QoveryCommand};
 use crate::cmd::helm::HelmCommand::{LIST, ROLLBACK, STATUS, UNINSTALL, UPGRADE};
 use crate::cmd::helm::HelmError::{CannotRollback, CmdError, InvalidKubeConfig, ReleaseDoesNotExist};
 use crate::cmd::structs::{HelmChart, HelmListItem};
 use crate::errors::{CommandError, EngineError, ErrorMessageVerbosity};
 use crate::events::EventDetails;
+use crate::metrics;
+use crate::metrics::HELM_UPGRADE_DURATION_SECONDS;
 use semver::Version;
 use serde_derive::Deserialize;
 use std::fs::File;
 use std::str::FromStr;
+use std::time::Instant;
 
 const HELM_DEFAULT_TIMEOUT_IN_SECONDS: u32 = 600;
 const HELM_MAX_HISTORY: &str = "50";
@@ -127,20 +130,17 @@ impl Helm {
             "json",
         ];
 
+        let all_envs = self.get_all_envs(envs);
         let mut stdout = String::new();
-        let mut stderr = String::new();
-        match helm_exec_with_output(
-            &args,
-            &self.get_all_envs(envs),
-            &mut |line| stdout.push_str(&line),
-            &mut |line| stderr.push_str(&line),
-        ) {
+        let mut stderr = CommandOutputCapture::new_for_envs(&all_envs);
+        match helm_exec_with_output(&args, &all_envs, &mut |line| stdout.push_str(&line), &mut |line| {
+            stderr.push(&line)
+        }) {
             Err(_) if stderr.contains("release: not found") => Err(ReleaseDoesNotExist(chart.name.clone())),
             Err(err) => {
-                stderr.push_str(&err.message(ErrorMessageVerbosity::FullDetails));
                 let error = CommandError::new(
                     err.message_safe(),
-                    Some(stderr),
+                    Some(captured_error_details(&stderr, &err)),
                     Some(envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
                 );
                 Err(CmdError(chart.name.clone(), STATUS, error))
@@ -175,13 +175,13 @@ impl Helm {
             "--wait",
         ];
 
-        let mut stderr = String::new();
-        match helm_exec_with_output(&args, &self.get_all_envs(envs), &mut |_| {}, &mut |line| stderr.push_str(&line)) {
+        let all_envs = self.get_all_envs(envs);
+        let mut stderr = CommandOutputCapture::new_for_envs(&all_envs);
+        match helm_exec_with_output(&args, &all_envs, &mut |_| {}, &mut |line| stderr.push(&line)) {
             Err(err) => {
-                stderr.push_str(&err.message(ErrorMessageVerbosity::FullDetails));
                 let error = CommandError::new(
                     err.message_safe(),
-                    Some(stderr),
+                    Some(captured_error_details(&stderr, &err)),
                     Some(envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
                 );
                 Err(CmdError(chart.name.clone(), ROLLBACK, error))
@@ -212,13 +212,13 @@ impl Helm {
             "--wait",
         ];
 
-        let mut stderr = String::new();
-        match helm_exec_with_output(&args, &self.get_all_envs(envs), &mut |_| {}, &mut |line| stderr.push_str(&line)) {
+        let all_envs = self.get_all_envs(envs);
+        let mut stderr = CommandOutputCapture::new_for_envs(&all_envs);
+        match helm_exec_with_output(&args, &all_envs, &mut |_| {}, &mut |line| stderr.push(&line)) {
             Err(err) => {
-                stderr.push_str(&err.message(ErrorMessageVerbosity::FullDetails));
                 let error = CommandError::new(
                     err.message_safe(),
-                    Some(stderr),
+                    Some(captured_error_details(&stderr, &err)),
                     Some(envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
                 );
                 Err(CmdError(chart.name.clone(), UNINSTALL, error))
@@ -324,10 +324,12 @@ impl Helm {
         Ok(None)
     }
 
-    pub fn upgrade_diff(&self, chart: &ChartInfo, envs: &[(&str, &str)]) -> Result<(), HelmError> {
+    /// Show what a `helm upgrade` would change and return the diff, empty if nothing would change
+    pub fn upgrade_diff(&self, chart: &ChartInfo, envs: &[(&str, &str)]) -> Result<String, HelmError> {
         let mut args_string: Vec<String> = vec![
             "diff".to_string(),
             "upgrade".to_string(),
+            "--no-color".to_string(),
             "--kubeconfig".to_string(),
             self.kubernetes_config.to_str().unwrap_or_default().to_string(),
             "--install".to_string(),
@@ -375,22 +377,25 @@ impl Helm {
         args_string.push(chart.name.clone());
         args_string.push(chart.path.clone());
 
-        let mut stderr_msg = String::new();
+        let all_envs = self.get_all_envs(envs);
+        let mut diff = Vec::new();
+        let mut stderr = CommandOutputCapture::new_for_envs(&all_envs);
         let helm_ret = helm_exec_with_output(
             &args_string.iter().map(|x| x.as_str()).collect::<Vec<&str>>(),
-            &self.get_all_envs(envs),
+            &all_envs,
             &mut |line| {
                 debug!("{}", line);
+                diff.push(line);
             },
             &mut |line| {
-                stderr_msg.push_str(&line);
+                stderr.push(&line);
                 warn!("chart {}: {}", chart.name, line);
             },
         );
 
         match helm_ret {
             // Ok is ok
-            Ok(_) => Ok(()),
+            Ok(_) => Ok(diff.join("\n")),
             Err(err) => {
                 error!("Helm error: {:?}", err);
                 Err(CmdError(
@@ -398,7 +403,7 @@ impl Helm {
                     HelmCommand::DIFF,
                     CommandError::new(
                         "Helm error".to_string(),
-                        Some(stderr_msg),
+                        Some(stderr.output("\n")),
                         Some(envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
                     ),
                 ))
@@ -490,30 +495,35 @@ impl Helm {
         args_string.push(chart.name.clone());
         args_string.push(chart.path.clone());
 
-        let mut error_message: Vec<String> = vec![];
+        let all_envs = self.get_all_envs(envs);
+        let mut stderr = CommandOutputCapture::new_for_envs(&all_envs);
 
+        let started_at = Instant::now();
         let helm_ret = helm_exec_with_output(
             &args_string.iter().map(|x| x.as_str()).collect::<Vec<&str>>(),
-            &self.get_all_envs(envs),
+            &all_envs,
             &mut |line| {
                 info!("{}", line);
             },
             &mut |line| {
                 warn!("chart {}: {}", chart.name, line);
-                error_message.push(line);
+                stderr.push(&line);
             },
         );
+        metrics::observe_duration(
+            &HELM_UPGRADE_DURATION_SECONDS,
+            &[
+                ("chart", metrics::helm_chart_label(&chart.path).as_str()),
+                ("outcome", metrics::outcome_label(&helm_ret)),
+            ],
+            started_at.elapsed(),
+        );
 
         if let Err(err) = helm_ret {
             error!("Helm error: {:?}", err);
 
             // Try do define/specify a bit more the message
-            let stderr_msg: String = error_message.into_iter().collect();
-            let stderr_msg = format!(
-                "{}: {}",
-                stderr_msg,
-                err.message(ErrorMessageVerbosity::FullDetailsWithoutEnvVars)
-            );
+            let stderr_msg = captured_error_details(&stderr, &err);
             let error = if stderr_msg.contains("another operation (install/upgrade/rollback) is in progress") {
                 HelmError::ReleaseLocked(chart.name.clone())
             } else if stderr_msg.contains("has been rolled back") {
@@ -589,6 +599,15 @@ where
     }
 }
 
+/// Bounded and redacted stderr of a failed helm command, followed by the command error
+fn captured_error_details(stderr: &CommandOutputCapture, err: &CommandError) -> String {
+    format!(
+        "{}: {}",
+        stderr.output("\n"),
+        stderr.redact(&err.message(ErrorMessageVerbosity::FullDetailsWithoutEnvVars))
+    )
+}
+
 pub fn to_command_error(error: HelmError) -> CommandError {
     CommandError::new_from_safe_message(error.to_string())
 }
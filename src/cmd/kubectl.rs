@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::fs;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
@@ -9,12 +11,12 @@ use serde::de::DeserializeOwned;
 
 use crate::cloud_provider::digitalocean::models::svc::DoLoadBalancer;
 use crate::cloud_provider::metrics::KubernetesApiMetrics;
-use crate::cmd::command::QoveryCommand;
+use crate::cmd::command::{CommandOutputCapture, QoveryCommand};
 use crate::cmd::structs::{
-    Configmap, Daemonset, Item, KubernetesEvent, KubernetesIngress, KubernetesIngressStatusLoadBalancerIngress,
-    KubernetesJob, KubernetesKind, KubernetesList, KubernetesNode, KubernetesPod, KubernetesPodStatusPhase,
-    KubernetesPodStatusReason, KubernetesService, KubernetesVersion, LabelsContent, Namespace, Secrets, HPA, PDB, PVC,
-    SVC,
+    CertManagerCertificate, CertManagerOrder, Configmap, Daemonset, Item, KubernetesEvent, KubernetesIngress,
+    KubernetesIngressStatusLoadBalancerIngress, KubernetesJob, KubernetesKind, KubernetesList, KubernetesNode,
+    KubernetesPod, KubernetesPodStatusPhase, KubernetesPodStatusReason, KubernetesSecret, KubernetesService,
+    KubernetesVersion, LabelsContent, Namespace, Secrets, HPA, PDB, PVC, SVC,
 };
 use crate::constants::KUBECONFIG;
 use crate::error::{SimpleError, SimpleErrorKind};
@@ -38,13 +40,34 @@ pub fn kubectl_exec_with_output<F, X>(
     stdout_output: &mut F,
     stderr_output: &mut X,
 ) -> Result<(), CommandError>
+where
+    F: FnMut(String),
+    X: FnMut(String),
+{
+    kubectl_exec_with_input_and_output(args, envs, None, stdout_output, stderr_output)
+}
+
+fn kubectl_exec_with_input_and_output<F, X>(
+    args: Vec<&str>,
+    envs: Vec<(&str, &str)>,
+    stdin: Option<&str>,
+    stdout_output: &mut F,
+    stderr_output: &mut X,
+) -> Result<(), CommandError>
 where
     F: FnMut(String),
     X: FnMut(String),
 {
     let mut cmd = QoveryCommand::new("kubectl", &args, &envs);
+    if let Some(stdin) = stdin {
+        cmd.set_stdin(stdin);
+    }
+    let mut stderr = CommandOutputCapture::new_for_envs(&envs);
 
-    if let Err(err) = cmd.exec_with_output(stdout_output, stderr_output) {
+    if let Err(err) = cmd.exec_with_output(stdout_output, &mut |line| {
+        stderr.push(&line);
+        stderr_output(line)
+    }) {
         let args_string = args.join(" ");
         let msg = format!("Error on command: kubectl {}. {:?}", args_string, &err);
         error!("{}", &msg);
@@ -54,7 +77,7 @@ where
             args.into_iter().map(|a| a.to_string()).collect(),
             envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
             None,
-            None,
+            Some(stderr.output("\n")),
         ));
     };
 
@@ -114,18 +137,24 @@ where
     })?;
 
     let output_string: String = output_vec.join("\n");
-    let err_output_string: String = output_vec.join("\n");
 
     match serde_json::from_str::<DoLoadBalancer>(output_string.as_str()) {
         Ok(x) => Ok(x),
-        Err(err) => Err(CommandError::new_from_command_line(
-            format!("Error while executing kubectl command: {:?}", err),
-            "kubectl".to_string(),
-            cmd_args.into_iter().map(|a| a.to_string()).collect(),
-            envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
-            Some(output_string.to_string()),
-            Some(err_output_string),
-        )),
+        Err(err) => {
+            let mut stdout = CommandOutputCapture::new_for_envs(&envs);
+            stdout.extend(&output_vec);
+            let mut stderr = CommandOutputCapture::new_for_envs(&envs);
+            stderr.extend(&err_output_vec);
+
+            Err(CommandError::new_from_command_line(
+                format!("Error while executing kubectl command: {:?}", err),
+                "kubectl".to_string(),
+                cmd_args.into_iter().map(|a| a.to_string()).collect(),
+                envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+                Some(stdout.output("\n")),
+                Some(stderr.output("\n")),
+            ))
+        }
     }
 }
 
@@ -216,6 +245,55 @@ where
     Ok(Some(result.status.load_balancer.ingress.first().unwrap().clone()))
 }
 
+/// Address of the load balancer of a `LoadBalancer` Service, a hostname or an ip depending on the cloud provider
+pub fn kubectl_exec_get_external_service<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    name: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<Option<KubernetesIngressStatusLoadBalancerIngress>, CommandError>
+where
+    P: AsRef<Path>,
+{
+    // Services and Ingresses share the same load balancer status
+    let result = kubectl_exec::<P, KubernetesIngress>(
+        vec!["get", "-n", namespace, "svc", name, "-o", "json"],
+        kubernetes_config,
+        envs,
+    )?;
+
+    Ok(result.status.load_balancer.ingress.first().cloned())
+}
+
+/// Label selector of a Service, `None` when the Service does not exist
+pub fn kubectl_exec_get_service_selector<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    name: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<Option<HashMap<String, String>>, CommandError>
+where
+    P: AsRef<Path>,
+{
+    let field_selector = format!("metadata.name={}", name);
+    let result = kubectl_exec::<P, KubernetesList<KubernetesService>>(
+        vec![
+            "get",
+            "-n",
+            namespace,
+            "svc",
+            "--field-selector",
+            &field_selector,
+            "-o",
+            "json",
+        ],
+        kubernetes_config,
+        envs,
+    )?;
+
+    Ok(result.items.into_iter().next().map(|service| service.spec.selector))
+}
+
 pub fn kubectl_exec_is_pod_ready_with_retry<P>(
     kubernetes_config: P,
     namespace: &str,
@@ -704,6 +782,209 @@ where
     })
 }
 
+pub fn kubectl_exec_rollout_restart_statefulset<P>(
+    kubernetes_config: P,
+    name: &str,
+    namespace: &str,
+    envs: &[(&str, &str)],
+) -> Result<(), CommandError>
+where
+    P: AsRef<Path>,
+{
+    let mut environment_variables: Vec<(&str, &str)> = envs.to_owned();
+    environment_variables.push(("KUBECONFIG", kubernetes_config.as_ref().to_str().unwrap()));
+    let args = vec!["-n", namespace, "rollout", "restart", "statefulset", name];
+
+    kubectl_exec_with_output(args, environment_variables, &mut |line| info!("{}", line), &mut |line| {
+        error!("{}", line)
+    })
+}
+
+/// wait for a rollout to be complete
+///
+/// # Arguments
+///
+/// * `kubernetes_config` - kubernetes config path
+/// * `kind` - kind of kubernetes resource rolled out
+/// * `name` - name of the resource
+/// * `namespace` - kubernetes namespace
+/// * `timeout_in_seconds` - maximum time to wait for the rollout
+/// * `envs` - environment variables required for kubernetes connection
+pub fn kubectl_exec_rollout_status<P>(
+    kubernetes_config: P,
+    kind: ScalingKind,
+    name: &str,
+    namespace: &str,
+    timeout_in_seconds: u32,
+    envs: &[(&str, &str)],
+) -> Result<(), CommandError>
+where
+    P: AsRef<Path>,
+{
+    let kind_with_name = match kind {
+        ScalingKind::Deployment => format!("deployment/{}", name),
+        ScalingKind::Statefulset => format!("statefulset/{}", name),
+    };
+    let timeout = format!("--timeout={}s", timeout_in_seconds);
+
+    let mut environment_variables: Vec<(&str, &str)> = envs.to_owned();
+    environment_variables.push(("KUBECONFIG", kubernetes_config.as_ref().to_str().unwrap()));
+    let args = vec!["-n", namespace, "rollout", "status", &kind_with_name, &timeout];
+
+    kubectl_exec_with_output(args, environment_variables, &mut |line| info!("{}", line), &mut |line| {
+        error!("{}", line)
+    })
+}
+
+/// set keys of an existing secret, values must be base64 encoded
+///
+/// # Arguments
+///
+/// * `kubernetes_config` - kubernetes config path
+/// * `namespace` - kubernetes namespace
+/// * `secret_name` - name of the secret to patch
+/// * `data` - keys and base64 encoded values to set
+/// * `envs` - environment variables required for kubernetes connection
+pub fn kubectl_exec_patch_secret<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    secret_name: &str,
+    data: &[(String, String)],
+    envs: Vec<(&str, &str)>,
+) -> Result<(), CommandError>
+where
+    P: AsRef<Path>,
+{
+    let data: serde_json::Map<String, serde_json::Value> = data
+        .iter()
+        .map(|(key, value)| (key.to_string(), serde_json::Value::String(value.to_string())))
+        .collect();
+    let patch = serde_json::json!({ "data": data }).to_string();
+
+    let _ = kubectl_exec_raw_output(
+        vec![
+            "-n",
+            namespace,
+            "patch",
+            "secret",
+            secret_name,
+            "--type",
+            "merge",
+            "-p",
+            &patch,
+        ],
+        kubernetes_config,
+        envs,
+        false,
+    )?;
+
+    Ok(())
+}
+
+/// run a one shot pod and return its output, the pod is deleted once done
+///
+/// # Arguments
+///
+/// * `kubernetes_config` - kubernetes config path
+/// * `namespace` - namespace where the pod is run
+/// * `pod_name` - name of the pod to run
+/// * `image` - container image to run
+/// * `pod_envs` - environment variables set into the pod, visible in the pod spec so they must not hold secrets
+/// * `command` - command to run into the pod
+/// * `stdin` - input sent to the command, secrets are passed this way
+/// * `envs` - environment variables required for kubernetes connection
+pub fn kubectl_exec_run_pod<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    pod_name: &str,
+    image: &str,
+    pod_envs: &[(&str, &str)],
+    command: &[&str],
+    stdin: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<String, CommandError>
+where
+    P: AsRef<Path>,
+{
+    let image = format!("--image={}", image);
+    let pod_envs = pod_envs
+        .iter()
+        .map(|(key, value)| format!("--env={}={}", key, value))
+        .collect::<Vec<_>>();
+
+    let mut args = vec![
+        "-n",
+        namespace,
+        "run",
+        pod_name,
+        "--rm",
+        "-i",
+        "--quiet",
+        "--restart=Never",
+        &image,
+    ];
+    args.extend(pod_envs.iter().map(|env| env.as_str()));
+    args.push("--command");
+    args.push("--");
+    args.extend(command);
+
+    kubectl_exec_raw_output_with_input(args, kubernetes_config, envs, Some(stdin), true)
+}
+
+/// create or update an opaque secret, values must be base64 encoded.
+/// The secret is sent through the command input so its values never appear in the command line.
+///
+/// # Arguments
+///
+/// * `kubernetes_config` - kubernetes config path
+/// * `namespace` - namespace of the secret
+/// * `secret_name` - name of the secret
+/// * `labels` - labels of the secret
+/// * `data` - keys and base64 encoded values of the secret
+/// * `envs` - environment variables required for kubernetes connection
+pub fn kubectl_exec_apply_secret<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    secret_name: &str,
+    labels: &[(&str, &str)],
+    data: &[(String, String)],
+    envs: Vec<(&str, &str)>,
+) -> Result<(), CommandError>
+where
+    P: AsRef<Path>,
+{
+    let labels: serde_json::Map<String, serde_json::Value> = labels
+        .iter()
+        .map(|(key, value)| (key.to_string(), serde_json::Value::String(value.to_string())))
+        .collect();
+    let data: serde_json::Map<String, serde_json::Value> = data
+        .iter()
+        .map(|(key, value)| (key.to_string(), serde_json::Value::String(value.to_string())))
+        .collect();
+    let secret = serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "type": "Opaque",
+        "metadata": {
+            "name": secret_name,
+            "namespace": namespace,
+            "labels": labels,
+        },
+        "data": data,
+    })
+    .to_string();
+
+    let _ = kubectl_exec_raw_output_with_input(
+        vec!["-n", namespace, "apply", "-f", "-"],
+        kubernetes_config,
+        envs,
+        Some(&secret),
+        false,
+    )?;
+
+    Ok(())
+}
+
 pub fn kubectl_exec_get_node<P>(
     kubernetes_config: P,
     envs: Vec<(&str, &str)>,
@@ -1236,6 +1517,19 @@ fn kubectl_exec_raw_output<P>(
     envs: Vec<(&str, &str)>,
     keep_format: bool,
 ) -> Result<String, CommandError>
+where
+    P: AsRef<Path>,
+{
+    kubectl_exec_raw_output_with_input(args, kubernetes_config, envs, None, keep_format)
+}
+
+fn kubectl_exec_raw_output_with_input<P>(
+    args: Vec<&str>,
+    kubernetes_config: P,
+    envs: Vec<(&str, &str)>,
+    stdin: Option<&str>,
+    keep_format: bool,
+) -> Result<String, CommandError>
 where
     P: AsRef<Path>,
 {
@@ -1244,9 +1538,13 @@ where
     _envs.extend(envs);
 
     let mut output_vec: Vec<String> = Vec::with_capacity(50);
-    let _ = kubectl_exec_with_output(args.clone(), _envs.clone(), &mut |line| output_vec.push(line), &mut |line| {
-        error!("{}", line)
-    })?;
+    let _ = kubectl_exec_with_input_and_output(
+        args.clone(),
+        _envs.clone(),
+        stdin,
+        &mut |line| output_vec.push(line),
+        &mut |line| error!("{}", line),
+    )?;
 
     match keep_format {
         true => Ok(output_vec.join("\n")),
@@ -1352,6 +1650,153 @@ where
     kubectl_exec_raw_output(cmd_args, kubernetes_config, envs, false)
 }
 
+/// cert-manager certificate, `None` when it does not exist
+pub fn kubectl_exec_get_certificate<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    name: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<Option<CertManagerCertificate>, CommandError>
+where
+    P: AsRef<Path>,
+{
+    let field_selector = format!("metadata.name={}", name);
+    let result = kubectl_exec::<P, KubernetesList<CertManagerCertificate>>(
+        vec![
+            "get",
+            "-n",
+            namespace,
+            "certificates.cert-manager.io",
+            "--field-selector",
+            &field_selector,
+            "-o",
+            "json",
+        ],
+        kubernetes_config,
+        envs,
+    )?;
+
+    Ok(result.items.into_iter().next())
+}
+
+/// ACME orders placed by cert-manager in the namespace, oldest first
+pub fn kubectl_exec_get_certificate_orders<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<Vec<CertManagerOrder>, CommandError>
+where
+    P: AsRef<Path>,
+{
+    let result = kubectl_exec::<P, KubernetesList<CertManagerOrder>>(
+        vec![
+            "get",
+            "-n",
+            namespace,
+            "orders.acme.cert-manager.io",
+            "--sort-by=.metadata.creationTimestamp",
+            "-o",
+            "json",
+        ],
+        kubernetes_config,
+        envs,
+    )?;
+
+    Ok(result.items)
+}
+
+/// Creates a `kubernetes.io/tls` secret from PEM encoded certificate and private key, or updates it when it exists.
+/// They are written in `workspace_dir` the time of the command only.
+pub fn kubectl_create_tls_secret<P>(
+    kubernetes_config: P,
+    envs: Vec<(&str, &str)>,
+    namespace: &str,
+    secret_name: &str,
+    certificate: &str,
+    private_key: &str,
+    workspace_dir: &str,
+) -> Result<(), CommandError>
+where
+    P: AsRef<Path>,
+{
+    let certificate_path = format!("{}/{}.crt", workspace_dir, secret_name);
+    let private_key_path = format!("{}/{}.key", workspace_dir, secret_name);
+    let manifest_path = format!("{}/{}.yaml", workspace_dir, secret_name);
+
+    let result = fs::write(&certificate_path, certificate)
+        .and_then(|_| fs::write(&private_key_path, private_key))
+        .map_err(|e| {
+            CommandError::new_from_safe_message(format!("Cannot write TLS secret `{}` files: {}", secret_name, e))
+        })
+        .and_then(|_| {
+            // a dry run followed by an apply updates the secret in place, it never disappears
+            let cert_arg = format!("--cert={}", certificate_path);
+            let key_arg = format!("--key={}", private_key_path);
+            kubectl_exec_raw_output(
+                vec![
+                    "create",
+                    "secret",
+                    "tls",
+                    secret_name,
+                    cert_arg.as_str(),
+                    key_arg.as_str(),
+                    "-n",
+                    namespace,
+                    "--dry-run=client",
+                    "-o",
+                    "yaml",
+                ],
+                &kubernetes_config,
+                envs.clone(),
+                true,
+            )
+        })
+        .and_then(|manifest| {
+            fs::write(&manifest_path, manifest).map_err(|e| {
+                CommandError::new_from_safe_message(format!(
+                    "Cannot write TLS secret `{}` manifest: {}",
+                    secret_name, e
+                ))
+            })
+        })
+        .and_then(|_| kubectl_apply_with_path(&kubernetes_config, envs, &manifest_path));
+
+    for path in [&certificate_path, &private_key_path, &manifest_path] {
+        let _ = fs::remove_file(path);
+    }
+
+    result.map(|_| ())
+}
+
+/// Data of the secret, `None` when it does not exist
+pub fn kubectl_exec_get_secret_data<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    name: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<Option<HashMap<String, String>>, CommandError>
+where
+    P: AsRef<Path>,
+{
+    let field_selector = format!("metadata.name={}", name);
+    let result = kubectl_exec::<P, KubernetesList<KubernetesSecret>>(
+        vec![
+            "get",
+            "-n",
+            namespace,
+            "secrets",
+            "--field-selector",
+            &field_selector,
+            "-o",
+            "json",
+        ],
+        kubernetes_config,
+        envs,
+    )?;
+
+    Ok(result.items.into_iter().next().map(|secret| secret.data))
+}
+
 pub fn kubectl_delete_secret<P>(
     kubernetes_config: P,
     envs: Vec<(&str, &str)>,
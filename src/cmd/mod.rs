@@ -5,3 +5,4 @@ pub mod helm_utils;
 pub mod kubectl;
 pub mod structs;
 pub mod terraform;
+pub mod terraform_errors;
@@ -12,9 +12,95 @@ pub struct KubernetesList<T> {
 #[derive(Deserialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct KubernetesService {
+    #[serde(default)]
+    pub spec: KubernetesServiceSpec,
     pub status: KubernetesServiceStatus,
 }
 
+#[derive(Deserialize, Clone, Eq, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct KubernetesServiceSpec {
+    #[serde(default)]
+    pub selector: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KubernetesSecret {
+    /// values are base64 encoded
+    #[serde(default)]
+    pub data: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CertManagerResourceMetadata {
+    pub name: String,
+    #[serde(default)]
+    pub creation_timestamp: String,
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CertManagerCertificate {
+    pub metadata: CertManagerResourceMetadata,
+    #[serde(default)]
+    pub spec: CertManagerCertificateSpec,
+    #[serde(default)]
+    pub status: CertManagerCertificateStatus,
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CertManagerCertificateSpec {
+    #[serde(default)]
+    pub dns_names: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CertManagerCertificateStatus {
+    #[serde(default)]
+    pub conditions: Vec<CertManagerCondition>,
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CertManagerCondition {
+    #[serde(rename = "type")]
+    pub condition_type: String,
+    /// `True`, `False` or `Unknown`
+    pub status: String,
+    pub reason: Option<String>,
+    pub message: Option<String>,
+}
+
+/// ACME order placed by cert-manager to issue a certificate
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CertManagerOrder {
+    pub metadata: CertManagerResourceMetadata,
+    #[serde(default)]
+    pub spec: CertManagerOrderSpec,
+    #[serde(default)]
+    pub status: CertManagerOrderStatus,
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CertManagerOrderSpec {
+    #[serde(default)]
+    pub dns_names: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CertManagerOrderStatus {
+    /// `pending`, `ready`, `processing`, `valid`, `invalid`, `expired` or `errored`
+    pub state: Option<String>,
+    pub reason: Option<String>,
+}
+
 #[derive(Deserialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct KubernetesIngress {
@@ -562,7 +648,10 @@ pub struct HPAAnnotationCondition {
 
 #[cfg(test)]
 mod tests {
-    use crate::cmd::structs::{KubernetesList, KubernetesPod, KubernetesPodStatusReason, PDB, PVC, SVC};
+    use crate::cmd::structs::{
+        CertManagerCertificate, CertManagerOrder, KubernetesList, KubernetesPod, KubernetesPodStatusReason, PDB, PVC,
+        SVC,
+    };
 
     #[test]
     fn test_svc_deserialize() {
@@ -1803,4 +1892,80 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_cert_manager_deserialize() {
+        let certificates = r#"{
+    "apiVersion": "v1",
+    "items": [
+        {
+            "apiVersion": "cert-manager.io/v1alpha2",
+            "kind": "Certificate",
+            "metadata": {
+                "creationTimestamp": "2022-05-10T09:12:40Z",
+                "name": "router-tls-z2fa2b8d4",
+                "namespace": "z9b830e28-ze23976e2"
+            },
+            "spec": {
+                "dnsNames": ["www.example.com"],
+                "issuerRef": {"kind": "Issuer", "name": "z2fa2b8d4"},
+                "secretName": "router-tls-z2fa2b8d4"
+            },
+            "status": {
+                "conditions": [
+                    {
+                        "lastTransitionTime": "2022-05-10T09:12:40Z",
+                        "message": "Waiting for CertificateRequest \"router-tls-z2fa2b8d4-1796547287\" to complete",
+                        "reason": "InProgress",
+                        "status": "False",
+                        "type": "Ready"
+                    }
+                ]
+            }
+        }
+    ],
+    "kind": "List"
+}"#;
+        let orders = r#"{
+    "apiVersion": "v1",
+    "items": [
+        {
+            "apiVersion": "acme.cert-manager.io/v1alpha2",
+            "kind": "Order",
+            "metadata": {
+                "creationTimestamp": "2022-05-10T09:12:41Z",
+                "name": "router-tls-z2fa2b8d4-1796547287-3306589234"
+            },
+            "spec": {
+                "dnsNames": ["www.example.com"],
+                "request": "LS0tLS1CRUdJTi..."
+            },
+            "status": {
+                "reason": "Failed to finalize Order: 429 urn:ietf:params:acme:error:rateLimited",
+                "state": "errored"
+            }
+        },
+        {
+            "apiVersion": "acme.cert-manager.io/v1alpha2",
+            "kind": "Order",
+            "metadata": {
+                "name": "router-tls-z2fa2b8d4-1796547287-1245789658"
+            },
+            "spec": {
+                "dnsNames": ["www.example.com"]
+            }
+        }
+    ],
+    "kind": "List"
+}"#;
+
+        let certificates = serde_json::from_str::<KubernetesList<CertManagerCertificate>>(certificates).unwrap();
+        assert_eq!(certificates.items[0].spec.dns_names, vec!["www.example.com"]);
+        assert_eq!(certificates.items[0].status.conditions[0].condition_type, "Ready");
+        assert_eq!(certificates.items[0].status.conditions[0].status, "False");
+
+        let orders = serde_json::from_str::<KubernetesList<CertManagerOrder>>(orders).unwrap();
+        assert_eq!(orders.items[0].status.state.as_deref(), Some("errored"));
+        assert_eq!(orders.items[1].status.state, None);
+    }
 }
@@ -2,73 +2,139 @@ use dirs::home_dir;
 use retry::delay::Fixed;
 use retry::OperationResult;
 
-use crate::cmd::command::QoveryCommand;
-use crate::constants::TF_PLUGIN_CACHE_DIR;
-use crate::errors::{CommandError, ErrorMessageVerbosity};
+use crate::cmd::command;
+use crate::cmd::command::{CommandOutputCapture, QoveryCommand};
+use crate::cmd::terraform_errors::{TerraformError, TerraformRemediation};
+use crate::constants::{TF_FORCE_UNLOCK_STALE_LOCKS, TF_PLUGIN_CACHE_DIR};
+use crate::errors::CommandError;
+use crate::io_models::ProgressCounter;
+use crate::metrics;
+use crate::metrics::TERRAFORM_APPLY_DURATION_SECONDS;
 use rand::Rng;
 use retry::Error::Operation;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
 use std::{env, fs, thread, time};
 
-fn manage_common_issues(terraform_provider_lock: &str, err: &CommandError) -> Result<(), CommandError> {
-    // Error: Failed to install provider from shared cache
-    // in order to avoid lock errors on parallel run, let's sleep a bit
-    // https://github.com/hashicorp/terraform/issues/28041
+/// Subset of `terraform show -json` plan output
+/// https://www.terraform.io/internals/json-format#plan-representation
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TerraformPlanOutput {
+    #[serde(default)]
+    pub resource_changes: Vec<TerraformResourceChange>,
+}
 
-    if err
-        .message(ErrorMessageVerbosity::FullDetails)
-        .contains("Failed to install provider from shared cache")
-        || err
-            .message(ErrorMessageVerbosity::FullDetails)
-            .contains("Failed to install provider")
-    {
-        let sleep_time_int = rand::thread_rng().gen_range(20..45);
-        let sleep_time = time::Duration::from_secs(sleep_time_int);
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TerraformResourceChange {
+    pub address: String,
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    pub name: String,
+    pub change: TerraformChange,
+}
 
-        // failed to install provider from shared cache, cleaning and sleeping before retrying...",
-        thread::sleep(sleep_time);
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TerraformChange {
+    /// one of: ["no-op"], ["create"], ["read"], ["update"], ["delete", "create"], ["create", "delete"], ["delete"]
+    pub actions: Vec<String>,
+}
 
-        return match fs::remove_file(&terraform_provider_lock) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(CommandError::new(
-                format!("Wasn't able to delete terraform lock file {}", &terraform_provider_lock),
-                Some(e.to_string()),
-                None,
-            )),
-        };
-    } else if err
-        .message(ErrorMessageVerbosity::FullDetails)
-        .contains("Plugin reinitialization required")
-    {
-        // terraform init is required
-        return Ok(());
+impl TerraformResourceChange {
+    pub fn is_no_op(&self) -> bool {
+        self.change
+            .actions
+            .iter()
+            .all(|action| action == "no-op" || action == "read")
     }
+}
 
-    Err(CommandError::new_from_safe_message(
-        "Not known method to fix this Terraform issue".to_string(),
-    ))
+/// Try to fix known issues before the next attempt, errors which can't be fixed by retrying end the retry loop
+fn retry_after_remediation<T>(root_dir: &str, error: TerraformError) -> OperationResult<T, TerraformError> {
+    if !error.is_retryable() {
+        return OperationResult::Err(error);
+    }
+
+    if let Some(remediation) = error.remediation(is_stale_lock_force_unlock_enabled()) {
+        if let Err(e) = terraform_remediate(root_dir, &remediation) {
+            error!("Error while trying to fix Terraform issue ({:?}): {}", remediation, e);
+        }
+    }
+
+    OperationResult::Retry(error)
 }
 
-fn terraform_init_validate(root_dir: &str) -> Result<(), CommandError> {
-    let terraform_provider_lock = format!("{}/.terraform.lock.hcl", &root_dir);
+/// Retry errors which may be transient without fixing anything, for runs which only read the state
+fn retry_without_remediation<T>(error: TerraformError) -> OperationResult<T, TerraformError> {
+    if !error.is_retryable() {
+        return OperationResult::Err(error);
+    }
+
+    OperationResult::Retry(error)
+}
 
+/// Releasing a stale lock may corrupt the state if the run holding it is still alive, it must be explicitly enabled
+fn is_stale_lock_force_unlock_enabled() -> bool {
+    matches!(env::var(TF_FORCE_UNLOCK_STALE_LOCKS), Ok(value) if value == "true")
+}
+
+fn terraform_remediate(root_dir: &str, remediation: &TerraformRemediation) -> Result<(), TerraformError> {
+    match remediation {
+        TerraformRemediation::ForceUnlock { lock_id } => {
+            info!("Terraform state lock {} is stale, releasing it", lock_id);
+            terraform_exec(root_dir, vec!["force-unlock", "-force", lock_id.as_str()]).map(|_| ())
+        }
+        TerraformRemediation::ReinitializeProviders => {
+            // in order to avoid lock errors on parallel run, let's sleep a bit
+            // https://github.com/hashicorp/terraform/issues/28041
+            let sleep_time_int = rand::thread_rng().gen_range(20..45);
+            thread::sleep(time::Duration::from_secs(sleep_time_int));
+
+            let terraform_provider_lock = format!("{}/.terraform.lock.hcl", &root_dir);
+            match fs::remove_file(&terraform_provider_lock) {
+                Err(e) if e.kind() != ErrorKind::NotFound => {
+                    return Err(TerraformError::new(
+                        CommandError::new(
+                            format!("Wasn't able to delete terraform lock file {}", &terraform_provider_lock),
+                            Some(e.to_string()),
+                            None,
+                        ),
+                        vec![],
+                    ))
+                }
+                _ => {}
+            };
+
+            terraform_exec(root_dir, vec!["init", "-no-color"]).map(|_| ())
+        }
+    }
+}
+
+fn terraform_init_validate(root_dir: &str) -> Result<(), TerraformError> {
+    terraform_init_validate_with_retry(root_dir, &|err| retry_after_remediation(root_dir, err))
+}
+
+/// Init and validate for runs which only read the state, nothing is remediated
+fn terraform_init_validate_read_only(root_dir: &str) -> Result<(), TerraformError> {
+    terraform_init_validate_with_retry(root_dir, &retry_without_remediation)
+}
+
+fn terraform_init_validate_with_retry(
+    root_dir: &str,
+    retry_on_error: &dyn Fn(TerraformError) -> OperationResult<(), TerraformError>,
+) -> Result<(), TerraformError> {
     let result = retry::retry(Fixed::from_millis(3000).take(5), || {
         // terraform init
-        match terraform_exec(root_dir, vec!["init", "-no-color"]) {
-            Ok(_) => OperationResult::Ok(()),
-            Err(err) => {
-                let _ = manage_common_issues(&terraform_provider_lock, &err);
-                // Error while trying to run terraform init, retrying...
-                OperationResult::Retry(err)
-            }
-        };
+        if let Err(err) = terraform_exec(root_dir, vec!["init", "-no-color"]) {
+            // Error while trying to run terraform init, retrying...
+            return retry_on_error(err);
+        }
 
         // validate config
         match terraform_exec(root_dir, vec!["validate", "-no-color"]) {
             Ok(_) => OperationResult::Ok(()),
             Err(err) => {
-                let _ = manage_common_issues(&terraform_provider_lock, &err);
                 // error while trying to Terraform validate on the rendered templates
-                OperationResult::Retry(err)
+                retry_on_error(err)
             }
         }
     });
@@ -76,28 +142,35 @@ fn terraform_init_validate(root_dir: &str) -> Result<(), CommandError> {
     match result {
         Ok(_) => Ok(()),
         Err(Operation { error, .. }) => Err(error),
-        Err(retry::Error::Internal(e)) => Err(CommandError::new(
-            "Error while performing Terraform validate.".to_string(),
-            Some(e),
-            None,
+        Err(retry::Error::Internal(e)) => Err(TerraformError::new(
+            CommandError::new("Error while performing Terraform validate.".to_string(), Some(e), None),
+            vec![],
         )),
     }
 }
 
-pub fn terraform_init_validate_plan_apply(root_dir: &str, dry_run: bool) -> Result<(), CommandError> {
+pub fn terraform_init_validate_plan_apply(root_dir: &str, dry_run: bool) -> Result<(), TerraformError> {
+    terraform_init_validate_plan_apply_with_progress(root_dir, dry_run, &|_| {})
+}
+
+/// `on_progress` is called each time a planned resource change has been applied
+pub fn terraform_init_validate_plan_apply_with_progress(
+    root_dir: &str,
+    dry_run: bool,
+    on_progress: &dyn Fn(ProgressCounter),
+) -> Result<(), TerraformError> {
     // terraform init
-    if let Err(e) = terraform_init_validate(root_dir) {
-        return Err(e);
-    }
+    terraform_init_validate(root_dir)?;
 
     if dry_run {
         // plan
         let result = retry::retry(Fixed::from_millis(3000).take(3), || {
-            match terraform_exec(root_dir, vec!["plan", "-no-color", "-out", "tf_plan"]) {
+            let args = vec!["plan", "-json", "-input=false", "-out", "tf_plan"];
+            match terraform_exec_with_stdout_callback(root_dir, args, &mut |_| {}) {
                 Ok(out) => OperationResult::Ok(out),
                 Err(err) => {
                     // Error while trying to Terraform plan the rendered templates
-                    OperationResult::Retry(err)
+                    retry_after_remediation(root_dir, err)
                 }
             }
         });
@@ -105,35 +178,179 @@ pub fn terraform_init_validate_plan_apply(root_dir: &str, dry_run: bool) -> Resu
         return match result {
             Ok(_) => Ok(()),
             Err(Operation { error, .. }) => Err(error),
-            Err(retry::Error::Internal(e)) => Err(CommandError::new(
-                "Error while performing Terraform validate.".to_string(),
-                Some(e),
-                None,
+            Err(retry::Error::Internal(e)) => Err(TerraformError::new(
+                CommandError::new("Error while performing Terraform validate.".to_string(), Some(e), None),
+                vec![],
             )),
         };
     }
 
-    terraform_plan_apply(root_dir)
+    terraform_plan_apply(root_dir, on_progress)
 }
 
-pub fn terraform_init_validate_destroy(root_dir: &str, run_apply_before_destroy: bool) -> Result<(), CommandError> {
+/// Plan without applying anything and return the planned resource changes
+pub fn terraform_init_validate_plan_show(root_dir: &str, destroy: bool) -> Result<TerraformPlanOutput, TerraformError> {
     // terraform init
-    if let Err(e) = terraform_init_validate(root_dir) {
-        return Err(e);
+    terraform_init_validate_read_only(root_dir)?;
+
+    // plan, state is only read so no need to lock it
+    let mut plan_args = vec!["plan", "-json", "-input=false", "-lock=false", "-out", "tf_plan"];
+    if destroy {
+        plan_args.push("-destroy");
     }
 
+    let result = retry::retry(Fixed::from_millis(3000).take(3), || {
+        match terraform_exec_with_stdout_callback(root_dir, plan_args.clone(), &mut |_| {}) {
+            Ok(out) => OperationResult::Ok(out),
+            Err(err) => {
+                // Error while trying to Terraform plan the rendered templates
+                retry_without_remediation(err)
+            }
+        }
+    });
+
+    match result {
+        Ok(_) => {}
+        Err(Operation { error, .. }) => return Err(error),
+        Err(retry::Error::Internal(e)) => {
+            return Err(TerraformError::new(
+                CommandError::new("Error while performing Terraform plan.".to_string(), Some(e), None),
+                vec![],
+            ))
+        }
+    };
+
+    // plan json output may contain sensitive values, it should not be logged
+    let output = terraform_exec_without_logging(root_dir, vec!["show", "-no-color", "-json", "tf_plan"])?;
+    parse_terraform_plan_output(output.join("").as_str()).map_err(|e| TerraformError::new(e, vec![]))
+}
+
+fn parse_terraform_plan_output(json: &str) -> Result<TerraformPlanOutput, CommandError> {
+    serde_json::from_str::<TerraformPlanOutput>(json).map_err(|e| {
+        CommandError::new(
+            "Error while parsing Terraform plan JSON output.".to_string(),
+            Some(e.to_string()),
+            None,
+        )
+    })
+}
+
+/// Resource changed outside of Terraform, ex: edited from the cloud provider console
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TerraformDrift {
+    pub address: String,
+    pub resource_type: String,
+    pub action: String,
+}
+
+/// One line of `terraform plan -json` machine readable UI output
+/// https://www.terraform.io/internals/machine-readable-ui
+#[derive(Deserialize)]
+struct TerraformUiMessage {
+    #[serde(rename = "@level")]
+    level: String,
+    #[serde(rename = "@message")]
+    message: String,
+    #[serde(rename = "type")]
+    message_type: String,
+    change: Option<TerraformUiChange>,
+    changes: Option<TerraformUiChangeSummary>,
+}
+
+#[derive(Deserialize)]
+struct TerraformUiChangeSummary {
+    add: usize,
+    change: usize,
+    remove: usize,
+}
+
+#[derive(Deserialize)]
+struct TerraformUiChange {
+    resource: TerraformUiResource,
+    action: String,
+}
+
+#[derive(Deserialize)]
+struct TerraformUiResource {
+    addr: String,
+    resource_type: String,
+}
+
+fn parse_terraform_drift(line: &str) -> Option<TerraformDrift> {
+    serde_json::from_str::<TerraformUiMessage>(line)
+        .ok()
+        // planned changes only tell the templates differ from the state, ex: a new engine version
+        .filter(|message| message.message_type == "resource_drift")
+        .and_then(|message| message.change)
+        .map(|change| TerraformDrift {
+            address: change.resource.addr,
+            resource_type: change.resource.resource_type,
+            action: change.action,
+        })
+}
+
+/// Number of resources a plan `change_summary` line announces to add, change or remove
+fn parse_planned_changes_count(line: &str) -> Option<usize> {
+    serde_json::from_str::<TerraformUiMessage>(line)
+        .ok()
+        .filter(|message| message.message_type == "change_summary")
+        .and_then(|message| message.changes)
+        .map(|changes| changes.add + changes.change + changes.remove)
+}
+
+/// Whether an `apply -json` output line reports a resource change as done, successfully or not
+fn is_resource_change_applied(line: &str) -> bool {
+    match serde_json::from_str::<TerraformUiMessage>(line) {
+        Ok(message) => message.message_type == "apply_complete" || message.message_type == "apply_errored",
+        Err(_) => false,
+    }
+}
+
+/// Plan without applying anything and return resources changed outside of Terraform since the last apply
+pub fn terraform_init_validate_detect_drift(root_dir: &str) -> Result<Vec<TerraformDrift>, TerraformError> {
+    // terraform init
+    terraform_init_validate_read_only(root_dir)?;
+
+    // state is only read so no need to lock it
+    let args = vec!["plan", "-detailed-exitcode", "-json", "-input=false", "-lock=false"];
+    let mut drifts = Vec::new();
+    let (result, stdout, stderr) = terraform_run(root_dir, &args, true, &mut |line| {
+        if let Some(drift) = parse_terraform_drift(line) {
+            drifts.push(drift);
+        }
+    });
+
+    // -detailed-exitcode: 0 = no changes, 1 = error, 2 = changes are present
+    // drifted resources matching the templates again don't lead to changes, they are reported on success too
+    match result {
+        Ok(_) => Ok(drifts),
+        Err(command::CommandError::ExitStatusError(status)) if status.code() == Some(2) => Ok(drifts),
+        Err(_) => Err(terraform_error(
+            "Error while performing Terraform drift detection.",
+            &args,
+            &stdout,
+            &stderr,
+        )),
+    }
+}
+
+pub fn terraform_init_validate_destroy(root_dir: &str, run_apply_before_destroy: bool) -> Result<(), TerraformError> {
+    // terraform init
+    terraform_init_validate(root_dir)?;
+
     // better to apply before destroy to ensure terraform destroy will delete on all resources
     if run_apply_before_destroy {
-        terraform_plan_apply(root_dir)?;
+        terraform_plan_apply(root_dir, &|_| {})?;
     }
 
     // terraform destroy
     let result = retry::retry(Fixed::from_millis(3000).take(5), || {
-        match terraform_exec(root_dir, vec!["destroy", "-no-color", "-auto-approve"]) {
+        let args = vec!["destroy", "-json", "-input=false", "-auto-approve"];
+        match terraform_exec_with_stdout_callback(root_dir, args, &mut |_| {}) {
             Ok(out) => OperationResult::Ok(out),
             Err(err) => {
                 // Error while trying to run terraform destroy on rendered templates, retrying...
-                OperationResult::Retry(err)
+                retry_after_remediation(root_dir, err)
             }
         }
     });
@@ -141,47 +358,71 @@ pub fn terraform_init_validate_destroy(root_dir: &str, run_apply_before_destroy:
     match result {
         Ok(_) => Ok(()),
         Err(Operation { error, .. }) => Err(error),
-        Err(retry::Error::Internal(e)) => Err(CommandError::new(
-            "Error while performing Terraform destroy".to_string(),
-            Some(e),
-            None,
+        Err(retry::Error::Internal(e)) => Err(TerraformError::new(
+            CommandError::new("Error while performing Terraform destroy".to_string(), Some(e), None),
+            vec![],
         )),
     }
 }
 
-fn terraform_plan_apply(root_dir: &str) -> Result<(), CommandError> {
+fn terraform_plan_apply(root_dir: &str, on_progress: &dyn Fn(ProgressCounter)) -> Result<(), TerraformError> {
+    let started_at = time::Instant::now();
     let result = retry::retry(Fixed::from_millis(3000).take(5), || {
         // plan
-        if let Err(err) = terraform_exec(root_dir, vec!["plan", "-no-color", "-out", "tf_plan"]) {
+        let mut planned_changes_count = 0;
+        let args = vec!["plan", "-json", "-input=false", "-out", "tf_plan"];
+        if let Err(err) = terraform_exec_with_stdout_callback(root_dir, args, &mut |line| {
+            if let Some(count) = parse_planned_changes_count(line) {
+                planned_changes_count = count;
+            }
+        }) {
             // Error while trying to Terraform plan the rendered templates
-            return OperationResult::Retry(err);
+            return retry_after_remediation(root_dir, err);
         }
         // apply
-        match terraform_exec(root_dir, vec!["apply", "-no-color", "-auto-approve", "tf_plan"]) {
+        let mut applied_changes_count = 0;
+        let args = vec!["apply", "-json", "-input=false", "-auto-approve", "tf_plan"];
+        match terraform_exec_with_stdout_callback(root_dir, args, &mut |line| {
+            if planned_changes_count > 0 && is_resource_change_applied(line) {
+                applied_changes_count += 1;
+                on_progress(ProgressCounter::new(
+                    applied_changes_count.min(planned_changes_count),
+                    planned_changes_count,
+                ));
+            }
+        }) {
             Ok(out) => OperationResult::Ok(out),
             Err(err) => {
                 // Error while trying to run terraform apply on rendered templates, retrying...
-                OperationResult::Retry(err)
+                retry_after_remediation(root_dir, err)
             }
         }
     });
 
-    match result {
+    let result = match result {
         Ok(_) => Ok(()),
         Err(Operation { error, .. }) => Err(error),
-        Err(retry::Error::Internal(e)) => Err(CommandError::new(
-            "Error while performing Terraform plan and apply.".to_string(),
-            Some(e),
-            None,
+        Err(retry::Error::Internal(e)) => Err(TerraformError::new(
+            CommandError::new("Error while performing Terraform plan and apply.".to_string(), Some(e), None),
+            vec![],
         )),
-    }
+    };
+
+    metrics::observe_duration(
+        &TERRAFORM_APPLY_DURATION_SECONDS,
+        &[
+            ("stack", metrics::terraform_stack_label(root_dir).as_str()),
+            ("outcome", metrics::outcome_label(&result)),
+        ],
+        started_at.elapsed(),
+    );
+
+    result
 }
 
-pub fn terraform_init_validate_state_list(root_dir: &str) -> Result<Vec<String>, CommandError> {
+pub fn terraform_init_validate_state_list(root_dir: &str) -> Result<Vec<String>, TerraformError> {
     // terraform init and validate
-    if let Err(e) = terraform_init_validate(root_dir) {
-        return Err(e);
-    }
+    terraform_init_validate(root_dir)?;
 
     // get terraform state list output
     let result = retry::retry(Fixed::from_millis(3000).take(5), || {
@@ -189,7 +430,7 @@ pub fn terraform_init_validate_state_list(root_dir: &str) -> Result<Vec<String>,
             Ok(out) => OperationResult::Ok(out),
             Err(err) => {
                 // Error while trying to run terraform state list, retrying...
-                OperationResult::Retry(err)
+                retry_after_remediation(root_dir, err)
             }
         }
     });
@@ -197,64 +438,198 @@ pub fn terraform_init_validate_state_list(root_dir: &str) -> Result<Vec<String>,
     match result {
         Ok(output) => Ok(output),
         Err(Operation { error, .. }) => Err(error),
-        Err(retry::Error::Internal(e)) => Err(CommandError::new(
-            "Error while performing Terraform state list.".to_string(),
-            Some(e),
-            None,
+        Err(retry::Error::Internal(e)) => Err(TerraformError::new(
+            CommandError::new("Error while performing Terraform state list.".to_string(), Some(e), None),
+            vec![],
         )),
     }
 }
 
-pub fn terraform_exec(root_dir: &str, args: Vec<&str>) -> Result<Vec<String>, CommandError> {
+fn tf_plugin_cache_dir_value() -> String {
     // override if environment variable is set
-    let tf_plugin_cache_dir_value = match env::var_os(TF_PLUGIN_CACHE_DIR) {
+    match env::var_os(TF_PLUGIN_CACHE_DIR) {
         Some(val) => format!("{:?}", val),
         None => {
             let home_dir = home_dir().expect("Could not find $HOME");
             format!("{}/.terraform.d/plugin-cache", home_dir.to_str().unwrap())
         }
-    };
+    }
+}
 
+/// Returns the whole stdout, to be used for commands with a short output only (i.e: `state list`)
+pub fn terraform_exec(root_dir: &str, args: Vec<&str>) -> Result<Vec<String>, TerraformError> {
     let mut stdout = Vec::new();
-    let mut stderr = Vec::new();
+    terraform_exec_with_output(root_dir, args, true, &mut |line| stdout.push(line.to_string()))?;
+    Ok(stdout)
+}
+
+fn terraform_exec_without_logging(root_dir: &str, args: Vec<&str>) -> Result<Vec<String>, TerraformError> {
+    let mut stdout = Vec::new();
+    terraform_exec_with_output(root_dir, args, false, &mut |line| stdout.push(line.to_string()))?;
+    Ok(stdout)
+}
+
+/// Stdout lines are only streamed to `on_stdout_line`, plan, apply and destroy outputs grow with the resources count
+fn terraform_exec_with_stdout_callback(
+    root_dir: &str,
+    args: Vec<&str>,
+    on_stdout_line: &mut dyn FnMut(&str),
+) -> Result<(), TerraformError> {
+    terraform_exec_with_output(root_dir, args, true, on_stdout_line)
+}
+
+fn terraform_exec_with_output(
+    root_dir: &str,
+    args: Vec<&str>,
+    log_stdout: bool,
+    on_stdout_line: &mut dyn FnMut(&str),
+) -> Result<(), TerraformError> {
+    let (result, stdout, stderr) = terraform_run(root_dir, &args, log_stdout, on_stdout_line);
+
+    result.map_err(|_| terraform_error("Error while performing Terraform command.", &args, &stdout, &stderr))
+}
+
+/// Only the last lines of stdout and stderr are kept, with secrets redacted, to classify and report errors
+fn terraform_run(
+    root_dir: &str,
+    args: &[&str],
+    log_stdout: bool,
+    on_stdout_line: &mut dyn FnMut(&str),
+) -> (Result<(), command::CommandError>, CommandOutputCapture, CommandOutputCapture) {
+    let tf_plugin_cache_dir_value = tf_plugin_cache_dir_value();
+
     let envs = &[(TF_PLUGIN_CACHE_DIR, tf_plugin_cache_dir_value.as_str())];
-    let mut cmd = QoveryCommand::new("terraform", &args, envs);
+    let mut stdout = CommandOutputCapture::new_for_envs(envs);
+    let mut stderr = CommandOutputCapture::new_for_envs(envs);
+    let mut cmd = QoveryCommand::new("terraform", args, envs);
     cmd.set_current_dir(root_dir);
 
     let result = cmd.exec_with_output(
         &mut |line| {
-            info!("{}", line);
-            stdout.push(line);
+            if log_stdout {
+                log_terraform_line(&line);
+            }
+            on_stdout_line(&line);
+            stdout.push(&line);
         },
         &mut |line| {
             error!("{}", line);
-            stderr.push(line);
+            stderr.push(&line);
         },
     );
 
-    stdout.extend(stderr.clone());
+    (result, stdout, stderr)
+}
 
-    match result {
-        Ok(_) => Ok(stdout),
-        Err(_) => Err(CommandError::new_from_command_line(
-            "Error while performing Terraform command.".to_string(),
+/// `-json` output lines are logged with their human readable message
+fn log_terraform_line(line: &str) {
+    match serde_json::from_str::<TerraformUiMessage>(line) {
+        Ok(message) if message.level == "error" => error!("{}", message.message),
+        Ok(message) if message.level == "warn" => warn!("{}", message.message),
+        Ok(message) => info!("{}", message.message),
+        Err(_) => info!("{}", line),
+    }
+}
+
+fn terraform_error(
+    message: &str,
+    args: &[&str],
+    stdout: &CommandOutputCapture,
+    stderr: &CommandOutputCapture,
+) -> TerraformError {
+    let output = stdout.lines().chain(stderr.lines()).cloned().collect::<Vec<String>>();
+
+    TerraformError::new_from_output(
+        CommandError::new_from_command_line(
+            message.to_string(),
             "terraform".to_string(),
             args.iter().map(|e| e.to_string()).collect(),
-            envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
-            Some(stdout.join(" ")),
-            Some(stderr.join(" ")),
-        )),
-    }
+            vec![(TF_PLUGIN_CACHE_DIR.to_string(), tf_plugin_cache_dir_value())],
+            Some(stdout.output(" ")),
+            Some(stderr.output(" ")),
+        ),
+        &output,
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::cmd::terraform::{manage_common_issues, terraform_init_validate};
+    use crate::cmd::terraform::{
+        is_resource_change_applied, parse_planned_changes_count, parse_terraform_drift, parse_terraform_plan_output,
+        terraform_init_validate,
+    };
+    use crate::cmd::terraform_errors::{TerraformError, TerraformRemediation};
     use crate::errors::CommandError;
     use std::fs;
     use tracing::{span, Level};
     use tracing_test::traced_test;
 
+    #[test]
+    fn test_terraform_apply_progress_parsing() {
+        assert_eq!(
+            parse_planned_changes_count(
+                r#"{"@level":"info","@message":"aws_vpc.eks: Plan to create","type":"planned_change","change":{"resource":{"addr":"aws_vpc.eks","resource_type":"aws_vpc"},"action":"create"}}"#
+            ),
+            None
+        );
+        assert_eq!(
+            parse_planned_changes_count(
+                r#"{"@level":"info","@message":"Plan: 2 to add, 1 to change, 1 to destroy.","type":"change_summary","changes":{"add":2,"change":1,"remove":1,"operation":"plan"}}"#
+            ),
+            Some(4)
+        );
+        assert_eq!(parse_planned_changes_count("not json"), None);
+
+        assert!(is_resource_change_applied(
+            r#"{"@level":"info","@message":"aws_vpc.eks: Creation complete after 2s [id=vpc-123]","type":"apply_complete","hook":{}}"#
+        ));
+        assert!(is_resource_change_applied(
+            r#"{"@level":"error","@message":"aws_vpc.eks: Creation errored after 2s","type":"apply_errored","hook":{}}"#
+        ));
+        assert!(!is_resource_change_applied(
+            r#"{"@level":"info","@message":"aws_vpc.eks: Creating...","type":"apply_start","hook":{}}"#
+        ));
+    }
+
+    #[test]
+    fn test_terraform_plan_output_parsing() {
+        let plan_json = r#"{"format_version":"1.0","terraform_version":"1.1.9","resource_changes":[{"address":"digitalocean_database_cluster.postgresql_instance","mode":"managed","type":"digitalocean_database_cluster","name":"postgresql_instance","change":{"actions":["create"],"before":null,"after":{"engine":"pg"}}},{"address":"digitalocean_database_db.postgresql_main","mode":"managed","type":"digitalocean_database_db","name":"postgresql_main","change":{"actions":["no-op"]}}]}"#;
+
+        let plan = parse_terraform_plan_output(plan_json).unwrap();
+        assert_eq!(plan.resource_changes.len(), 2);
+        assert_eq!(plan.resource_changes[0].resource_type, "digitalocean_database_cluster");
+        assert_eq!(plan.resource_changes[0].change.actions, vec!["create".to_string()]);
+        assert!(!plan.resource_changes[0].is_no_op());
+        assert!(plan.resource_changes[1].is_no_op());
+
+        // plan without any resource
+        let plan = parse_terraform_plan_output(r#"{"format_version":"1.0"}"#).unwrap();
+        assert!(plan.resource_changes.is_empty());
+
+        assert!(parse_terraform_plan_output("Error: not a json").is_err());
+    }
+
+    #[test]
+    fn test_terraform_drifts_parsing() {
+        let plan_output = vec![
+            r#"{"@level":"info","@message":"Terraform 1.1.9","@module":"terraform.ui","type":"version","terraform":"1.1.9","ui":"1.0"}"#,
+            r#"{"@level":"info","@message":"aws_eks_cluster.eks_cluster: Drift detected (update)","@module":"terraform.ui","type":"resource_drift","change":{"resource":{"addr":"aws_eks_cluster.eks_cluster","module":"","resource":"aws_eks_cluster.eks_cluster","implied_provider":"aws","resource_type":"aws_eks_cluster","resource_name":"eks_cluster","resource_key":null},"action":"update"}}"#,
+            r#"{"@level":"info","@message":"aws_eks_cluster.eks_cluster: Plan to update","@module":"terraform.ui","type":"planned_change","change":{"resource":{"addr":"aws_eks_cluster.eks_cluster","module":"","resource":"aws_eks_cluster.eks_cluster","implied_provider":"aws","resource_type":"aws_eks_cluster","resource_name":"eks_cluster","resource_key":null},"action":"update"}}"#,
+            r#"{"@level":"info","@message":"Plan: 0 to add, 1 to change, 0 to destroy.","@module":"terraform.ui","type":"change_summary","changes":{"add":0,"change":1,"remove":0,"operation":"plan"}}"#,
+            "not a json line",
+        ];
+
+        let drifts = plan_output
+            .into_iter()
+            .filter_map(parse_terraform_drift)
+            .collect::<Vec<_>>();
+
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(drifts[0].address, "aws_eks_cluster.eks_cluster");
+        assert_eq!(drifts[0].resource_type, "aws_eks_cluster");
+        assert_eq!(drifts[0].action, "update");
+    }
+
     #[test]
     fn test_terraform_managed_errors() {
         let could_not_load_plugin = r#"
@@ -277,8 +652,17 @@ obtain schema: the cached package for registry.terraform.io/hashicorp/time
 in the dependency lock file
         "#;
 
-        let could_not_load_plugin_error = CommandError::new_from_safe_message(could_not_load_plugin.to_string());
-        assert!(manage_common_issues("/tmp/do_not_exists", &could_not_load_plugin_error).is_ok());
+        let could_not_load_plugin_error = TerraformError::new_from_output(
+            CommandError::new_from_safe_message(could_not_load_plugin.to_string()),
+            &could_not_load_plugin
+                .lines()
+                .map(|line| line.to_string())
+                .collect::<Vec<String>>(),
+        );
+        assert_eq!(
+            could_not_load_plugin_error.remediation(false),
+            Some(TerraformRemediation::ReinitializeProviders)
+        );
     }
 
     #[test]
@@ -0,0 +1,472 @@
+use crate::errors::CommandError;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+
+/// A lock older than this is considered left behind by a crashed run
+pub const STALE_STATE_LOCK_DURATION_IN_MINUTES: i64 = 120;
+
+/// Terraform error or warning, with the resource it is attached to if any
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TerraformDiagnostic {
+    pub severity: String,
+    pub summary: String,
+    pub detail: String,
+    pub address: Option<String>,
+}
+
+impl TerraformDiagnostic {
+    fn text(&self) -> String {
+        format!("{}\n{}", self.summary, self.detail)
+    }
+}
+
+/// `diagnostic` message of `-json` machine readable UI output
+/// https://www.terraform.io/internals/machine-readable-ui#diagnostic
+#[derive(Deserialize)]
+struct UiMessage {
+    #[serde(rename = "type")]
+    message_type: String,
+    diagnostic: Option<UiDiagnostic>,
+}
+
+#[derive(Deserialize)]
+struct UiDiagnostic {
+    severity: String,
+    summary: String,
+    #[serde(default)]
+    detail: String,
+    address: Option<String>,
+}
+
+impl From<UiDiagnostic> for TerraformDiagnostic {
+    fn from(diagnostic: UiDiagnostic) -> Self {
+        TerraformDiagnostic {
+            severity: diagnostic.severity,
+            summary: diagnostic.summary,
+            detail: diagnostic.detail,
+            address: diagnostic.address,
+        }
+    }
+}
+
+/// Extract diagnostics from Terraform output, either `-json` machine readable or human readable
+pub fn parse_diagnostics(lines: &[String]) -> Vec<TerraformDiagnostic> {
+    let mut diagnostics: Vec<TerraformDiagnostic> = lines
+        .iter()
+        .filter_map(|line| serde_json::from_str::<UiMessage>(line).ok())
+        .filter(|message| message.message_type == "diagnostic")
+        .filter_map(|message| message.diagnostic.map(TerraformDiagnostic::from))
+        .collect();
+
+    if diagnostics.is_empty() {
+        diagnostics = parse_human_readable_diagnostics(lines);
+    }
+
+    diagnostics
+}
+
+/// Human readable errors look like:
+/// ```text
+/// Error: <summary>
+///
+///   with <resource address>,
+///   on <file> line <n>, in resource "<type>" "<name>":
+///
+/// <detail>
+/// ```
+fn parse_human_readable_diagnostics(lines: &[String]) -> Vec<TerraformDiagnostic> {
+    let mut diagnostics: Vec<TerraformDiagnostic> = vec![];
+
+    for line in lines {
+        // box drawing chars are added on the left by recent terraform versions
+        let trimmed = line.trim_start_matches(|c: char| c == '│' || c == '╷' || c == '╵' || c.is_whitespace());
+
+        if let Some(summary) = trimmed.strip_prefix("Error: ") {
+            diagnostics.push(TerraformDiagnostic {
+                severity: "error".to_string(),
+                summary: summary.trim().to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        if let Some(diagnostic) = diagnostics.last_mut() {
+            match trimmed.strip_prefix("with ") {
+                Some(address) if diagnostic.address.is_none() && diagnostic.detail.is_empty() => {
+                    diagnostic.address = Some(address.trim_end_matches(',').trim().to_string())
+                }
+                _ => {
+                    if !diagnostic.detail.is_empty() || !trimmed.is_empty() {
+                        diagnostic.detail.push_str(trimmed);
+                        diagnostic.detail.push('\n');
+                    }
+                }
+            }
+        }
+    }
+
+    for diagnostic in diagnostics.iter_mut() {
+        diagnostic.detail = diagnostic.detail.trim().to_string();
+    }
+
+    diagnostics
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerraformErrorKind {
+    /// state is locked by another run, or by a run which crashed before releasing it
+    StateLocked {
+        lock_id: Option<String>,
+        created_at: Option<DateTime<Utc>>,
+    },
+    /// cloud provider refused to create resources because account quotas are reached
+    QuotaExceeded,
+    /// cloud provider API rate limit is reached, the request can be retried later
+    Throttled,
+    /// resource to create already exists on cloud provider side but is not in the state.
+    /// It is intentionally never imported automatically, it may not belong to this state.
+    ResourceAlreadyExists {
+        address: Option<String>,
+    },
+    /// provider package doesn't match the checksums of the dependency lock file
+    /// https://github.com/hashicorp/terraform/issues/28041
+    ProviderChecksumMismatch,
+    Unknown,
+}
+
+/// Action which may fix a known Terraform error before retrying
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerraformRemediation {
+    ForceUnlock { lock_id: String },
+    ReinitializeProviders,
+}
+
+struct KnownIssue {
+    /// lowercase patterns, any of them matching a diagnostic identifies the issue
+    patterns: &'static [&'static str],
+    kind: fn(&TerraformDiagnostic) -> TerraformErrorKind,
+}
+
+const KNOWN_ISSUES: &[KnownIssue] = &[
+    KnownIssue {
+        patterns: &["error acquiring the state lock", "error locking state"],
+        kind: |diagnostic| TerraformErrorKind::StateLocked {
+            lock_id: lock_info_field(diagnostic, "ID:"),
+            created_at: lock_info_field(diagnostic, "Created:").and_then(|created_at| parse_lock_date(&created_at)),
+        },
+    },
+    // generic `LimitExceeded` errors are also returned on throttling, only quota specific errors are matched
+    KnownIssue {
+        patterns: &[
+            "throttling",
+            "throttled",
+            "rate exceeded",
+            "requestlimitexceeded",
+            "too many requests",
+            "toomanyrequests",
+        ],
+        kind: |_| TerraformErrorKind::Throttled,
+    },
+    KnownIssue {
+        patterns: &[
+            "quota exceeded",
+            "quotaexceeded",
+            "exceeded quota",
+            "quotas reached",
+            "quota reached",
+            "vcpulimitexceeded",
+            "instancelimitexceeded",
+            "addresslimitexceeded",
+            "vpclimitexceeded",
+        ],
+        kind: |_| TerraformErrorKind::QuotaExceeded,
+    },
+    KnownIssue {
+        patterns: &["already exists", "alreadyexists", "bucketalreadyownedbyyou"],
+        kind: |diagnostic| TerraformErrorKind::ResourceAlreadyExists {
+            address: diagnostic.address.clone(),
+        },
+    },
+    KnownIssue {
+        patterns: &[
+            "does not match any of the checksums",
+            "checksums previously recorded in the dependency lock file",
+            "failed to install provider",
+            "plugin reinitialization required",
+        ],
+        kind: |_| TerraformErrorKind::ProviderChecksumMismatch,
+    },
+];
+
+/// Lock info is printed in diagnostic detail as `  ID:        a6e9c3a4-...`
+fn lock_info_field(diagnostic: &TerraformDiagnostic, field: &str) -> Option<String> {
+    diagnostic
+        .detail
+        .lines()
+        .map(|line| line.trim())
+        .find_map(|line| line.strip_prefix(field))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// ex: `2022-04-07 08:47:43.227329779 +0000 UTC`
+fn parse_lock_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(date.trim_end_matches(" UTC"), "%Y-%m-%d %H:%M:%S%.f %z")
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub struct TerraformError {
+    pub kind: TerraformErrorKind,
+    pub diagnostics: Vec<TerraformDiagnostic>,
+    pub raw_error: CommandError,
+}
+
+impl Display for TerraformError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let description = match &self.kind {
+            TerraformErrorKind::StateLocked { lock_id, .. } => format!(
+                "Terraform state is locked{}",
+                lock_id
+                    .as_ref()
+                    .map(|id| format!(" (lock id: {})", id))
+                    .unwrap_or_default()
+            ),
+            TerraformErrorKind::QuotaExceeded => "Cloud provider quotas are reached".to_string(),
+            TerraformErrorKind::Throttled => "Cloud provider API rate limit is reached".to_string(),
+            TerraformErrorKind::ResourceAlreadyExists { address } => format!(
+                "Resource {}already exists",
+                address
+                    .as_ref()
+                    .map(|address| format!("`{}` ", address))
+                    .unwrap_or_default()
+            ),
+            TerraformErrorKind::ProviderChecksumMismatch => {
+                "Terraform provider doesn't match the dependency lock file checksums".to_string()
+            }
+            TerraformErrorKind::Unknown => self.raw_error.to_string(),
+        };
+
+        match self.diagnostics.first() {
+            Some(diagnostic) if self.kind != TerraformErrorKind::Unknown => {
+                write!(f, "{}: {}", description, diagnostic.summary)
+            }
+            _ => f.write_str(description.as_str()),
+        }
+    }
+}
+
+impl TerraformError {
+    /// Classify the error against known issues, the first diagnostic matching one of them wins
+    pub fn new(raw_error: CommandError, diagnostics: Vec<TerraformDiagnostic>) -> TerraformError {
+        let kind = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == "error")
+            .find_map(|diagnostic| {
+                let text = diagnostic.text().to_lowercase();
+                KNOWN_ISSUES
+                    .iter()
+                    .find(|issue| issue.patterns.iter().any(|pattern| text.contains(pattern)))
+                    .map(|issue| (issue.kind)(diagnostic))
+            })
+            .unwrap_or(TerraformErrorKind::Unknown);
+
+        TerraformError {
+            kind,
+            diagnostics,
+            raw_error,
+        }
+    }
+
+    pub fn new_from_output(raw_error: CommandError, output: &[String]) -> TerraformError {
+        TerraformError::new(raw_error, parse_diagnostics(output))
+    }
+
+    /// Retrying won't help until the user raises quotas or removes the existing resource
+    pub fn is_retryable(&self) -> bool {
+        !matches!(
+            self.kind,
+            TerraformErrorKind::QuotaExceeded | TerraformErrorKind::ResourceAlreadyExists { .. }
+        )
+    }
+
+    /// Stale locks are only released when `force_unlock_stale_locks` is enabled, the run holding it may still be alive
+    pub fn remediation(&self, force_unlock_stale_locks: bool) -> Option<TerraformRemediation> {
+        match &self.kind {
+            TerraformErrorKind::StateLocked {
+                lock_id: Some(lock_id),
+                created_at: Some(created_at),
+            } if force_unlock_stale_locks
+                && Utc::now() - *created_at > Duration::minutes(STALE_STATE_LOCK_DURATION_IN_MINUTES) =>
+            {
+                Some(TerraformRemediation::ForceUnlock {
+                    lock_id: lock_id.to_string(),
+                })
+            }
+            TerraformErrorKind::ProviderChecksumMismatch => Some(TerraformRemediation::ReinitializeProviders),
+            _ => None,
+        }
+    }
+}
+
+impl From<CommandError> for TerraformError {
+    fn from(error: CommandError) -> Self {
+        TerraformError::new(error, vec![])
+    }
+}
+
+impl From<TerraformError> for CommandError {
+    fn from(error: TerraformError) -> Self {
+        error.raw_error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::terraform_errors::{parse_diagnostics, TerraformError, TerraformErrorKind, TerraformRemediation};
+    use crate::errors::CommandError;
+
+    fn lines(output: &str) -> Vec<String> {
+        output.lines().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_json_diagnostics() {
+        let output = lines(
+            r#"{"@level":"info","@message":"Terraform 1.1.9","@module":"terraform.ui","type":"version","terraform":"1.1.9","ui":"1.0"}
+{"@level":"error","@message":"Error: error creating IAM Role (qovery-eks-workers): EntityAlreadyExists: Role with name qovery-eks-workers already exists.","@module":"terraform.ui","type":"diagnostic","diagnostic":{"severity":"error","summary":"error creating IAM Role (qovery-eks-workers): EntityAlreadyExists: Role with name qovery-eks-workers already exists.","detail":"","address":"aws_iam_role.eks_workers"}}"#,
+        );
+
+        let diagnostics = parse_diagnostics(&output);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].address, Some("aws_iam_role.eks_workers".to_string()));
+
+        let error = TerraformError::new(CommandError::new_from_safe_message("apply".to_string()), diagnostics);
+        assert_eq!(
+            error.kind,
+            TerraformErrorKind::ResourceAlreadyExists {
+                address: Some("aws_iam_role.eks_workers".to_string()),
+            }
+        );
+        // existing resources may belong to someone else, they are never imported automatically
+        assert!(!error.is_retryable());
+        assert_eq!(error.remediation(true), None);
+    }
+
+    #[test]
+    fn test_parse_human_readable_diagnostics() {
+        let could_not_load_plugin = lines(
+            r#"
+Error:    Could not load plugin
+
+
+Plugin reinitialization required. Please run "terraform init".
+
+Failed to instantiate provider "registry.terraform.io/hashicorp/time" to
+obtain schema: the cached package for registry.terraform.io/hashicorp/time
+0.7.2 (in .terraform/providers) does not match any of the checksums recorded
+in the dependency lock file
+        "#,
+        );
+
+        let error = TerraformError::new_from_output(
+            CommandError::new_from_safe_message("init".to_string()),
+            &could_not_load_plugin,
+        );
+        assert_eq!(error.diagnostics.len(), 1);
+        assert_eq!(error.diagnostics[0].summary, "Could not load plugin");
+        assert_eq!(error.kind, TerraformErrorKind::ProviderChecksumMismatch);
+        assert_eq!(error.remediation(false), Some(TerraformRemediation::ReinitializeProviders));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_state_lock_classification() {
+        let state_locked = |created_at: &str| {
+            lines(&format!(
+                r#"╷
+│ Error: Error acquiring the state lock
+│
+│ Error message: resource temporarily unavailable
+│ Lock Info:
+│   ID:        a6e9c3a4-4d2c-8b61-f7d5-1f6c1ba6d9b6
+│   Path:      default
+│   Operation: OperationTypeApply
+│   Who:       root@engine
+│   Version:   1.1.9
+│   Created:   {}
+│   Info:
+│
+│ Terraform acquires a state lock to protect the state from being written
+│ by multiple users at the same time.
+╵"#,
+                created_at
+            ))
+        };
+
+        let stale = TerraformError::new_from_output(
+            CommandError::new_from_safe_message("plan".to_string()),
+            &state_locked("2022-04-07 08:47:43.227329779 +0000 UTC"),
+        );
+        assert!(matches!(
+            &stale.kind,
+            TerraformErrorKind::StateLocked { lock_id: Some(id), created_at: Some(_) } if id == "a6e9c3a4-4d2c-8b61-f7d5-1f6c1ba6d9b6"
+        ));
+        assert_eq!(
+            stale.remediation(true),
+            Some(TerraformRemediation::ForceUnlock {
+                lock_id: "a6e9c3a4-4d2c-8b61-f7d5-1f6c1ba6d9b6".to_string()
+            })
+        );
+        assert_eq!(stale.remediation(false), None);
+
+        // a recent lock is likely held by a running deployment, it should not be released
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.f +0000 UTC").to_string();
+        let held = TerraformError::new_from_output(
+            CommandError::new_from_safe_message("plan".to_string()),
+            &state_locked(now.as_str()),
+        );
+        assert!(matches!(held.kind, TerraformErrorKind::StateLocked { .. }));
+        assert_eq!(held.remediation(true), None);
+    }
+
+    #[test]
+    fn test_quota_and_unknown_classification() {
+        let quota = lines(
+            r#"{"@level":"error","@message":"Error: ...","type":"diagnostic","diagnostic":{"severity":"error","summary":"Error launching source instance: VcpuLimitExceeded: You have requested more vCPU capacity than your current vCPU limit of 32 allows","detail":"","address":"aws_instance.ec2_instance"}}"#,
+        );
+        let error = TerraformError::new_from_output(CommandError::new_from_safe_message("apply".to_string()), &quota);
+        assert_eq!(error.kind, TerraformErrorKind::QuotaExceeded);
+        assert!(!error.is_retryable());
+        assert_eq!(error.remediation(true), None);
+
+        let throttling = lines(
+            r#"{"@level":"error","@message":"Error: ...","type":"diagnostic","diagnostic":{"severity":"error","summary":"error creating EKS Node Group: LimitExceededException: Rate exceeded","detail":"","address":"aws_eks_node_group.eks_cluster_workers_1"}}"#,
+        );
+        let error =
+            TerraformError::new_from_output(CommandError::new_from_safe_message("apply".to_string()), &throttling);
+        assert_eq!(error.kind, TerraformErrorKind::Throttled);
+        assert!(error.is_retryable());
+
+        let validate_output = lines(
+            r#"
+Error: Unsupported argument
+
+  on main.tf line 12, in resource "aws_db_instance" "postgresql_instance":
+  12:   foo = "bar"
+
+An argument named "foo" is not expected here.
+"#,
+        );
+        let error = TerraformError::new_from_output(
+            CommandError::new_from_safe_message("validate".to_string()),
+            &validate_output,
+        );
+        assert_eq!(error.diagnostics[0].summary, "Unsupported argument");
+        assert_eq!(error.diagnostics[0].address, None);
+        assert_eq!(error.kind, TerraformErrorKind::Unknown);
+        assert!(error.is_retryable());
+    }
+}
@@ -1,4 +1,5 @@
 pub const TF_PLUGIN_CACHE_DIR: &str = "TF_PLUGIN_CACHE_DIR";
+pub const TF_FORCE_UNLOCK_STALE_LOCKS: &str = "TF_FORCE_UNLOCK_STALE_LOCKS";
 pub const AWS_ACCESS_KEY_ID: &str = "AWS_ACCESS_KEY_ID";
 pub const AWS_SECRET_ACCESS_KEY: &str = "AWS_SECRET_ACCESS_KEY";
 pub const AWS_DEFAULT_REGION: &str = "AWS_DEFAULT_REGION";
@@ -1,5 +1,11 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
 use crypto::digest::Digest;
+use crypto::hmac::Hmac;
+use crypto::mac::Mac;
 use crypto::sha1::Sha1;
+use crypto::sha2::Sha256;
+use std::fmt::Write;
+use x509_parser::pem::parse_x509_pem;
 
 pub fn to_sha1(input: &str) -> String {
     let mut hasher = Sha1::new();
@@ -13,6 +19,41 @@ pub fn to_sha1_truncate_16(input: &str) -> String {
     hash_str
 }
 
+/// Returns hex encoded HMAC-SHA256 of `input` signed with `key`
+pub fn to_hmac_sha256(key: &str, input: &str) -> String {
+    let mut mac = Hmac::new(Sha256::new(), key.as_bytes());
+    mac.input(input.as_bytes());
+    mac.result().code().iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+/// Returns `password` hashed with salted SHA-1 in the RFC 2307 `{SSHA}` format understood by nginx basic auth
+pub fn to_ssha(password: &str, salt: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.input(password.as_bytes());
+    hasher.input(salt);
+    let mut hash = vec![0; hasher.output_bytes()];
+    hasher.result(&mut hash);
+    hash.extend_from_slice(salt);
+
+    format!("{{SSHA}}{}", base64::encode(hash))
+}
+
+/// Returns the expiration date (`notAfter`) of the first certificate of a PEM encoded chain
+pub fn to_certificate_expiration_date(pem: &str) -> Result<DateTime<Utc>, String> {
+    let (_, pem) = parse_x509_pem(pem.as_bytes()).map_err(|e| format!("no valid PEM certificate found: {}", e))?;
+    let certificate = pem
+        .parse_x509()
+        .map_err(|e| format!("certificate is not valid: {}", e))?;
+
+    let not_after = certificate.validity().not_after.timestamp();
+    NaiveDateTime::from_timestamp_opt(not_after, 0)
+        .map(|date| DateTime::from_utc(date, Utc))
+        .ok_or_else(|| format!("certificate expiration date `{}` is not valid", not_after))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,4 +123,65 @@ mod tests {
             assert_eq!(tc.expected_output, result, "case {} : '{}'", tc.description, tc.input);
         }
     }
+
+    #[test]
+    fn test_to_hmac_sha256() {
+        // https://datatracker.ietf.org/doc/html/rfc4231#section-4.3
+        assert_eq!(
+            to_hmac_sha256("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_to_ssha() {
+        // base64(sha1(password + salt) + salt)
+        assert_eq!(to_ssha("secret", b"salt"), "{SSHA}gVK8WC9YyFT1gMsQHTGCgT3sSv5zYWx0");
+    }
+
+    #[test]
+    fn test_to_certificate_expiration_date() {
+        // openssl req -x509 -newkey ec -pkeyopt ec_paramgen_curve:prime256v1 -not_after 20300615120000Z
+        let utc_time_certificate = "-----BEGIN CERTIFICATE-----
+MIIBgTCCASegAwIBAgIUKgiGRxKPFLiKqcyk1QQHXiYPEDYwCgYIKoZIzj0EAwIw
+FjEUMBIGA1UEAwwLZXhhbXBsZS5jb20wHhcNMjAwMTAxMDAwMDAwWhcNMzAwNjE1
+MTIwMDAwWjAWMRQwEgYDVQQDDAtleGFtcGxlLmNvbTBZMBMGByqGSM49AgEGCCqG
+SM49AwEHA0IABBW4Ckgic0CSPkkqUy83lisePCmujvTK5g2ps/oFcjz3p9f3HaUf
+9qKrSQDTeV2ZSXvF7Wjb2GzKefegv5GQyNWjUzBRMB0GA1UdDgQWBBTeF2Q9otkO
+XLG0teiwKSA3HUeglTAfBgNVHSMEGDAWgBTeF2Q9otkOXLG0teiwKSA3HUeglTAP
+BgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCIQCRjo3WJ3gnlXvHfzFr
+F/bmyOKKlmj0IJo0K9i8rsRqlQIgcVE6Q8BSCXOtmgdzvTcJtO8+ZeeyVfrLiGFQ
+TD7grng=
+-----END CERTIFICATE-----
+";
+        // same with -not_after 20501231235959Z, encoded as a GeneralizedTime
+        let generalized_time_certificate = "-----BEGIN CERTIFICATE-----
+MIIBgzCCASmgAwIBAgIUEJUiTQbi39xeWUvRIv0HDnMAZh4wCgYIKoZIzj0EAwIw
+FjEUMBIGA1UEAwwLZXhhbXBsZS5jb20wIBcNMjAwMTAxMDAwMDAwWhgPMjA1MDEy
+MzEyMzU5NTlaMBYxFDASBgNVBAMMC2V4YW1wbGUuY29tMFkwEwYHKoZIzj0CAQYI
+KoZIzj0DAQcDQgAEp8MbZG1eHIwuvKGH/A12amn1wPaNobXGasj1LabydpxyemG4
+8WtqWXaWiFi1z0Fw3Md6QsS2ZG1VrF9NyJIlOqNTMFEwHQYDVR0OBBYEFJnEKtG5
+ELVyR973VLmxPa5EBLRYMB8GA1UdIwQYMBaAFJnEKtG5ELVyR973VLmxPa5EBLRY
+MA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSAAwRQIhAOCT4VOiHAa5wLT2
+e23oCqN4QgoDX13ekvGBIL7tJtt2AiBSmC/ON3OwzBRTG+aoGZA3o1HDu27W3EE2
+vcJRXsUCEw==
+-----END CERTIFICATE-----
+";
+
+        assert_eq!(
+            to_certificate_expiration_date(utc_time_certificate).map(|date| date.to_rfc3339()),
+            Ok("2030-06-15T12:00:00+00:00".to_string())
+        );
+        // the first certificate of a chain is the one served
+        assert_eq!(
+            to_certificate_expiration_date(&format!("{}{}", generalized_time_certificate, utc_time_certificate))
+                .map(|date| date.to_rfc3339()),
+            Ok("2050-12-31T23:59:59+00:00".to_string())
+        );
+        assert!(to_certificate_expiration_date("").is_err());
+        assert!(
+            to_certificate_expiration_date("-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----").is_err()
+        );
+        assert!(to_certificate_expiration_date(&utc_time_certificate.replace("MIIBgTCC", "MIIBgTDD")).is_err());
+    }
 }
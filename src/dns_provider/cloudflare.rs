@@ -1,9 +1,23 @@
 use std::net::Ipv4Addr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use tera::Context as TeraContext;
 
 use crate::dns_provider::errors::DnsProviderError;
-use crate::dns_provider::{DnsProvider, DnsProviderConfiguration, Kind};
+use crate::dns_provider::{
+    normalize_record_value, zone_name_candidates, DnsProvider, DnsProviderConfiguration, DnsRecord, DnsRecordType,
+    DnsZone, Kind,
+};
 use crate::io_models::{Context, Domain};
+use crate::utilities::get_header_with_bearer;
+
+const CLOUDFLARE_API_URL: &str = "https://api.cloudflare.com/client/v4";
+// calls are made during deployments, an unresponsive API must not hold them
+const CLOUDFLARE_API_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub struct CloudflareDnsConfig {
     pub cloudflare_email: String,
@@ -37,6 +51,133 @@ impl Cloudflare {
             cloudflare_email: cloudflare_email.to_string(),
         }
     }
+
+    fn call_api<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&CloudflareDnsRecordRequest>,
+    ) -> Result<T, String> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(CLOUDFLARE_API_TIMEOUT)
+            .build()
+            .map_err(|err| format!("unable to create Cloudflare API client: {}", err))?;
+        let mut request = client
+            .request(method, format!("{}{}", CLOUDFLARE_API_URL, path))
+            .headers(get_header_with_bearer(&self.cloudflare_api_token))
+            .query(query);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request
+            .send()
+            .map_err(|err| format!("unable to get a response from Cloudflare API: {}", err))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .map_err(|err| format!("unable to read Cloudflare API response: {}", err))?;
+
+        parse_cloudflare_response(&body).map_err(|err| format!("{} (status {})", err, status))
+    }
+
+    fn zone_records(
+        &self,
+        zone_id: &str,
+        name: &str,
+        record_type: Option<DnsRecordType>,
+    ) -> Result<Vec<CloudflareDnsRecord>, String> {
+        let mut query = vec![("name", name), ("per_page", "100")];
+        if let Some(record_type) = &record_type {
+            query.push(("type", record_type.as_str()));
+        }
+
+        self.call_api(Method::GET, &format!("/zones/{}/dns_records", zone_id), &query, None)
+    }
+}
+
+#[derive(Deserialize)]
+struct CloudflareResponse<T> {
+    success: bool,
+    #[serde(default)]
+    errors: Vec<CloudflareApiError>,
+    result: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct CloudflareApiError {
+    code: u32,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct CloudflareZone {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CloudflareDnsRecord {
+    id: String,
+    #[serde(rename = "type")]
+    record_type: String,
+    name: String,
+    content: String,
+    ttl: u32,
+}
+
+#[derive(Serialize)]
+struct CloudflareDnsRecordRequest<'a> {
+    #[serde(rename = "type")]
+    record_type: &'a str,
+    name: &'a str,
+    content: &'a str,
+    ttl: u32,
+}
+
+impl<'a> From<&'a DnsRecord> for CloudflareDnsRecordRequest<'a> {
+    fn from(record: &'a DnsRecord) -> Self {
+        CloudflareDnsRecordRequest {
+            record_type: record.record_type.as_str(),
+            name: &record.name,
+            content: &record.value,
+            ttl: record.ttl,
+        }
+    }
+}
+
+impl CloudflareDnsRecord {
+    /// Records of other types than the managed ones are ignored
+    fn to_dns_record(&self) -> Option<DnsRecord> {
+        let record_type = DnsRecordType::from_str(&self.record_type).ok()?;
+        Some(DnsRecord {
+            name: self.name.clone(),
+            record_type,
+            value: normalize_record_value(record_type, &self.content),
+            ttl: self.ttl,
+        })
+    }
+}
+
+/// Every Cloudflare API response is wrapped in an envelope telling if the call succeeded
+fn parse_cloudflare_response<T: DeserializeOwned>(body: &str) -> Result<T, String> {
+    let response: CloudflareResponse<T> =
+        serde_json::from_str(body).map_err(|err| format!("unexpected Cloudflare API response: {}", err))?;
+
+    match (response.success, response.result) {
+        (true, Some(result)) => Ok(result),
+        (true, None) => Err("Cloudflare API response has no result".to_string()),
+        (false, _) => Err(format!(
+            "Cloudflare API error: {}",
+            response
+                .errors
+                .iter()
+                .map(|err| format!("{} ({})", err.message, err.code))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
 }
 
 impl DnsProvider for Cloudflare {
@@ -89,4 +230,153 @@ impl DnsProvider for Cloudflare {
             Ok(())
         }
     }
+
+    fn find_zone(&self, name: &str) -> Result<DnsZone, DnsProviderError> {
+        for candidate in zone_name_candidates(name) {
+            let zones: Vec<CloudflareZone> = self
+                .call_api(Method::GET, "/zones", &[("name", candidate.as_str())], None)
+                .map_err(|raw_error_message| DnsProviderError::CannotFindZone {
+                    name: name.to_string(),
+                    raw_error_message,
+                })?;
+
+            if let Some(zone) = zones.into_iter().find(|zone| zone.name == candidate) {
+                return Ok(DnsZone {
+                    id: zone.id,
+                    name: zone.name,
+                });
+            }
+        }
+
+        Err(DnsProviderError::CannotFindZone {
+            name: name.to_string(),
+            raw_error_message: "no zone of the Cloudflare account hosts it".to_string(),
+        })
+    }
+
+    fn get_records(&self, name: &str, record_type: Option<DnsRecordType>) -> Result<Vec<DnsRecord>, DnsProviderError> {
+        let zone = self.find_zone(name)?;
+        let records = self
+            .zone_records(&zone.id, name, record_type)
+            .map_err(|raw_error_message| DnsProviderError::CannotGetRecords {
+                name: name.to_string(),
+                raw_error_message,
+            })?;
+
+        Ok(records.iter().filter_map(|record| record.to_dns_record()).collect())
+    }
+
+    fn upsert_record(&self, record: &DnsRecord) -> Result<(), DnsProviderError> {
+        let to_error = |raw_error_message| DnsProviderError::CannotUpsertRecord {
+            name: record.name.clone(),
+            raw_error_message,
+        };
+        let zone = self.find_zone(&record.name)?;
+        let existing_records = self
+            .zone_records(&zone.id, &record.name, Some(record.record_type))
+            .map_err(to_error)?;
+        let request = CloudflareDnsRecordRequest::from(record);
+        let records_path = format!("/zones/{}/dns_records", zone.id);
+
+        match existing_records.split_first() {
+            None => {
+                self.call_api::<serde_json::Value>(Method::POST, &records_path, &[], Some(&request))
+                    .map_err(to_error)?;
+            }
+            Some((replaced, others)) => {
+                self.call_api::<serde_json::Value>(
+                    Method::PUT,
+                    &format!("{}/{}", records_path, replaced.id),
+                    &[],
+                    Some(&request),
+                )
+                .map_err(to_error)?;
+                for other in others {
+                    self.call_api::<serde_json::Value>(
+                        Method::DELETE,
+                        &format!("{}/{}", records_path, other.id),
+                        &[],
+                        None,
+                    )
+                    .map_err(to_error)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn delete_record(&self, record: &DnsRecord) -> Result<(), DnsProviderError> {
+        let to_error = |raw_error_message| DnsProviderError::CannotDeleteRecord {
+            name: record.name.clone(),
+            raw_error_message,
+        };
+        let zone = self.find_zone(&record.name)?;
+        let existing_records = self
+            .zone_records(&zone.id, &record.name, Some(record.record_type))
+            .map_err(to_error)?;
+
+        let value = normalize_record_value(record.record_type, &record.value);
+        for existing_record in existing_records
+            .iter()
+            .filter(|existing_record| normalize_record_value(record.record_type, &existing_record.content) == value)
+        {
+            self.call_api::<serde_json::Value>(
+                Method::DELETE,
+                &format!("/zones/{}/dns_records/{}", zone.id, existing_record.id),
+                &[],
+                None,
+            )
+            .map_err(to_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dns_provider::cloudflare::{parse_cloudflare_response, CloudflareDnsRecord};
+    use crate::dns_provider::{DnsRecord, DnsRecordType};
+
+    #[test]
+    fn test_parse_cloudflare_response() {
+        let records: Vec<CloudflareDnsRecord> = parse_cloudflare_response(
+            r#"{"success": true, "errors": [], "messages": [], "result": [
+                {"id": "1", "type": "CNAME", "name": "app.example.com", "content": "lb.example.com", "ttl": 300, "proxied": false},
+                {"id": "2", "type": "TXT", "name": "app.example.com", "content": "\"heritage=external-dns\"", "ttl": 1},
+                {"id": "3", "type": "MX", "name": "app.example.com", "content": "mail.example.com", "ttl": 1}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            records
+                .iter()
+                .filter_map(|record| record.to_dns_record())
+                .collect::<Vec<_>>(),
+            vec![
+                DnsRecord {
+                    name: "app.example.com".to_string(),
+                    record_type: DnsRecordType::CNAME,
+                    value: "lb.example.com".to_string(),
+                    ttl: 300,
+                },
+                DnsRecord {
+                    name: "app.example.com".to_string(),
+                    record_type: DnsRecordType::TXT,
+                    value: "heritage=external-dns".to_string(),
+                    ttl: 1,
+                },
+            ]
+        );
+
+        let error = parse_cloudflare_response::<Vec<CloudflareDnsRecord>>(
+            r#"{"success": false, "errors": [{"code": 10000, "message": "Authentication error"}], "result": null}"#,
+        );
+        assert_eq!(
+            error.err(),
+            Some("Cloudflare API error: Authentication error (10000)".to_string())
+        );
+    }
 }
@@ -4,4 +4,14 @@ use thiserror::Error;
 pub enum DnsProviderError {
     #[error("Invalid credentials error.")]
     InvalidCredentials,
+    #[error("Cannot find zone error for `{name:?}`: {raw_error_message:?}.")]
+    CannotFindZone { name: String, raw_error_message: String },
+    #[error("Cannot get records error for `{name:?}`: {raw_error_message:?}.")]
+    CannotGetRecords { name: String, raw_error_message: String },
+    #[error("Cannot upsert record error for `{name:?}`: {raw_error_message:?}.")]
+    CannotUpsertRecord { name: String, raw_error_message: String },
+    #[error("Cannot delete record error for `{name:?}`: {raw_error_message:?}.")]
+    CannotDeleteRecord { name: String, raw_error_message: String },
+    #[error("Records management is not supported by dns provider `{provider_name:?}`.")]
+    RecordsManagementNotSupported { provider_name: String },
 }
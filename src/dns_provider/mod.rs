@@ -1,8 +1,10 @@
 use std::net::Ipv4Addr;
+use std::str::FromStr;
 
 use crate::dns_provider::cloudflare::CloudflareDnsConfig;
 use crate::dns_provider::errors::DnsProviderError;
 use crate::dns_provider::qoverydns::QoveryDnsConfig;
+use crate::dns_provider::route53::Route53DnsConfig;
 use serde::{Deserialize, Serialize};
 use tera::Context as TeraContext;
 
@@ -11,17 +13,20 @@ use crate::io_models::{Context, Domain};
 pub mod cloudflare;
 pub mod errors;
 pub mod qoverydns;
+pub mod route53;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Kind {
     Cloudflare,
     QoveryDns,
+    Route53,
 }
 
 pub enum DnsProviderConfiguration {
     Cloudflare(CloudflareDnsConfig),
     QoveryDns(QoveryDnsConfig),
+    Route53(Route53DnsConfig),
 }
 
 impl DnsProviderConfiguration {
@@ -29,6 +34,7 @@ impl DnsProviderConfiguration {
         match self {
             DnsProviderConfiguration::Cloudflare(_) => "cloudflare",
             DnsProviderConfiguration::QoveryDns(_) => "pdns",
+            DnsProviderConfiguration::Route53(_) => "route53",
         }
         .to_string()
     }
@@ -48,4 +54,107 @@ pub trait DnsProvider {
     fn domain(&self) -> &Domain;
     fn resolvers(&self) -> Vec<Ipv4Addr>;
     fn is_valid(&self) -> Result<(), DnsProviderError>;
+    /// Zone hosting the given name, the most specific one when sub zones are delegated
+    fn find_zone(&self, name: &str) -> Result<DnsZone, DnsProviderError>;
+    /// Records of the given name, of any type when none is given
+    fn get_records(&self, name: &str, record_type: Option<DnsRecordType>) -> Result<Vec<DnsRecord>, DnsProviderError>;
+    /// Creates the record, replacing the existing records of the same name and type
+    fn upsert_record(&self, record: &DnsRecord) -> Result<(), DnsProviderError>;
+    /// Deletes the record, other values of the same name and type are kept
+    fn delete_record(&self, record: &DnsRecord) -> Result<(), DnsProviderError>;
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DnsRecordType {
+    A,
+    AAAA,
+    CNAME,
+    TXT,
+}
+
+impl DnsRecordType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            DnsRecordType::A => "A",
+            DnsRecordType::AAAA => "AAAA",
+            DnsRecordType::CNAME => "CNAME",
+            DnsRecordType::TXT => "TXT",
+        }
+    }
+}
+
+impl FromStr for DnsRecordType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "A" => Ok(DnsRecordType::A),
+            "AAAA" => Ok(DnsRecordType::AAAA),
+            "CNAME" => Ok(DnsRecordType::CNAME),
+            "TXT" => Ok(DnsRecordType::TXT),
+            _ => Err(format!("`{}` records are not managed", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DnsRecord {
+    /// fully qualified name, without the trailing dot
+    pub name: String,
+    pub record_type: DnsRecordType,
+    /// address, target name without the trailing dot, or text without the surrounding quotes
+    pub value: String,
+    pub ttl: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DnsZone {
+    pub id: String,
+    /// without the trailing dot
+    pub name: String,
+}
+
+/// Zones which could host the given name, from the most specific one to the top level domain excluded
+pub fn zone_name_candidates(name: &str) -> Vec<String> {
+    let labels = name.trim_end_matches('.').split('.').collect::<Vec<_>>();
+    (0..labels.len().saturating_sub(1))
+        .map(|idx| labels[idx..].join("."))
+        .collect()
+}
+
+/// Names and values are compared without the trailing dot, and texts without their quotes
+pub fn normalize_record_value(record_type: DnsRecordType, value: &str) -> String {
+    match record_type {
+        DnsRecordType::TXT => value.trim_matches('"').to_string(),
+        DnsRecordType::CNAME => value.trim_end_matches('.').to_string(),
+        DnsRecordType::A | DnsRecordType::AAAA => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dns_provider::{normalize_record_value, zone_name_candidates, DnsRecordType};
+
+    #[test]
+    fn test_zone_name_candidates() {
+        assert_eq!(
+            zone_name_candidates("app.cluster.example.com."),
+            vec!["app.cluster.example.com", "cluster.example.com", "example.com"]
+        );
+        assert_eq!(zone_name_candidates("example.com"), vec!["example.com"]);
+        assert!(zone_name_candidates("localhost").is_empty());
+    }
+
+    #[test]
+    fn test_normalize_record_value() {
+        assert_eq!(
+            normalize_record_value(DnsRecordType::TXT, "\"heritage=external-dns\""),
+            "heritage=external-dns"
+        );
+        assert_eq!(
+            normalize_record_value(DnsRecordType::CNAME, "lb.example.com."),
+            "lb.example.com"
+        );
+        assert_eq!(normalize_record_value(DnsRecordType::A, "10.0.0.1"), "10.0.0.1");
+    }
 }
@@ -2,7 +2,7 @@ use std::net::Ipv4Addr;
 use tera::Context as TeraContext;
 
 use crate::dns_provider::errors::DnsProviderError;
-use crate::dns_provider::{DnsProvider, DnsProviderConfiguration, Kind};
+use crate::dns_provider::{DnsProvider, DnsProviderConfiguration, DnsRecord, DnsRecordType, DnsZone, Kind};
 use crate::io_models::{Context, Domain};
 
 pub struct QoveryDnsConfig {
@@ -95,4 +95,33 @@ impl DnsProvider for QoveryDns {
             Ok(())
         }
     }
+
+    // records are only managed by external-dns through the Qovery DNS api
+    fn find_zone(&self, _name: &str) -> Result<DnsZone, DnsProviderError> {
+        Err(DnsProviderError::RecordsManagementNotSupported {
+            provider_name: self.provider_name().to_string(),
+        })
+    }
+
+    fn get_records(
+        &self,
+        _name: &str,
+        _record_type: Option<DnsRecordType>,
+    ) -> Result<Vec<DnsRecord>, DnsProviderError> {
+        Err(DnsProviderError::RecordsManagementNotSupported {
+            provider_name: self.provider_name().to_string(),
+        })
+    }
+
+    fn upsert_record(&self, _record: &DnsRecord) -> Result<(), DnsProviderError> {
+        Err(DnsProviderError::RecordsManagementNotSupported {
+            provider_name: self.provider_name().to_string(),
+        })
+    }
+
+    fn delete_record(&self, _record: &DnsRecord) -> Result<(), DnsProviderError> {
+        Err(DnsProviderError::RecordsManagementNotSupported {
+            provider_name: self.provider_name().to_string(),
+        })
+    }
 }
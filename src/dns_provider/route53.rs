@@ -0,0 +1,363 @@
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use rusoto_core::{Client, HttpClient, Region};
+use rusoto_credential::StaticProvider;
+use rusoto_route53::{
+    Change, ChangeBatch, ChangeResourceRecordSetsRequest, ListHostedZonesByNameRequest, ListResourceRecordSetsRequest,
+    ResourceRecord, ResourceRecordSet, Route53, Route53Client,
+};
+use tera::Context as TeraContext;
+
+use crate::dns_provider::errors::DnsProviderError;
+use crate::dns_provider::{
+    normalize_record_value, zone_name_candidates, DnsProvider, DnsProviderConfiguration, DnsRecord, DnsRecordType,
+    DnsZone, Kind,
+};
+use crate::io_models::{Context, Domain};
+use crate::runtime::block_on;
+
+pub struct Route53DnsConfig {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+pub struct Route53Dns {
+    context: Context,
+    id: String,
+    name: String,
+    domain: Domain,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl Route53Dns {
+    pub fn new(
+        context: Context,
+        id: &str,
+        name: &str,
+        domain: Domain,
+        access_key_id: &str,
+        secret_access_key: &str,
+    ) -> Self {
+        Route53Dns {
+            context,
+            id: id.to_string(),
+            name: name.to_string(),
+            domain,
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+        }
+    }
+
+    fn get_route53_client(&self) -> Route53Client {
+        let credentials = StaticProvider::new(self.access_key_id.clone(), self.secret_access_key.clone(), None, None);
+        let client = Client::new_with(credentials, HttpClient::new().unwrap());
+
+        // Route53 is a global service, served from us-east-1
+        Route53Client::new_with_client(client, Region::UsEast1)
+    }
+
+    fn record_sets(
+        &self,
+        zone_id: &str,
+        name: &str,
+        record_type: Option<DnsRecordType>,
+    ) -> Result<Vec<ResourceRecordSet>, String> {
+        // record sets are listed in order, starting from the requested one
+        let response = block_on(
+            self.get_route53_client()
+                .list_resource_record_sets(ListResourceRecordSetsRequest {
+                    hosted_zone_id: zone_id.to_string(),
+                    start_record_name: Some(name.to_string()),
+                    start_record_type: record_type.map(|record_type| record_type.as_str().to_string()),
+                    ..Default::default()
+                }),
+        )
+        .map_err(|err| err.to_string())?;
+
+        Ok(response
+            .resource_record_sets
+            .into_iter()
+            .filter(|record_set| {
+                record_set.name.trim_end_matches('.') == name.trim_end_matches('.')
+                    && record_type.map_or(true, |record_type| record_set.type_ == record_type.as_str())
+            })
+            .collect())
+    }
+
+    fn change_record_set(&self, zone_id: &str, action: &str, record_set: ResourceRecordSet) -> Result<(), String> {
+        block_on(
+            self.get_route53_client()
+                .change_resource_record_sets(ChangeResourceRecordSetsRequest {
+                    hosted_zone_id: zone_id.to_string(),
+                    change_batch: ChangeBatch {
+                        changes: vec![Change {
+                            action: action.to_string(),
+                            resource_record_set: record_set,
+                        }],
+                        comment: None,
+                    },
+                }),
+        )
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+    }
+}
+
+/// Values of a record set, alias records created by external-dns for load balancers have their target as value
+fn to_dns_records(record_set: &ResourceRecordSet) -> Vec<DnsRecord> {
+    let record_type = match DnsRecordType::from_str(&record_set.type_) {
+        Ok(record_type) => record_type,
+        Err(_) => return vec![],
+    };
+    let values = match (&record_set.alias_target, &record_set.resource_records) {
+        (Some(alias_target), _) => vec![alias_target.dns_name.trim_end_matches('.').to_string()],
+        (None, Some(resource_records)) => resource_records.iter().map(|record| record.value.clone()).collect(),
+        (None, None) => vec![],
+    };
+
+    values
+        .iter()
+        .map(|value| DnsRecord {
+            name: record_set.name.trim_end_matches('.').to_string(),
+            record_type,
+            value: normalize_record_value(record_type, value),
+            ttl: record_set.ttl.unwrap_or_default() as u32,
+        })
+        .collect()
+}
+
+/// Route53 expects texts to be quoted
+fn to_resource_record(record_type: DnsRecordType, value: &str) -> ResourceRecord {
+    ResourceRecord {
+        value: match record_type {
+            DnsRecordType::TXT => format!("\"{}\"", value.trim_matches('"')),
+            DnsRecordType::A | DnsRecordType::AAAA | DnsRecordType::CNAME => value.to_string(),
+        },
+    }
+}
+
+impl DnsProvider for Route53Dns {
+    fn context(&self) -> &Context {
+        &self.context
+    }
+
+    fn provider_name(&self) -> &str {
+        "aws"
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::Route53
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn insert_into_teracontext<'a>(&self, context: &'a mut TeraContext) -> &'a mut TeraContext {
+        context.insert("external_dns_provider", &self.provider_name());
+        context.insert("route53_access_key_id", &self.access_key_id);
+        context.insert("route53_secret_access_key", &self.secret_access_key);
+        context
+    }
+
+    fn provider_configuration(&self) -> DnsProviderConfiguration {
+        DnsProviderConfiguration::Route53(Route53DnsConfig {
+            access_key_id: self.access_key_id.clone(),
+            secret_access_key: self.secret_access_key.clone(),
+        })
+    }
+
+    fn domain(&self) -> &Domain {
+        &self.domain
+    }
+
+    fn resolvers(&self) -> Vec<Ipv4Addr> {
+        vec![Ipv4Addr::new(8, 8, 8, 8), Ipv4Addr::new(8, 8, 4, 4)]
+    }
+
+    fn is_valid(&self) -> Result<(), DnsProviderError> {
+        if self.access_key_id.is_empty() || self.secret_access_key.is_empty() {
+            Err(DnsProviderError::InvalidCredentials)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn find_zone(&self, name: &str) -> Result<DnsZone, DnsProviderError> {
+        let client = self.get_route53_client();
+        for candidate in zone_name_candidates(name) {
+            let response = block_on(client.list_hosted_zones_by_name(ListHostedZonesByNameRequest {
+                dns_name: Some(candidate.clone()),
+                max_items: Some("10".to_string()),
+                ..Default::default()
+            }))
+            .map_err(|err| DnsProviderError::CannotFindZone {
+                name: name.to_string(),
+                raw_error_message: err.to_string(),
+            })?;
+
+            // private zones of the same name are only resolved inside their VPC
+            if let Some(zone) = response.hosted_zones.into_iter().find(|zone| {
+                zone.name.trim_end_matches('.') == candidate
+                    && !zone
+                        .config
+                        .as_ref()
+                        .and_then(|config| config.private_zone)
+                        .unwrap_or(false)
+            }) {
+                return Ok(DnsZone {
+                    id: zone.id.trim_start_matches("/hostedzone/").to_string(),
+                    name: candidate,
+                });
+            }
+        }
+
+        Err(DnsProviderError::CannotFindZone {
+            name: name.to_string(),
+            raw_error_message: "no public hosted zone of the AWS account hosts it".to_string(),
+        })
+    }
+
+    fn get_records(&self, name: &str, record_type: Option<DnsRecordType>) -> Result<Vec<DnsRecord>, DnsProviderError> {
+        let zone = self.find_zone(name)?;
+        let record_sets = self
+            .record_sets(&zone.id, name, record_type)
+            .map_err(|raw_error_message| DnsProviderError::CannotGetRecords {
+                name: name.to_string(),
+                raw_error_message,
+            })?;
+
+        Ok(record_sets.iter().flat_map(to_dns_records).collect())
+    }
+
+    fn upsert_record(&self, record: &DnsRecord) -> Result<(), DnsProviderError> {
+        let zone = self.find_zone(&record.name)?;
+        let record_set = ResourceRecordSet {
+            name: record.name.clone(),
+            type_: record.record_type.as_str().to_string(),
+            ttl: Some(record.ttl as i64),
+            resource_records: Some(vec![to_resource_record(record.record_type, &record.value)]),
+            ..Default::default()
+        };
+
+        self.change_record_set(&zone.id, "UPSERT", record_set)
+            .map_err(|raw_error_message| DnsProviderError::CannotUpsertRecord {
+                name: record.name.clone(),
+                raw_error_message,
+            })
+    }
+
+    fn delete_record(&self, record: &DnsRecord) -> Result<(), DnsProviderError> {
+        let to_error = |raw_error_message| DnsProviderError::CannotDeleteRecord {
+            name: record.name.clone(),
+            raw_error_message,
+        };
+        let zone = self.find_zone(&record.name)?;
+        let value = normalize_record_value(record.record_type, &record.value);
+
+        for record_set in self
+            .record_sets(&zone.id, &record.name, Some(record.record_type))
+            .map_err(to_error)?
+        {
+            let values = to_dns_records(&record_set);
+            if !values.iter().any(|existing_record| existing_record.value == value) {
+                continue;
+            }
+
+            // a record set is deleted with its exact content, it is updated when other values remain
+            let remaining_values = values
+                .iter()
+                .filter(|existing_record| existing_record.value != value)
+                .map(|existing_record| to_resource_record(record.record_type, &existing_record.value))
+                .collect::<Vec<_>>();
+            match remaining_values.is_empty() || record_set.alias_target.is_some() {
+                true => self.change_record_set(&zone.id, "DELETE", record_set),
+                false => self.change_record_set(
+                    &zone.id,
+                    "UPSERT",
+                    ResourceRecordSet {
+                        resource_records: Some(remaining_values),
+                        ..record_set
+                    },
+                ),
+            }
+            .map_err(to_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dns_provider::route53::{to_dns_records, to_resource_record};
+    use crate::dns_provider::{DnsRecord, DnsRecordType};
+    use rusoto_route53::{AliasTarget, ResourceRecord, ResourceRecordSet};
+
+    #[test]
+    fn test_to_dns_records() {
+        let alias = ResourceRecordSet {
+            name: "app.example.com.".to_string(),
+            type_: "A".to_string(),
+            alias_target: Some(AliasTarget {
+                dns_name: "lb-123.elb.eu-west-3.amazonaws.com.".to_string(),
+                evaluate_target_health: true,
+                hosted_zone_id: "Z3Q77PNBQS71R4".to_string(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_dns_records(&alias),
+            vec![DnsRecord {
+                name: "app.example.com".to_string(),
+                record_type: DnsRecordType::A,
+                value: "lb-123.elb.eu-west-3.amazonaws.com".to_string(),
+                ttl: 0,
+            }]
+        );
+
+        let texts = ResourceRecordSet {
+            name: "qvy-app.example.com.".to_string(),
+            type_: "TXT".to_string(),
+            ttl: Some(300),
+            resource_records: Some(vec![
+                ResourceRecord {
+                    value: "\"heritage=external-dns\"".to_string(),
+                },
+                ResourceRecord {
+                    value: "\"other\"".to_string(),
+                },
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_dns_records(&texts)
+                .into_iter()
+                .map(|record| record.value)
+                .collect::<Vec<_>>(),
+            vec!["heritage=external-dns", "other"]
+        );
+
+        let mx = ResourceRecordSet {
+            type_: "MX".to_string(),
+            ..texts
+        };
+        assert!(to_dns_records(&mx).is_empty());
+    }
+
+    #[test]
+    fn test_to_resource_record() {
+        assert_eq!(to_resource_record(DnsRecordType::TXT, "owner=qovery").value, "\"owner=qovery\"");
+        assert_eq!(
+            to_resource_record(DnsRecordType::TXT, "\"owner=qovery\"").value,
+            "\"owner=qovery\""
+        );
+        assert_eq!(to_resource_record(DnsRecordType::A, "10.0.0.1").value, "10.0.0.1");
+    }
+}
@@ -5,6 +5,7 @@ use thiserror::Error;
 use crate::build_platform::BuildPlatform;
 use crate::cloud_provider::kubernetes::Kubernetes;
 use crate::cloud_provider::CloudProvider;
+use crate::cmd::command;
 use crate::container_registry::errors::ContainerRegistryError;
 use crate::container_registry::ContainerRegistry;
 use crate::dns_provider::errors::DnsProviderError;
@@ -44,6 +45,19 @@ impl EngineConfig {
         dns_provider: Arc<Box<dyn DnsProvider>>,
         kubernetes: Box<dyn Kubernetes>,
     ) -> EngineConfig {
+        // credentials reach terraform through its rendered templates, they must be redacted from any command output
+        let terraform_state_credentials = cloud_provider.terraform_state_credentials();
+        command::register_secrets(
+            cloud_provider
+                .credentials_environment_variables()
+                .into_iter()
+                .map(|(_, value)| value)
+                .chain(vec![
+                    terraform_state_credentials.access_key_id.as_str(),
+                    terraform_state_credentials.secret_access_key.as_str(),
+                ]),
+        );
+
         EngineConfig {
             context,
             build_platform,
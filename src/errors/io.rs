@@ -1,23 +1,41 @@
 use crate::errors;
+use crate::errors::ErrorMessageVerbosity;
 use crate::events::io::EventDetails;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub struct CommandError {
     message: String,
     full_details: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    env_vars: Option<BTreeMap<String, String>>,
 }
 
-impl From<errors::CommandError> for CommandError {
-    fn from(error: errors::CommandError) -> Self {
+impl CommandError {
+    /// Keeps only details allowed by `verbosity`
+    pub fn new_with_verbosity(error: errors::CommandError, verbosity: ErrorMessageVerbosity) -> Self {
         CommandError {
             message: error.message_safe,
-            full_details: error.full_details.unwrap_or_default(),
+            full_details: match verbosity {
+                ErrorMessageVerbosity::SafeOnly => String::new(),
+                _ => error.full_details.unwrap_or_default(),
+            },
+            env_vars: match verbosity {
+                ErrorMessageVerbosity::FullDetails => error.env_vars.map(|env_vars| env_vars.into_iter().collect()),
+                _ => None,
+            },
         }
     }
 }
 
+impl From<errors::CommandError> for CommandError {
+    fn from(error: errors::CommandError) -> Self {
+        CommandError::new_with_verbosity(error, ErrorMessageVerbosity::FullDetailsWithoutEnvVars)
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Tag {
@@ -52,6 +70,8 @@ pub enum Tag {
     K8sDescribe,
     K8sHistory,
     K8sCannotCreateNamespace,
+    K8sCannotCreateTlsSecret,
+    K8sCannotGetCertificateStatus,
     K8sPodIsNotReady,
     K8sNodeIsNotReadyWithTheRequestedVersion,
     K8sNodeIsNotReady,
@@ -80,16 +100,26 @@ pub enum Tag {
     ContainerRegistryError,
     ObjectStorageCannotCreateBucket,
     ObjectStorageCannotPutFileIntoBucket,
+    DnsProviderCannotManageRecords,
     NoClusterFound,
     OnlyOneClusterExpected,
     CloudProviderApiMissingInfo,
     K8sValidateRequiredCPUandBurstableError,
+    ApplicationReleaseAborted,
     TerraformContextUnsupportedParameterValue,
+    TerraformStateLocked,
+    TerraformCloudProviderQuotasReached,
+    TerraformCloudProviderRateLimited,
+    TerraformResourceAlreadyExists,
+    TerraformProviderChecksumMismatch,
     TerraformQoveryConfigMismatch,
     ClientServiceFailedToStart,
     ClientServiceFailedToDeployBeforeStart,
     DatabaseFailedToStartAfterSeveralRetries,
     RouterFailedToDeploy,
+    RouterRoutePortNotFound,
+    DatabaseCredentialsRotationNotSupported,
+    DatabaseCredentialsRotationError,
     CloudProviderClientInvalidCredentials,
     VersionNumberParsingError,
     NotImplementedError,
@@ -155,6 +185,8 @@ impl From<errors::Tag> for Tag {
             errors::Tag::K8sDescribe => Tag::K8sDescribe,
             errors::Tag::K8sHistory => Tag::K8sHistory,
             errors::Tag::K8sCannotCreateNamespace => Tag::K8sCannotCreateNamespace,
+            errors::Tag::K8sCannotCreateTlsSecret => Tag::K8sCannotCreateTlsSecret,
+            errors::Tag::K8sCannotGetCertificateStatus => Tag::K8sCannotGetCertificateStatus,
             errors::Tag::K8sPodIsNotReady => Tag::K8sPodIsNotReady,
             errors::Tag::CannotFindRequiredBinary => Tag::CannotFindRequiredBinary,
             errors::Tag::SubnetsCountShouldBeEven => Tag::SubnetsCountShouldBeEven,
@@ -178,6 +210,7 @@ impl From<errors::Tag> for Tag {
             errors::Tag::CannotGetCluster => Tag::CannotGetCluster,
             errors::Tag::ObjectStorageCannotCreateBucket => Tag::ObjectStorageCannotCreateBucket,
             errors::Tag::ObjectStorageCannotPutFileIntoBucket => Tag::ObjectStorageCannotPutFileIntoBucket,
+            errors::Tag::DnsProviderCannotManageRecords => Tag::DnsProviderCannotManageRecords,
             errors::Tag::UnsupportedRegion => Tag::UnsupportedRegion,
             errors::Tag::UnsupportedZone => Tag::UnsupportedZone,
             errors::Tag::K8sNodeIsNotReadyWithTheRequestedVersion => Tag::K8sNodeIsNotReadyWithTheRequestedVersion,
@@ -186,11 +219,20 @@ impl From<errors::Tag> for Tag {
             errors::Tag::OnlyOneClusterExpected => Tag::OnlyOneClusterExpected,
             errors::Tag::CloudProviderApiMissingInfo => Tag::CloudProviderApiMissingInfo,
             errors::Tag::K8sValidateRequiredCPUandBurstableError => Tag::K8sValidateRequiredCPUandBurstableError,
+            errors::Tag::ApplicationReleaseAborted => Tag::ApplicationReleaseAborted,
             errors::Tag::TerraformContextUnsupportedParameterValue => Tag::TerraformContextUnsupportedParameterValue,
+            errors::Tag::TerraformStateLocked => Tag::TerraformStateLocked,
+            errors::Tag::TerraformCloudProviderQuotasReached => Tag::TerraformCloudProviderQuotasReached,
+            errors::Tag::TerraformCloudProviderRateLimited => Tag::TerraformCloudProviderRateLimited,
+            errors::Tag::TerraformResourceAlreadyExists => Tag::TerraformResourceAlreadyExists,
+            errors::Tag::TerraformProviderChecksumMismatch => Tag::TerraformProviderChecksumMismatch,
             errors::Tag::ClientServiceFailedToStart => Tag::ClientServiceFailedToStart,
             errors::Tag::ClientServiceFailedToDeployBeforeStart => Tag::ClientServiceFailedToDeployBeforeStart,
             errors::Tag::DatabaseFailedToStartAfterSeveralRetries => Tag::DatabaseFailedToStartAfterSeveralRetries,
             errors::Tag::RouterFailedToDeploy => Tag::RouterFailedToDeploy,
+            errors::Tag::RouterRoutePortNotFound => Tag::RouterRoutePortNotFound,
+            errors::Tag::DatabaseCredentialsRotationNotSupported => Tag::DatabaseCredentialsRotationNotSupported,
+            errors::Tag::DatabaseCredentialsRotationError => Tag::DatabaseCredentialsRotationError,
             errors::Tag::CloudProviderClientInvalidCredentials => Tag::CloudProviderClientInvalidCredentials,
             errors::Tag::VersionNumberParsingError => Tag::VersionNumberParsingError,
             errors::Tag::NotImplementedError => Tag::NotImplementedError,
@@ -246,16 +288,25 @@ pub struct EngineError {
     hint_message: Option<String>,
 }
 
-impl From<errors::EngineError> for EngineError {
-    fn from(error: errors::EngineError) -> Self {
+impl EngineError {
+    /// Keeps only underlying error details allowed by `verbosity`
+    pub fn new_with_verbosity(error: errors::EngineError, verbosity: ErrorMessageVerbosity) -> Self {
         EngineError {
             tag: Tag::from(error.tag),
             event_details: EventDetails::from(error.event_details),
             qovery_log_message: error.qovery_log_message,
             user_log_message: error.user_log_message,
-            underlying_error: error.underlying_error.map(CommandError::from),
+            underlying_error: error
+                .underlying_error
+                .map(|e| CommandError::new_with_verbosity(e, verbosity)),
             link: error.link.map(|url| url.to_string()),
             hint_message: error.hint_message,
         }
     }
 }
+
+impl From<errors::EngineError> for EngineError {
+    fn from(error: errors::EngineError) -> Self {
+        EngineError::new_with_verbosity(error, ErrorMessageVerbosity::FullDetailsWithoutEnvVars)
+    }
+}
@@ -5,9 +5,12 @@ extern crate url;
 
 use crate::build_platform::BuildError;
 use crate::cmd;
+use crate::cmd::command::CommandOutputCapture;
 use crate::cmd::docker::DockerError;
 use crate::cmd::helm::HelmError;
+use crate::cmd::terraform_errors::{TerraformError, TerraformErrorKind};
 use crate::container_registry::errors::ContainerRegistryError;
+use crate::dns_provider::errors::DnsProviderError;
 use crate::error::{EngineError as LegacyEngineError, EngineErrorCause, EngineErrorScope};
 use crate::events::{EventDetails, GeneralStep, Stage, Transmitter};
 use crate::io_models::QoveryIdentifier;
@@ -19,6 +22,7 @@ use thiserror::Error;
 use url::Url;
 
 /// ErrorMessageVerbosity: represents command error message's verbosity from minimal to full verbosity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ErrorMessageVerbosity {
     SafeOnly,
     FullDetailsWithoutEnvVars,
@@ -119,7 +123,13 @@ impl CommandError {
         stdout: Option<String>,
         stderr: Option<String>,
     ) -> Self {
-        let mut unsafe_message = format!("{}\ncommand: {} {}", message, bin, cmd_args.join(" "),);
+        // arguments may carry secrets as well, i.e: a token given to login
+        let envs_refs = envs
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect::<Vec<(&str, &str)>>();
+        let cmd_args = CommandOutputCapture::new_for_envs(&envs_refs).redact(&cmd_args.join(" "));
+        let mut unsafe_message = format!("{}\ncommand: {} {}", message, bin, cmd_args);
 
         if let Some(txt) = stdout {
             unsafe_message = format!("{}\nSTDOUT {}", unsafe_message, txt);
@@ -154,6 +164,12 @@ impl From<ObjectStorageError> for CommandError {
     }
 }
 
+impl From<DnsProviderError> for CommandError {
+    fn from(dns_provider_error: DnsProviderError) -> Self {
+        CommandError::new_from_safe_message(dns_provider_error.to_string())
+    }
+}
+
 impl From<ContainerRegistryError> for CommandError {
     fn from(container_registry_error: ContainerRegistryError) -> Self {
         CommandError::new_from_safe_message(container_registry_error.to_string())
@@ -233,6 +249,10 @@ pub enum Tag {
     K8sHistory,
     /// K8sCannotCreateNamespace: represents an error while trying to create a k8s namespace.
     K8sCannotCreateNamespace,
+    /// K8sCannotCreateTlsSecret: represents an error while trying to create or update a k8s TLS secret.
+    K8sCannotCreateTlsSecret,
+    /// K8sCannotGetCertificateStatus: represents an error while trying to get the status of cert-manager certificates.
+    K8sCannotGetCertificateStatus,
     /// K8sPodIsNotReady: represents an error where the given pod is not ready.
     K8sPodIsNotReady,
     /// K8sNodeIsNotReadyInTheGivenVersion: represents an error where the given node is not ready in the given version.
@@ -241,6 +261,8 @@ pub enum Tag {
     K8sNodeIsNotReady,
     /// K8sValidateRequiredCPUandBurstableError: represents an error validating required CPU and burstable.
     K8sValidateRequiredCPUandBurstableError,
+    /// ApplicationReleaseAborted: represents an error where a new release of an application failed its analysis and was rolled back.
+    ApplicationReleaseAborted,
     /// CannotFindRequiredBinary: represents an error where a required binary is not found on the system.
     CannotFindRequiredBinary,
     /// SubnetsCountShouldBeEven: represents an error where subnets count should be even to have as many public than private subnets.
@@ -263,6 +285,16 @@ pub enum Tag {
     TerraformErrorWhileExecutingDestroyPipeline,
     /// TerraformContextUnsupportedParameterValue: represents an error while trying to render terraform context because of unsupported parameter value.
     TerraformContextUnsupportedParameterValue,
+    /// TerraformStateLocked: represents an error where Terraform state is locked by another operation.
+    TerraformStateLocked,
+    /// TerraformCloudProviderQuotasReached: represents an error where cloud provider refused to create resources because of account quotas.
+    TerraformCloudProviderQuotasReached,
+    /// TerraformCloudProviderRateLimited: represents an error where cloud provider API calls were throttled.
+    TerraformCloudProviderRateLimited,
+    /// TerraformResourceAlreadyExists: represents an error where a resource to create already exists on cloud provider side.
+    TerraformResourceAlreadyExists,
+    /// TerraformProviderChecksumMismatch: represents an error where a Terraform provider doesn't match the dependency lock file.
+    TerraformProviderChecksumMismatch,
     /// HelmChartsSetupError: represents an error while trying to setup helm charts.
     HelmChartsSetupError,
     /// HelmChartsDeployError: represents an error while trying to deploy helm charts.
@@ -287,6 +319,8 @@ pub enum Tag {
     ObjectStorageCannotCreateBucket,
     /// ObjectStorageCannotPutFileIntoBucket: represents an error while trying to put a file into an object storage bucket.
     ObjectStorageCannotPutFileIntoBucket,
+    /// DnsProviderCannotManageRecords: represents an error while trying to get, create or delete DNS records.
+    DnsProviderCannotManageRecords,
     /// ClientServiceFailedToStart: represent an error while trying to start a client's service.
     ClientServiceFailedToStart,
     /// ClientServiceFailedToDeployBeforeStart: represents an error while trying to deploy a client's service before start.
@@ -295,6 +329,12 @@ pub enum Tag {
     DatabaseFailedToStartAfterSeveralRetries,
     /// RouterFailedToDeploy: represents an error while trying to deploy a router.
     RouterFailedToDeploy,
+    /// RouterRoutePortNotFound: represents an error where a route targets an application port which doesn't exist.
+    RouterRoutePortNotFound,
+    /// DatabaseCredentialsRotationNotSupported: represents an error where credentials rotation is not supported for a database.
+    DatabaseCredentialsRotationNotSupported,
+    /// DatabaseCredentialsRotationError: represents an error while trying to rotate database credentials.
+    DatabaseCredentialsRotationError,
     /// CloudProviderClientInvalidCredentials: represents an error where client credentials for a cloud providers appear to be invalid.
     CloudProviderClientInvalidCredentials,
     /// CloudProviderApiMissingInfo: represents an error while expecting mandatory info
@@ -1404,6 +1444,64 @@ impl EngineError {
         )
     }
 
+    /// Creates new error for kubernetes TLS secret which cannot be created or updated.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `secret_name`: TLS secret name.
+    /// * `domain`: Domain the certificate is served for.
+    /// * `raw_error`: Raw error message.
+    pub fn new_k8s_cannot_create_tls_secret(
+        event_details: EventDetails,
+        secret_name: String,
+        domain: String,
+        raw_error: CommandError,
+    ) -> EngineError {
+        let message = format!(
+            "Error, unable to create TLS secret `{}` holding the certificate of domain `{}`.",
+            secret_name, domain
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::K8sCannotCreateTlsSecret,
+            message.to_string(),
+            message,
+            Some(raw_error),
+            None,
+            Some("Ensure the certificate and the private key are valid and match each other.".to_string()),
+        )
+    }
+
+    /// Creates new error for cert-manager certificates status which cannot be retrieved.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `router_name`: Router serving the certificates.
+    /// * `raw_error`: Raw error message.
+    pub fn new_k8s_cannot_get_certificate_status(
+        event_details: EventDetails,
+        router_name: String,
+        raw_error: CommandError,
+    ) -> EngineError {
+        let message = format!(
+            "Error, unable to get the status of the certificates of router `{}`.",
+            router_name
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::K8sCannotGetCertificateStatus,
+            message.to_string(),
+            message,
+            Some(raw_error),
+            None,
+            None,
+        )
+    }
+
     /// Creates new error for kubernetes pod not being ready.
     ///
     /// Arguments:
@@ -1509,6 +1607,40 @@ impl EngineError {
         )
     }
 
+    /// Creates new error for an application release which failed its analysis, the previous release is still serving.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `application_name`: Name of the released application.
+    /// * `release_slot`: Slot the new release was deployed to.
+    /// * `reason`: Why the release was aborted.
+    /// * `raw_error`: Raw error message.
+    pub fn new_application_release_aborted(
+        event_details: EventDetails,
+        application_name: String,
+        release_slot: String,
+        reason: String,
+        raw_error: Option<CommandError>,
+    ) -> EngineError {
+        let message = format!(
+            "Release `{}` of application `{}` has been aborted: {}",
+            release_slot, application_name, reason
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::ApplicationReleaseAborted,
+            message.to_string(),
+            message,
+            raw_error,
+            None,
+            Some(
+                "The previous release is still serving traffic, please check the logs of the new release.".to_string(),
+            ),
+        )
+    }
+
     /// Creates new error for kubernetes not being able to get crash looping pods.
     ///
     /// Arguments:
@@ -1661,7 +1793,7 @@ impl EngineError {
     pub fn new_terraform_cannot_remove_entry_out(
         event_details: EventDetails,
         entry: String,
-        raw_error: CommandError,
+        raw_error: TerraformError,
     ) -> EngineError {
         let message = format!("Error while trying to remove {} out of terraform state file.", entry);
 
@@ -1670,7 +1802,7 @@ impl EngineError {
             Tag::TerraformCannotRemoveEntryOut,
             message.to_string(),
             message,
-            Some(raw_error),
+            Some(raw_error.into()),
             None,
             None,
         )
@@ -1682,7 +1814,7 @@ impl EngineError {
     ///
     /// * `event_details`: Error linked event details.
     /// * `raw_error`: Raw error message.
-    pub fn new_terraform_state_does_not_exist(event_details: EventDetails, raw_error: CommandError) -> EngineError {
+    pub fn new_terraform_state_does_not_exist(event_details: EventDetails, raw_error: TerraformError) -> EngineError {
         let message = "No state list exists yet.";
 
         EngineError::new(
@@ -1690,7 +1822,7 @@ impl EngineError {
             Tag::TerraformNoStateFileExists,
             message.to_string(),
             message.to_string(),
-            Some(raw_error),
+            Some(raw_error.into()),
             None,
             Some("This is normal if it's a newly created cluster".to_string()),
         )
@@ -1704,18 +1836,15 @@ impl EngineError {
     /// * `raw_error`: Raw error message.
     pub fn new_terraform_error_while_executing_pipeline(
         event_details: EventDetails,
-        raw_error: CommandError,
+        raw_error: TerraformError,
     ) -> EngineError {
         let message = "Error while applying Terraform pipeline: init, plan & apply";
 
-        EngineError::new(
+        EngineError::new_from_terraform_error(
             event_details,
             Tag::TerraformErrorWhileExecutingPipeline,
-            message.to_string(),
-            message.to_string(),
-            Some(raw_error),
-            None,
-            None,
+            message,
+            raw_error,
         )
     }
 
@@ -1727,18 +1856,69 @@ impl EngineError {
     /// * `raw_error`: Raw error message.
     pub fn new_terraform_error_while_executing_destroy_pipeline(
         event_details: EventDetails,
-        raw_error: CommandError,
+        raw_error: TerraformError,
     ) -> EngineError {
         let message = "Error while applying Terraform pipeline: init, validate & destroy";
 
-        EngineError::new(
+        EngineError::new_from_terraform_error(
             event_details,
             Tag::TerraformErrorWhileExecutingDestroyPipeline,
+            message,
+            raw_error,
+        )
+    }
+
+    /// Creates new error from a Terraform error, known issues get their own tag and hint.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `tag`: Tag used if the error doesn't match any known issue.
+    /// * `message`: Message used if the error doesn't match any known issue.
+    /// * `raw_error`: Raw error message.
+    fn new_from_terraform_error(
+        event_details: EventDetails,
+        tag: Tag,
+        message: &str,
+        raw_error: TerraformError,
+    ) -> EngineError {
+        let (tag, message, hint) = match &raw_error.kind {
+            TerraformErrorKind::StateLocked { .. } => (
+                Tag::TerraformStateLocked,
+                raw_error.to_string(),
+                Some("Another operation is running on those resources, wait for it to finish before retrying.".to_string()),
+            ),
+            TerraformErrorKind::QuotaExceeded => (
+                Tag::TerraformCloudProviderQuotasReached,
+                raw_error.to_string(),
+                Some("Request a quota increase from your cloud provider console or remove unused resources.".to_string()),
+            ),
+            TerraformErrorKind::Throttled => (
+                Tag::TerraformCloudProviderRateLimited,
+                raw_error.to_string(),
+                Some("Cloud provider API calls are limited, please retry later.".to_string()),
+            ),
+            TerraformErrorKind::ResourceAlreadyExists { .. } => (
+                Tag::TerraformResourceAlreadyExists,
+                raw_error.to_string(),
+                Some("A resource with the same name has been created outside of Qovery, delete it or rename your service.".to_string()),
+            ),
+            TerraformErrorKind::ProviderChecksumMismatch => (
+                Tag::TerraformProviderChecksumMismatch,
+                raw_error.to_string(),
+                Some("This is usually caused by concurrent Terraform runs, please retry.".to_string()),
+            ),
+            TerraformErrorKind::Unknown => (tag, message.to_string(), None),
+        };
+
+        EngineError::new(
+            event_details,
+            tag,
             message.to_string(),
-            message.to_string(),
-            Some(raw_error),
-            None,
+            message,
+            Some(raw_error.into()),
             None,
+            hint,
         )
     }
 
@@ -2143,6 +2323,94 @@ impl EngineError {
         )
     }
 
+    /// Creates new error when a router route targets a port which doesn't exist on the application.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `route_path`: Path of the route.
+    /// * `application_name`: Application targeted by the route.
+    /// * `port`: Id or name of the port targeted by the route.
+    pub fn new_router_route_port_not_found(
+        event_details: EventDetails,
+        route_path: String,
+        application_name: String,
+        port: String,
+    ) -> EngineError {
+        let message = format!(
+            "Route `{}` targets port `{}` which doesn't exist or isn't an HTTP port on application `{}`.",
+            route_path, port, application_name
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::RouterRoutePortNotFound,
+            message.to_string(),
+            message,
+            None,
+            None,
+            Some("Update the route to target an HTTP port of the application.".to_string()),
+        )
+    }
+
+    /// Creates new error when database credentials rotation is not supported for the database type.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `database_type`: Database type.
+    pub fn new_database_credentials_rotation_not_supported(
+        event_details: EventDetails,
+        database_type: String,
+    ) -> EngineError {
+        let message = format!("Credentials rotation is not supported for `{}` databases.", database_type);
+
+        EngineError::new(
+            event_details,
+            Tag::DatabaseCredentialsRotationNotSupported,
+            message.to_string(),
+            message,
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// Creates new error while trying to rotate database credentials.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `database_name`: Database name.
+    /// * `rotation_step`: Rotation step which has failed.
+    /// * `raw_error`: Raw error message.
+    /// * `is_reverted`: Whether previous credentials have been restored.
+    pub fn new_database_credentials_rotation_error(
+        event_details: EventDetails,
+        database_name: String,
+        rotation_step: String,
+        raw_error: CommandError,
+        is_reverted: bool,
+    ) -> EngineError {
+        let message = format!(
+            "Error while rotating credentials of database `{}`, step `{}` has failed.",
+            database_name, rotation_step
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::DatabaseCredentialsRotationError,
+            message.to_string(),
+            message,
+            Some(raw_error),
+            None,
+            Some(match is_reverted {
+                true => "Previous credentials have been restored, your applications keep using them.".to_string(),
+                false => "Previous credentials could not be fully restored, check your database users and your applications before retrying.".to_string(),
+            }),
+        )
+    }
+
     /// Creates new error when trying to connect to user's account with its credentials.
     ///
     /// Arguments:
@@ -2939,6 +3207,31 @@ impl EngineError {
             None,
         )
     }
+
+    /// Creates new error while trying to get, create or delete DNS records.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `domain`: Domain of the records.
+    /// * `raw_error`: Raw error message.
+    pub fn new_dns_provider_cannot_manage_records(
+        event_details: EventDetails,
+        domain: String,
+        raw_error: DnsProviderError,
+    ) -> EngineError {
+        let message = format!("Error, unable to manage DNS records of `{}`.", domain);
+
+        EngineError::new(
+            event_details,
+            Tag::DnsProviderCannotManageRecords,
+            message.to_string(),
+            message,
+            Some(raw_error.into()),
+            None,
+            None,
+        )
+    }
 }
 
 impl Display for EngineError {
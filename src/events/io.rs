@@ -3,7 +3,9 @@
 use crate::cloud_provider::io::Kind;
 use crate::errors::io::EngineError;
 use crate::events;
+use crate::events::EventMessageVerbosity;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "type")]
@@ -27,45 +29,68 @@ pub enum EngineEvent {
     },
 }
 
-impl From<events::EngineEvent> for EngineEvent {
-    fn from(event: events::EngineEvent) -> Self {
+impl EngineEvent {
+    /// Keeps only message details allowed by `verbosity`
+    pub fn new_with_verbosity(event: events::EngineEvent, verbosity: EventMessageVerbosity) -> Self {
         match event {
             events::EngineEvent::Debug(d, m) => EngineEvent::Debug {
                 details: EventDetails::from(d),
-                message: EventMessage::from(m),
+                message: EventMessage::new_with_verbosity(m, verbosity),
             },
             events::EngineEvent::Info(d, m) => EngineEvent::Info {
                 details: EventDetails::from(d),
-                message: EventMessage::from(m),
+                message: EventMessage::new_with_verbosity(m, verbosity),
             },
             events::EngineEvent::Warning(d, m) => EngineEvent::Warning {
                 details: EventDetails::from(d),
-                message: EventMessage::from(m),
+                message: EventMessage::new_with_verbosity(m, verbosity),
             },
             events::EngineEvent::Error(e, m) => EngineEvent::Error {
-                error: EngineError::from(e),
-                message: m.map(EventMessage::from),
+                error: EngineError::new_with_verbosity(e, verbosity.into()),
+                message: m.map(|m| EventMessage::new_with_verbosity(m, verbosity)),
             },
         }
     }
 }
 
+impl From<events::EngineEvent> for EngineEvent {
+    fn from(event: events::EngineEvent) -> Self {
+        EngineEvent::new_with_verbosity(event, EventMessageVerbosity::FullDetailsWithoutEnvVars)
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub struct EventMessage {
     safe_message: String,
     full_details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    env_vars: Option<BTreeMap<String, String>>,
 }
 
-impl From<events::EventMessage> for EventMessage {
-    fn from(message: events::EventMessage) -> Self {
+impl EventMessage {
+    /// Keeps only details allowed by `verbosity`
+    pub fn new_with_verbosity(message: events::EventMessage, verbosity: EventMessageVerbosity) -> Self {
         EventMessage {
             safe_message: message.safe_message,
-            full_details: message.full_details,
+            full_details: match verbosity {
+                EventMessageVerbosity::SafeOnly => None,
+                _ => message.full_details,
+            },
+            env_vars: match verbosity {
+                EventMessageVerbosity::FullDetails => message.env_vars.map(|env_vars| env_vars.into_iter().collect()),
+                _ => None,
+            },
         }
     }
 }
 
+impl From<events::EventMessage> for EventMessage {
+    fn from(message: events::EventMessage) -> Self {
+        EventMessage::new_with_verbosity(message, EventMessageVerbosity::FullDetailsWithoutEnvVars)
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Stage {
@@ -91,6 +116,7 @@ pub enum GeneralStep {
     RetrieveClusterResources,
     ValidateSystemRequirements,
     UnderMigration,
+    ArchiveWorkspace,
 }
 
 impl From<events::GeneralStep> for GeneralStep {
@@ -100,6 +126,7 @@ impl From<events::GeneralStep> for GeneralStep {
             events::GeneralStep::RetrieveClusterResources => GeneralStep::RetrieveClusterResources,
             events::GeneralStep::ValidateSystemRequirements => GeneralStep::ValidateSystemRequirements,
             events::GeneralStep::UnderMigration => GeneralStep::UnderMigration,
+            events::GeneralStep::ArchiveWorkspace => GeneralStep::ArchiveWorkspace,
         }
     }
 }
@@ -114,6 +141,7 @@ pub enum InfrastructureStep {
     Downgrade,
     Upgrade,
     Delete,
+    DetectDrift,
 }
 
 impl From<events::InfrastructureStep> for InfrastructureStep {
@@ -126,6 +154,7 @@ impl From<events::InfrastructureStep> for InfrastructureStep {
             events::InfrastructureStep::Delete => InfrastructureStep::Delete,
             events::InfrastructureStep::Resume => InfrastructureStep::Resume,
             events::InfrastructureStep::Downgrade => InfrastructureStep::Downgrade,
+            events::InfrastructureStep::DetectDrift => InfrastructureStep::DetectDrift,
         }
     }
 }
@@ -142,6 +171,7 @@ pub enum EnvironmentStep {
     LoadConfiguration,
     ScaleUp,
     ScaleDown,
+    DetectDrift,
 }
 
 impl From<events::EnvironmentStep> for EnvironmentStep {
@@ -156,6 +186,7 @@ impl From<events::EnvironmentStep> for EnvironmentStep {
             events::EnvironmentStep::LoadConfiguration => EnvironmentStep::LoadConfiguration,
             events::EnvironmentStep::ScaleUp => EnvironmentStep::ScaleUp,
             events::EnvironmentStep::ScaleDown => EnvironmentStep::ScaleDown,
+            events::EnvironmentStep::DetectDrift => EnvironmentStep::DetectDrift,
         }
     }
 }
@@ -49,6 +49,7 @@ impl EngineEvent {
 }
 
 /// EventMessageVerbosity: represents event message's verbosity from minimal to full verbosity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum EventMessageVerbosity {
     SafeOnly,
     FullDetailsWithoutEnvVars,
@@ -219,6 +220,8 @@ pub enum GeneralStep {
     RetrieveClusterResources,
     /// UnderMigration: error migration hasn't been completed yet.
     UnderMigration,
+    /// ArchiveWorkspace: archiving execution workspace for post-mortem debugging
+    ArchiveWorkspace,
 }
 
 impl Display for GeneralStep {
@@ -231,6 +234,7 @@ impl Display for GeneralStep {
                 GeneralStep::RetrieveClusterResources => "retrieve-cluster-resources",
                 GeneralStep::ValidateSystemRequirements => "validate-system-requirements",
                 GeneralStep::UnderMigration => "under-migration",
+                GeneralStep::ArchiveWorkspace => "archive-workspace",
             }
         )
     }
@@ -253,6 +257,8 @@ pub enum InfrastructureStep {
     Downgrade,
     /// Delete: delete a cluster.
     Delete,
+    /// DetectDrift: compare cluster cloud resources against their Terraform templates.
+    DetectDrift,
 }
 
 impl Display for InfrastructureStep {
@@ -268,6 +274,7 @@ impl Display for InfrastructureStep {
                 InfrastructureStep::Downgrade => "downgrade",
                 InfrastructureStep::Delete => "delete",
                 InfrastructureStep::Resume => "resume",
+                InfrastructureStep::DetectDrift => "detect-drift",
             },
         )
     }
@@ -294,6 +301,8 @@ pub enum EnvironmentStep {
     ScaleUp,
     /// ScaleDown: scale down an environment.
     ScaleDown,
+    /// DetectDrift: compare managed services cloud resources against their Terraform templates.
+    DetectDrift,
 }
 
 impl Display for EnvironmentStep {
@@ -311,6 +320,7 @@ impl Display for EnvironmentStep {
                 EnvironmentStep::LoadConfiguration => "load-configuration",
                 EnvironmentStep::ScaleUp => "scale-up",
                 EnvironmentStep::ScaleDown => "scale-down",
+                EnvironmentStep::DetectDrift => "detect-drift",
             },
         )
     }
@@ -6,6 +6,7 @@ extern crate tracing;
 extern crate trust_dns_resolver;
 
 pub mod build_platform;
+pub mod change_set;
 pub mod cloud_provider;
 pub mod cmd;
 pub mod constants;
@@ -21,11 +22,15 @@ pub mod fs;
 pub mod git;
 pub mod io_models;
 pub mod logger;
+pub mod metrics;
 pub mod models;
 pub mod object_storage;
+pub mod progress;
 pub mod runtime;
 mod string;
+pub mod telemetry;
 mod template;
 pub mod transaction;
 mod unit_conversion;
 pub mod utilities;
+pub mod webhook;
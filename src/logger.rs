@@ -1,4 +1,8 @@
-use crate::events::{EngineEvent, EventMessageVerbosity};
+use crate::events::{io, EngineEvent, EventMessageVerbosity};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use tracing;
 
 pub trait Logger: Send + Sync {
@@ -69,6 +73,57 @@ impl Logger for StdIoLogger {
     }
 }
 
+/// Writes one serialized `events::io::EngineEvent` per line, keeping only details allowed by `verbosity`.
+#[derive(Clone)]
+pub struct JsonLogger {
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    verbosity: EventMessageVerbosity,
+}
+
+impl JsonLogger {
+    pub fn new(writer: Box<dyn Write + Send>, verbosity: EventMessageVerbosity) -> JsonLogger {
+        JsonLogger {
+            writer: Arc::new(Mutex::new(writer)),
+            verbosity,
+        }
+    }
+
+    /// Appends events to `path`, the file is created if it doesn't exist.
+    pub fn new_to_file<P: AsRef<Path>>(
+        path: P,
+        verbosity: EventMessageVerbosity,
+    ) -> Result<JsonLogger, std::io::Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(JsonLogger::new(Box::new(file), verbosity))
+    }
+}
+
+impl Logger for JsonLogger {
+    fn log(&self, event: EngineEvent) {
+        let line = match serde_json::to_string(&io::EngineEvent::new_with_verbosity(event, self.verbosity)) {
+            Ok(line) => line,
+            Err(err) => {
+                error!("Cannot serialize engine event: {}", err);
+                return;
+            }
+        };
+
+        // a poisoned lock only means another thread panicked while writing, the writer is still usable
+        let mut writer = match self.writer.lock() {
+            Ok(writer) => writer,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if let Err(err) = writeln!(writer, "{}", line).and_then(|_| writer.flush()) {
+            error!("Cannot write engine event: {}", err);
+        }
+    }
+
+    fn clone_dyn(&self) -> Box<dyn Logger> {
+        Box::new(self.clone())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,4 +320,92 @@ mod tests {
             assert!(logs_contain(raw_message), "{}", tc.description);
         }
     }
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_log() {
+        // setup:
+        let cluster_id = QoveryIdentifier::new_from_long_id(Uuid::new_v4().to_string());
+        let event_details = EventDetails::new(
+            Some(Kind::Scw),
+            QoveryIdentifier::new_from_long_id(Uuid::new_v4().to_string()),
+            cluster_id.clone(),
+            QoveryIdentifier::new_from_long_id(Uuid::new_v4().to_string()),
+            Some(ScwRegion::Paris.as_str().to_string()),
+            Stage::Infrastructure(InfrastructureStep::Create),
+            Transmitter::Kubernetes(cluster_id.to_string(), format!("qovery-{}", cluster_id)),
+        );
+        let events = vec![
+            EngineEvent::Info(
+                event_details.clone(),
+                EventMessage::new_with_env_vars(
+                    "Safe message".to_string(),
+                    Some("Raw message".to_string()),
+                    Some(vec![("SECRET_KEY".to_string(), "secret_value".to_string())]),
+                ),
+            ),
+            EngineEvent::Error(
+                EngineError::new_unknown(
+                    event_details,
+                    "Qovery message".to_string(),
+                    "User message".to_string(),
+                    Some(errors::CommandError::new(
+                        "Safe error".to_string(),
+                        Some("Raw error".to_string()),
+                        Some(vec![("SECRET_KEY".to_string(), "secret_value".to_string())]),
+                    )),
+                    None,
+                    None,
+                ),
+                None,
+            ),
+        ];
+
+        let log = |verbosity: EventMessageVerbosity| {
+            let buffer = SharedBuffer::default();
+            let logger = JsonLogger::new(Box::new(buffer.clone()), verbosity);
+            for event in events.iter() {
+                logger.log(event.clone());
+            }
+            let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+            output.lines().map(|line| line.to_string()).collect::<Vec<String>>()
+        };
+
+        // execute & validate:
+        let lines = log(EventMessageVerbosity::SafeOnly);
+        assert_eq!(lines.len(), 2);
+        let info: serde_json::Value = serde_json::from_str(&lines[0]).expect("cannot parse json line");
+        assert_eq!(info["type"], "info");
+        assert_eq!(info["message"]["safe_message"], "Safe message");
+        assert!(info["message"]["full_details"].is_null());
+        let error: serde_json::Value = serde_json::from_str(&lines[1]).expect("cannot parse json line");
+        assert_eq!(error["type"], "error");
+        assert_eq!(error["error"]["underlying_error"]["message"], "Safe error");
+        assert!(!lines
+            .iter()
+            .any(|line| line.contains("Raw") || line.contains("secret_value")));
+
+        let lines = log(EventMessageVerbosity::FullDetailsWithoutEnvVars);
+        assert!(lines[0].contains("Raw message"));
+        assert!(lines[1].contains("Raw error"));
+        assert!(!lines.iter().any(|line| line.contains("secret_value")));
+
+        let lines = log(EventMessageVerbosity::FullDetails);
+        let info: serde_json::Value = serde_json::from_str(&lines[0]).expect("cannot parse json line");
+        assert_eq!(info["message"]["env_vars"]["SECRET_KEY"], "secret_value");
+        let error: serde_json::Value = serde_json::from_str(&lines[1]).expect("cannot parse json line");
+        assert_eq!(error["error"]["underlying_error"]["env_vars"]["SECRET_KEY"], "secret_value");
+    }
 }
@@ -0,0 +1,339 @@
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Histogram,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: MetricKind,
+}
+
+/// labels: build_platform, outcome
+pub const BUILD_DURATION_SECONDS: Metric = Metric {
+    name: "qovery_engine_build_duration_seconds",
+    help: "Duration of container image builds",
+    kind: MetricKind::Histogram,
+};
+
+/// labels: build_platform, cache (image, layers or miss)
+pub const BUILD_CACHE_TOTAL: Metric = Metric {
+    name: "qovery_engine_build_cache_total",
+    help: "Container image builds by cache usage",
+    kind: MetricKind::Counter,
+};
+
+/// labels: chart (kind of service or name of the bundled chart, see `helm_chart_label`), outcome
+pub const HELM_UPGRADE_DURATION_SECONDS: Metric = Metric {
+    name: "qovery_engine_helm_upgrade_duration_seconds",
+    help: "Duration of helm upgrades",
+    kind: MetricKind::Histogram,
+};
+
+/// labels: stack (kind of stack, see `terraform_stack_label`), outcome
+pub const TERRAFORM_APPLY_DURATION_SECONDS: Metric = Metric {
+    name: "qovery_engine_terraform_apply_duration_seconds",
+    help: "Duration of terraform plan and apply",
+    kind: MetricKind::Histogram,
+};
+
+/// labels: outcome
+pub const TRANSACTION_OUTCOME_TOTAL: Metric = Metric {
+    name: "qovery_engine_transaction_outcome_total",
+    help: "Transactions by result",
+    kind: MetricKind::Counter,
+};
+
+pub type Labels<'a> = &'a [(&'static str, &'a str)];
+
+/// Receives every metric recorded by the engine, to be registered once with `set_metrics_sink`
+pub trait MetricsSink: Send + Sync {
+    fn increment_counter(&self, metric: &Metric, labels: Labels);
+    fn observe_duration(&self, metric: &Metric, labels: Labels, duration: Duration);
+}
+
+lazy_static! {
+    static ref METRICS_SINK: RwLock<Option<Arc<dyn MetricsSink>>> = RwLock::new(None);
+}
+
+pub fn set_metrics_sink(sink: Arc<dyn MetricsSink>) {
+    *METRICS_SINK.write().unwrap() = Some(sink);
+}
+
+pub fn increment_counter(metric: &Metric, labels: Labels) {
+    if let Some(sink) = METRICS_SINK.read().unwrap().as_ref() {
+        sink.increment_counter(metric, labels);
+    }
+}
+
+pub fn observe_duration(metric: &Metric, labels: Labels, duration: Duration) {
+    if let Some(sink) = METRICS_SINK.read().unwrap().as_ref() {
+        sink.observe_duration(metric, labels, duration);
+    }
+}
+
+pub fn outcome_label<T, E>(result: &Result<T, E>) -> &'static str {
+    match result {
+        Ok(_) => "success",
+        Err(_) => "error",
+    }
+}
+
+type LabelValues = Vec<(&'static str, String)>;
+
+#[derive(Clone, Debug, Default)]
+struct Histogram {
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Debug)]
+enum MetricValues {
+    Counter(BTreeMap<LabelValues, u64>),
+    Histogram(BTreeMap<LabelValues, Histogram>),
+}
+
+/// Keeps metrics in memory and renders them in the Prometheus text exposition format
+pub struct PrometheusMetricsSink {
+    // upper bounds in seconds, +Inf is implicit
+    buckets: Vec<f64>,
+    metrics: Mutex<BTreeMap<&'static str, (Metric, MetricValues)>>,
+}
+
+impl PrometheusMetricsSink {
+    pub fn new() -> Self {
+        // builds, helm upgrades and terraform applies last from seconds to tens of minutes
+        Self::new_with_buckets(vec![
+            1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 900.0, 1200.0, 1800.0, 3600.0,
+        ])
+    }
+
+    pub fn new_with_buckets(mut buckets: Vec<f64>) -> Self {
+        buckets.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        PrometheusMetricsSink {
+            buckets,
+            metrics: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let metrics = self.metrics.lock().unwrap();
+        let mut output = String::new();
+
+        for (metric, values) in metrics.values() {
+            let _ = writeln!(output, "# HELP {} {}", metric.name, metric.help);
+            match values {
+                MetricValues::Counter(counters) => {
+                    let _ = writeln!(output, "# TYPE {} counter", metric.name);
+                    for (labels, value) in counters {
+                        let _ = writeln!(output, "{}{} {}", metric.name, format_labels(labels, None), value);
+                    }
+                }
+                MetricValues::Histogram(histograms) => {
+                    let _ = writeln!(output, "# TYPE {} histogram", metric.name);
+                    for (labels, histogram) in histograms {
+                        for (bucket, count) in self.buckets.iter().zip(histogram.bucket_counts.iter()) {
+                            let _ = writeln!(
+                                output,
+                                "{}_bucket{} {}",
+                                metric.name,
+                                format_labels(labels, Some(&bucket.to_string())),
+                                count
+                            );
+                        }
+                        let _ = writeln!(
+                            output,
+                            "{}_bucket{} {}",
+                            metric.name,
+                            format_labels(labels, Some("+Inf")),
+                            histogram.count
+                        );
+                        let _ =
+                            writeln!(output, "{}_sum{} {}", metric.name, format_labels(labels, None), histogram.sum);
+                        let _ = writeln!(
+                            output,
+                            "{}_count{} {}",
+                            metric.name,
+                            format_labels(labels, None),
+                            histogram.count
+                        );
+                    }
+                }
+            }
+        }
+
+        output
+    }
+}
+
+impl Default for PrometheusMetricsSink {
+    fn default() -> Self {
+        PrometheusMetricsSink::new()
+    }
+}
+
+impl MetricsSink for PrometheusMetricsSink {
+    fn increment_counter(&self, metric: &Metric, labels: Labels) {
+        let mut metrics = self.metrics.lock().unwrap();
+        let (_, values) = metrics
+            .entry(metric.name)
+            .or_insert_with(|| (*metric, MetricValues::Counter(BTreeMap::new())));
+
+        if let MetricValues::Counter(counters) = values {
+            *counters.entry(to_label_values(labels)).or_insert(0) += 1;
+        }
+    }
+
+    fn observe_duration(&self, metric: &Metric, labels: Labels, duration: Duration) {
+        let mut metrics = self.metrics.lock().unwrap();
+        let (_, values) = metrics
+            .entry(metric.name)
+            .or_insert_with(|| (*metric, MetricValues::Histogram(BTreeMap::new())));
+
+        if let MetricValues::Histogram(histograms) = values {
+            let histogram = histograms.entry(to_label_values(labels)).or_insert_with(|| Histogram {
+                bucket_counts: vec![0; self.buckets.len()],
+                ..Default::default()
+            });
+
+            let seconds = duration.as_secs_f64();
+            for (bucket, count) in self.buckets.iter().zip(histogram.bucket_counts.iter_mut()) {
+                if seconds <= *bucket {
+                    *count += 1;
+                }
+            }
+            histogram.sum += seconds;
+            histogram.count += 1;
+        }
+    }
+}
+
+fn to_label_values(labels: Labels) -> LabelValues {
+    let mut values: LabelValues = labels.iter().map(|(k, v)| (*k, v.to_string())).collect();
+    values.sort();
+    values
+}
+
+fn format_labels(labels: &[(&'static str, String)], le: Option<&str>) -> String {
+    let mut formatted = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape_label_value(v)))
+        .collect::<Vec<_>>();
+    if let Some(le) = le {
+        formatted.push(format!("le=\"{}\"", le));
+    }
+
+    match formatted.is_empty() {
+        true => "".to_string(),
+        false => format!("{{{}}}", formatted.join(",")),
+    }
+}
+
+/// Kind of workspace a path belongs to, ex: `databases` for `<root>/.qovery-workspace/<execution id>/databases/<name>`
+fn workspace_kind(path: &str) -> Option<String> {
+    let components = Path::new(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+
+    match components.iter().position(|component| component == ".qovery-workspace") {
+        Some(idx) if components.len() > idx + 3 => Some(components[idx + 2].clone()),
+        _ => None,
+    }
+}
+
+/// Service and cluster names or ids can't be used as label values, their cardinality is unbounded
+pub fn terraform_stack_label(root_dir: &str) -> String {
+    workspace_kind(root_dir).unwrap_or_else(|| "other".to_string())
+}
+
+/// Services charts are labelled with their kind, bundled charts with their directory name
+pub fn helm_chart_label(chart_path: &str) -> String {
+    match workspace_kind(chart_path) {
+        Some(kind) if kind != "bootstrap" => kind,
+        _ => Path::new(chart_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "other".to_string()),
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::{
+        helm_chart_label, terraform_stack_label, MetricsSink, PrometheusMetricsSink, HELM_UPGRADE_DURATION_SECONDS,
+        TRANSACTION_OUTCOME_TOTAL,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_labels_have_bounded_cardinality() {
+        assert_eq!(
+            terraform_stack_label("/tmp/.qovery-workspace/execution-id/bootstrap/z1234"),
+            "bootstrap"
+        );
+        assert_eq!(
+            terraform_stack_label("/tmp/.qovery-workspace/execution-id/databases/my-db/"),
+            "databases"
+        );
+        assert_eq!(terraform_stack_label("/tmp/test"), "other");
+
+        assert_eq!(
+            helm_chart_label("/tmp/.qovery-workspace/execution-id/applications/my-app/blue"),
+            "applications"
+        );
+        assert_eq!(
+            helm_chart_label("/tmp/.qovery-workspace/execution-id/bootstrap/z1234/common/charts/ingress-nginx"),
+            "ingress-nginx"
+        );
+        assert_eq!(helm_chart_label("lib/common/bootstrap/charts/coredns-config"), "coredns-config");
+    }
+
+    #[test]
+    fn test_prometheus_render() {
+        let sink = PrometheusMetricsSink::new_with_buckets(vec![10.0, 1.0]);
+        sink.increment_counter(&TRANSACTION_OUTCOME_TOTAL, &[("outcome", "ok")]);
+        sink.increment_counter(&TRANSACTION_OUTCOME_TOTAL, &[("outcome", "ok")]);
+        sink.increment_counter(&TRANSACTION_OUTCOME_TOTAL, &[("outcome", "rollback")]);
+        sink.observe_duration(
+            &HELM_UPGRADE_DURATION_SECONDS,
+            &[("outcome", "success"), ("chart", "app-\"x\"")],
+            Duration::from_millis(500),
+        );
+        sink.observe_duration(
+            &HELM_UPGRADE_DURATION_SECONDS,
+            &[("chart", "app-\"x\""), ("outcome", "success")],
+            Duration::from_secs(5),
+        );
+
+        assert_eq!(
+            sink.render(),
+            r#"# HELP qovery_engine_helm_upgrade_duration_seconds Duration of helm upgrades
+# TYPE qovery_engine_helm_upgrade_duration_seconds histogram
+qovery_engine_helm_upgrade_duration_seconds_bucket{chart="app-\"x\"",outcome="success",le="1"} 1
+qovery_engine_helm_upgrade_duration_seconds_bucket{chart="app-\"x\"",outcome="success",le="10"} 2
+qovery_engine_helm_upgrade_duration_seconds_bucket{chart="app-\"x\"",outcome="success",le="+Inf"} 2
+qovery_engine_helm_upgrade_duration_seconds_sum{chart="app-\"x\"",outcome="success"} 5.5
+qovery_engine_helm_upgrade_duration_seconds_count{chart="app-\"x\"",outcome="success"} 2
+# HELP qovery_engine_transaction_outcome_total Transactions by result
+# TYPE qovery_engine_transaction_outcome_total counter
+qovery_engine_transaction_outcome_total{outcome="ok"} 2
+qovery_engine_transaction_outcome_total{outcome="rollback"} 1
+"#
+        );
+    }
+}
@@ -1,22 +1,34 @@
 use crate::build_platform::Build;
+use crate::change_set::ServiceChangeSet;
 use crate::cloud_provider::environment::Environment;
 use crate::cloud_provider::kubernetes::Kubernetes;
-use crate::cloud_provider::models::{EnvironmentVariable, EnvironmentVariableDataTemplate, Storage};
+use crate::cloud_provider::models::{
+    DatabaseConnectionInfos, DatabaseLink, EnvironmentVariable, EnvironmentVariableDataTemplate,
+    LoadBalancerServiceDataTemplate, Route, Storage,
+};
 use crate::cloud_provider::service::{delete_stateless_service, scale_down_application};
 use crate::cloud_provider::service::{
-    deploy_stateless_service_error, deploy_user_stateless_service, send_progress_on_long_task, Action, Create, Delete,
-    Helm, Pause, Service, ServiceType, StatelessService,
+    deploy_stateless_service_error, deploy_user_stateless_service, plan_helm_service, send_progress_on_long_task,
+    Action, Create, Delete, Helm, Pause, Service, ServiceType, StatelessService,
 };
 use crate::cloud_provider::utilities::{print_action, sanitize_name};
 use crate::cloud_provider::DeploymentTarget;
 use crate::cmd::kubectl::ScalingKind::{Deployment, Statefulset};
 use crate::errors::EngineError;
-use crate::events::{EnvironmentStep, EventDetails, Stage, ToTransmitter, Transmitter};
-use crate::io_models::{ApplicationAdvancedSettings, Context, Listen, Listener, Listeners, Port, QoveryIdentifier};
+use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, Stage, ToTransmitter, Transmitter};
+use crate::io_models::{
+    ApplicationAdvancedSettings, Context, Listen, Listener, Listeners, Port, Protocol, QoveryIdentifier,
+    ReleaseStrategy,
+};
 use crate::logger::Logger;
+use crate::models::application_release::workload_name;
+use crate::models::database_credentials::current_connection_infos;
 use crate::models::types::{CloudProvider, ToTeraContext};
 use crate::utilities::to_short_id;
 use function_name::named;
+use itertools::Itertools;
+use retry::delay::Fibonacci;
+use retry::OperationResult;
 use std::marker::PhantomData;
 use tera::Context as TeraContext;
 use uuid::Uuid;
@@ -43,9 +55,11 @@ pub struct Application<T: CloudProvider> {
     pub(super) build: Build,
     pub(super) storage: Vec<Storage<T::StorageTypes>>,
     pub(super) environment_variables: Vec<EnvironmentVariable>,
+    pub(super) database_links: Vec<DatabaseLink>,
     pub(super) listeners: Listeners,
     pub(super) logger: Box<dyn Logger>,
     pub(super) advanced_settings: ApplicationAdvancedSettings,
+    pub(super) release_strategy: ReleaseStrategy,
     pub(super) _extra_settings: T::AppExtraSettings,
 }
 
@@ -65,12 +79,41 @@ impl<T: CloudProvider> Application<T> {
         build: Build,
         storage: Vec<Storage<T::StorageTypes>>,
         environment_variables: Vec<EnvironmentVariable>,
+        database_links: Vec<DatabaseLink>,
         advance_settings: ApplicationAdvancedSettings,
+        release_strategy: ReleaseStrategy,
         extra_settings: T::AppExtraSettings,
         listeners: Listeners,
         logger: Box<dyn Logger>,
     ) -> Result<Self, ApplicationError> {
         // TODO: Check that the information provided are coherent
+        if release_strategy != ReleaseStrategy::RollingUpdate {
+            // releases run side by side and are switched by the router
+            if !storage.is_empty() {
+                return Err(ApplicationError::InvalidConfig(
+                    "blue-green and canary releases are not available for applications with storage".to_string(),
+                ));
+            }
+            if !ports
+                .iter()
+                .any(|port| port.publicly_accessible && port.protocol == Protocol::HTTP)
+            {
+                return Err(ApplicationError::InvalidConfig(
+                    "blue-green and canary releases require a public HTTP port".to_string(),
+                ));
+            }
+        }
+        if let ReleaseStrategy::Canary {
+            traffic_weight_percent, ..
+        } = release_strategy
+        {
+            if traffic_weight_percent == 0 || traffic_weight_percent > 99 {
+                return Err(ApplicationError::InvalidConfig(format!(
+                    "canary traffic weight must be between 1 and 99%, got {}%",
+                    traffic_weight_percent
+                )));
+            }
+        }
 
         Ok(Self {
             _marker: PhantomData,
@@ -88,9 +131,11 @@ impl<T: CloudProvider> Application<T> {
             build,
             storage,
             environment_variables,
+            database_links,
             listeners,
             logger,
             advanced_settings: advance_settings,
+            release_strategy,
             _extra_settings: extra_settings,
         })
     }
@@ -134,7 +179,7 @@ impl<T: CloudProvider> Application<T> {
             &self.advanced_settings.deployment_delay_start_time_sec,
         );
 
-        let environment_variables = self
+        let mut environment_variables = self
             .environment_variables
             .iter()
             .map(|ev| EnvironmentVariableDataTemplate {
@@ -143,8 +188,54 @@ impl<T: CloudProvider> Application<T> {
             })
             .collect::<Vec<_>>();
 
+        // inject connection variables of linked databases, so they follow credentials changes
+        let target = DeploymentTarget {
+            kubernetes,
+            environment,
+        };
+        for link in &self.database_links {
+            let database = match environment
+                .databases
+                .iter()
+                .find(|db| db.long_id() == &link.database_long_id)
+            {
+                Some(database) => database,
+                None => continue,
+            };
+
+            // credentials may have been rotated, the original ones are always valid otherwise
+            let connection_infos = current_connection_infos(&target, database.as_ref()).unwrap_or_else(|err| {
+                warn!(
+                    "Cannot get rotated credentials of database `{}`, using the original ones: {:?}",
+                    database.name(),
+                    err
+                );
+                database.connection_infos(&target)
+            });
+            for ev in self.linked_database_environment_variables(link, &connection_infos) {
+                if environment_variables.iter().any(|existing| existing.key == ev.key) {
+                    continue;
+                }
+
+                environment_variables.push(EnvironmentVariableDataTemplate {
+                    key: ev.key,
+                    value: ev.value,
+                });
+            }
+        }
+
         context.insert("environment_variables", &environment_variables);
         context.insert("ports", &self.ports);
+        context.insert("load_balancer_services", &self.load_balancer_services(kubernetes, environment));
+        context.insert("router_ports", &self.router_ports(environment));
+        // the whole application is rendered by a single release, see `application_release` for the other strategies
+        context.insert("workload_name", &self.sanitized_name());
+        context.insert("render_workloads", &true);
+        context.insert("render_network", &true);
+        context.insert("release_slot", &None::<&str>);
+        context.insert("selector_release_slot", &None::<&str>);
+        context.insert("canary_weight_percent", &None::<u8>);
+        context.insert("canary_ingresses", &Vec::<String>::new());
         context.insert("is_registry_secret", &true);
         context.insert("registry_secret", self.build().image.registry_host());
 
@@ -155,6 +246,19 @@ impl<T: CloudProvider> Application<T> {
         context
     }
 
+    /// Connection variables of a linked database, variables defined by the user take precedence
+    fn linked_database_environment_variables(
+        &self,
+        link: &DatabaseLink,
+        connection_infos: &DatabaseConnectionInfos,
+    ) -> Vec<EnvironmentVariable> {
+        connection_infos
+            .to_environment_variables(&link.env_var_prefix)
+            .into_iter()
+            .filter(|ev| !self.environment_variables.iter().any(|existing| existing.key == ev.key))
+            .collect()
+    }
+
     pub fn is_stateful(&self) -> bool {
         !self.storage.is_empty()
     }
@@ -190,6 +294,80 @@ impl<T: CloudProvider> Application<T> {
             .map(|port| port.port as u16)
     }
 
+    /// Public TCP and UDP ports are exposed by a load balancer per protocol, reachable from a hostname
+    /// registered by external-dns
+    pub fn load_balancer_services(
+        &self,
+        kubernetes: &dyn Kubernetes,
+        environment: &Environment,
+    ) -> Vec<LoadBalancerServiceDataTemplate> {
+        LoadBalancerServiceDataTemplate::from_ports(
+            &self.sanitized_name(),
+            &environment.id,
+            &self.ports,
+            kubernetes.dns_provider().domain(),
+        )
+    }
+
+    /// HTTP ports reached by the routers of the environment, through the Ingress controller
+    pub fn router_ports(&self, environment: &Environment) -> Vec<u16> {
+        Route::routed_ports(
+            environment.routers.iter().flat_map(|router| router.routes()),
+            self.name(),
+            &self.ports,
+        )
+    }
+
+    /// Load balancers take a few minutes to get an address, it is only reported as the hostname follows it
+    fn notify_load_balancer_addresses(&self, target: &DeploymentTarget, event_details: EventDetails) {
+        let kubernetes_config_file_path = match target.kubernetes.get_kubeconfig_file_path() {
+            Ok(path) => path,
+            Err(_) => return,
+        };
+
+        for service in self.load_balancer_services(target.kubernetes, target.environment) {
+            let address = retry::retry(Fibonacci::from_millis(3000).take(8), || {
+                match crate::cmd::kubectl::kubectl_exec_get_external_service(
+                    &kubernetes_config_file_path,
+                    target.environment.namespace(),
+                    &service.name,
+                    target.kubernetes.cloud_provider().credentials_environment_variables(),
+                ) {
+                    Ok(Some(ingress)) => match ingress.hostname.or(ingress.ip) {
+                        Some(address) => OperationResult::Ok(address),
+                        None => OperationResult::Retry(()),
+                    },
+                    _ => OperationResult::Retry(()),
+                }
+            });
+
+            let ports = service.ports.iter().map(|port| port.public_port.to_string()).join(", ");
+            match address {
+                Ok(address) => self.logger().log(EngineEvent::Info(
+                    event_details.clone(),
+                    EventMessage::new_from_safe(format!(
+                        "{} ports {} of application `{}` are exposed on {} (load balancer {})",
+                        service.protocol,
+                        ports,
+                        self.name(),
+                        service.hostname,
+                        address
+                    )),
+                )),
+                Err(_) => self.logger().log(EngineEvent::Warning(
+                    event_details.clone(),
+                    EventMessage::new_from_safe(format!(
+                        "Load balancer of {} ports {} of application `{}` has no address yet, {} will be reachable once it has one",
+                        service.protocol,
+                        ports,
+                        self.name(),
+                        service.hostname
+                    )),
+                )),
+            }
+        }
+    }
+
     pub fn total_cpus(&self) -> String {
         self.total_cpus.to_string()
     }
@@ -211,7 +389,7 @@ impl<T: CloudProvider> Application<T> {
     }
 
     pub fn publicly_accessible(&self) -> bool {
-        self.public_port().is_some()
+        self.ports.iter().any(|port| port.publicly_accessible)
     }
 
     pub fn logger(&self) -> &dyn Logger {
@@ -384,10 +562,29 @@ where
             "application",
             function_name!(),
             self.name(),
-            event_details,
+            event_details.clone(),
             self.logger(),
         );
-        send_progress_on_long_task(self, Action::Create, || deploy_user_stateless_service(target, self))
+        match &self.release_strategy {
+            ReleaseStrategy::RollingUpdate => send_progress_on_long_task(self, Action::Create, || {
+                deploy_user_stateless_service(target, self)?;
+
+                // the application may have been previously released with another strategy
+                self.delete_release_slots(target, event_details.clone())
+            })?,
+            ReleaseStrategy::BlueGreen { analysis } => send_progress_on_long_task(self, Action::Create, || {
+                self.deploy_with_release_slots(target, analysis, None)
+            })?,
+            ReleaseStrategy::Canary {
+                traffic_weight_percent,
+                analysis,
+            } => send_progress_on_long_task(self, Action::Create, || {
+                self.deploy_with_release_slots(target, analysis, Some(*traffic_weight_percent))
+            })?,
+        }
+        self.notify_load_balancer_addresses(target, event_details);
+
+        Ok(())
     }
 
     fn on_create_check(&self) -> Result<(), EngineError> {
@@ -468,7 +665,9 @@ where
         );
 
         send_progress_on_long_task(self, Action::Delete, || {
-            delete_stateless_service(target, self, event_details.clone())
+            delete_stateless_service(target, self, event_details.clone())?;
+            // the strategy may have changed since the slots were released
+            self.delete_release_slots(target, event_details.clone())
         })
     }
 
@@ -501,11 +700,32 @@ where
     fn as_stateless_service(&self) -> &dyn StatelessService {
         self
     }
+
+    fn plan_action(&self, target: &DeploymentTarget) -> Result<ServiceChangeSet, EngineError> {
+        let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
+        match &self.release_strategy {
+            ReleaseStrategy::RollingUpdate => plan_helm_service(target, self, &[self.helm_chart_dir()], event_details),
+            ReleaseStrategy::BlueGreen { .. } | ReleaseStrategy::Canary { .. } => {
+                self.plan_with_release_slots(target, event_details)
+            }
+        }
+    }
 }
 
 pub trait ApplicationService: StatelessService {
     fn get_build(&self) -> &Build;
     fn get_build_mut(&mut self) -> &mut Build;
+    fn is_stateful(&self) -> bool;
+    fn ports(&self) -> &[Port];
+    /// Name of the workloads and secret currently running the application, suffixed by the live slot
+    /// for blue-green and canary releases
+    fn live_workload_name(&self, target: &DeploymentTarget) -> Result<String, EngineError>;
+    /// Connection variables injected from the given database, empty if the application is not linked to it
+    fn database_environment_variables(
+        &self,
+        database_long_id: &Uuid,
+        connection_infos: &DatabaseConnectionInfos,
+    ) -> Vec<EnvironmentVariable>;
 }
 
 impl<T: CloudProvider> ApplicationService for Application<T>
@@ -519,4 +739,31 @@ where
     fn get_build_mut(&mut self) -> &mut Build {
         self.build_mut()
     }
+
+    fn is_stateful(&self) -> bool {
+        !self.storage.is_empty()
+    }
+
+    fn ports(&self) -> &[Port] {
+        &self.ports
+    }
+
+    fn live_workload_name(&self, target: &DeploymentTarget) -> Result<String, EngineError> {
+        let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Update));
+        let live_slot = self.live_release_slot(target, &event_details)?;
+
+        Ok(workload_name(&self.sanitized_name(), live_slot))
+    }
+
+    fn database_environment_variables(
+        &self,
+        database_long_id: &Uuid,
+        connection_infos: &DatabaseConnectionInfos,
+    ) -> Vec<EnvironmentVariable> {
+        self.database_links
+            .iter()
+            .filter(|link| &link.database_long_id == database_long_id)
+            .flat_map(|link| self.linked_database_environment_variables(link, connection_infos))
+            .collect()
+    }
 }
@@ -0,0 +1,431 @@
+use crate::change_set::ServiceChangeSet;
+use crate::cloud_provider::environment::Environment;
+use crate::cloud_provider::models::{CanaryIngressDataTemplate, CanaryRouteDataTemplate};
+use crate::cloud_provider::service::{
+    deploy_stateless_service_release, helm_uninstall_release, plan_helm_service, plan_helm_service_release, Create,
+    Helm, Service,
+};
+use crate::cloud_provider::DeploymentTarget;
+use crate::cmd::kubectl::{
+    kubectl_exec_get_number_of_restart, kubectl_exec_get_pods, kubectl_exec_get_service_selector,
+};
+use crate::cmd::structs::KubernetesPodStatusPhase;
+use crate::errors::{CommandError, EngineError};
+use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, Stage};
+use crate::io_models::ReleaseAnalysis;
+use crate::models::application::Application;
+use crate::models::types::CloudProvider;
+use std::thread;
+use std::time::{Duration, Instant};
+use tera::Context as TeraContext;
+
+/// Label of the pods telling in which slot they are released
+const RELEASE_SLOT_LABEL: &str = "releaseSlot";
+const ANALYSIS_CANCELLATION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Blue-green and canary releases run the application in two slots, each one being its own helm release.
+/// The Service of the application, the backend of routers, selects the pods of the live slot only
+/// while the next release is deployed and analysed in the other slot.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ReleaseSlot {
+    Blue,
+    Green,
+}
+
+impl ReleaseSlot {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ReleaseSlot::Blue => "blue",
+            ReleaseSlot::Green => "green",
+        }
+    }
+
+    pub fn other(&self) -> ReleaseSlot {
+        match self {
+            ReleaseSlot::Blue => ReleaseSlot::Green,
+            ReleaseSlot::Green => ReleaseSlot::Blue,
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<ReleaseSlot> {
+        match label {
+            "blue" => Some(ReleaseSlot::Blue),
+            "green" => Some(ReleaseSlot::Green),
+            _ => None,
+        }
+    }
+}
+
+/// Name of the workloads and secret of the application released in `slot`, or in its default release
+pub(super) fn workload_name(sanitized_name: &str, slot: Option<ReleaseSlot>) -> String {
+    match slot {
+        Some(slot) => format!("{}-{}", sanitized_name, slot.as_str()),
+        None => sanitized_name.to_string(),
+    }
+}
+
+/// Total restarts of a pod containers, as returned by `kubectl_exec_get_number_of_restart`
+fn total_restart_count(output: &str) -> u32 {
+    output
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|count| count.parse::<u32>().ok())
+        .sum()
+}
+
+impl<T: CloudProvider> Application<T>
+where
+    Application<T>: Service,
+{
+    fn release_slot_helm_release_name(&self, slot: ReleaseSlot) -> String {
+        crate::string::cut(format!("application-{}-{}", self.id(), slot.as_str()), 50)
+    }
+
+    fn release_slot_selector(&self, slot: ReleaseSlot) -> String {
+        format!("appId={},{}={}", self.id(), RELEASE_SLOT_LABEL, slot.as_str())
+    }
+
+    /// Slot currently served by the application Service, `None` when the application is not released in slots
+    pub(super) fn live_release_slot(
+        &self,
+        target: &DeploymentTarget,
+        event_details: &EventDetails,
+    ) -> Result<Option<ReleaseSlot>, EngineError> {
+        let kubernetes = target.kubernetes;
+        let selector = kubectl_exec_get_service_selector(
+            kubernetes.get_kubeconfig_file_path()?,
+            target.environment.namespace(),
+            &self.sanitized_name(),
+            kubernetes.cloud_provider().credentials_environment_variables(),
+        )
+        .map_err(|e| EngineError::new_k8s_service_issue(event_details.clone(), e))?;
+
+        Ok(selector
+            .and_then(|selector| selector.get(RELEASE_SLOT_LABEL).cloned())
+            .and_then(|slot| ReleaseSlot::from_label(&slot)))
+    }
+
+    /// Deploys the application in the slot which is not live, analyses it and then switches the
+    /// application Service to it. The live slot keeps serving if anything fails before the switch.
+    pub(super) fn deploy_with_release_slots(
+        &self,
+        target: &DeploymentTarget,
+        analysis: &ReleaseAnalysis,
+        canary_weight_percent: Option<u8>,
+    ) -> Result<(), EngineError> {
+        let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
+        let live_slot = self.live_release_slot(target, &event_details)?;
+        let slot = live_slot
+            .map(|live_slot| live_slot.other())
+            .unwrap_or(ReleaseSlot::Blue);
+
+        self.log_release_info(
+            &event_details,
+            match canary_weight_percent {
+                Some(weight) => format!(
+                    "Deploying release `{}` of application `{}`, it will receive {}% of the traffic until promoted",
+                    slot.as_str(),
+                    self.name(),
+                    weight
+                ),
+                None => format!(
+                    "Deploying release `{}` of application `{}`, it will receive traffic once promoted",
+                    slot.as_str(),
+                    self.name()
+                ),
+            },
+        );
+
+        let deployed_and_analysed = self
+            .deploy_release_slot(target, slot, canary_weight_percent)
+            .and_then(|_| self.on_create_check())
+            .and_then(|_| self.analyse_release_slot(target, slot, analysis, &event_details));
+        if let Err(err) = deployed_and_analysed {
+            self.log_release_info(
+                &event_details,
+                format!("Aborting release `{}` of application `{}`", slot.as_str(), self.name()),
+            );
+            // the release error is the one to report, a leftover release is uninstalled by the next one anyway
+            if let Err(uninstall_err) = helm_uninstall_release(
+                target.kubernetes,
+                target.environment,
+                &self.release_slot_helm_release_name(slot),
+                event_details.clone(),
+            ) {
+                self.logger().log(EngineEvent::Error(
+                    uninstall_err,
+                    Some(EventMessage::new_from_safe(format!(
+                        "Cannot uninstall aborted release `{}` of application `{}`",
+                        slot.as_str(),
+                        self.name()
+                    ))),
+                ));
+            }
+            return Err(err);
+        }
+
+        self.switch_to_release_slot(target, slot)?;
+        self.log_release_info(
+            &event_details,
+            format!("Release `{}` of application `{}` is promoted", slot.as_str(), self.name()),
+        );
+
+        // the canary Ingresses are not needed anymore, the application Service serves the new release
+        if canary_weight_percent.is_some() {
+            self.deploy_release_slot(target, slot, None)?;
+        }
+        if let Some(live_slot) = live_slot {
+            helm_uninstall_release(
+                target.kubernetes,
+                target.environment,
+                &self.release_slot_helm_release_name(live_slot),
+                event_details,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Uninstalls the releases of both slots
+    pub(super) fn delete_release_slots(
+        &self,
+        target: &DeploymentTarget,
+        event_details: EventDetails,
+    ) -> Result<(), EngineError> {
+        for slot in [ReleaseSlot::Blue, ReleaseSlot::Green] {
+            helm_uninstall_release(
+                target.kubernetes,
+                target.environment,
+                &self.release_slot_helm_release_name(slot),
+                event_details.clone(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Plans the application against the release of its live slot, where its workloads run,
+    /// or against its default release when it has not been released in slots yet
+    pub(super) fn plan_with_release_slots(
+        &self,
+        target: &DeploymentTarget,
+        event_details: EventDetails,
+    ) -> Result<ServiceChangeSet, EngineError> {
+        let live_slot = match self.live_release_slot(target, &event_details)? {
+            Some(live_slot) => live_slot,
+            None => return plan_helm_service(target, self, &[self.helm_chart_dir()], event_details),
+        };
+
+        // a promoted release no longer receives canary traffic, see `deploy_with_release_slots`
+        plan_helm_service_release(
+            target,
+            self,
+            self.release_slot_helm_release_name(live_slot),
+            format!("{}/{}", self.workspace_directory(), live_slot.as_str()),
+            &[self.helm_chart_dir()],
+            &|| self.release_slot_tera_context(target, live_slot, None),
+            event_details,
+        )
+    }
+
+    /// Workloads of the application, with the canary Ingresses when some traffic has to be sent to them
+    fn deploy_release_slot(
+        &self,
+        target: &DeploymentTarget,
+        slot: ReleaseSlot,
+        canary_weight_percent: Option<u8>,
+    ) -> Result<(), EngineError> {
+        deploy_stateless_service_release(
+            target,
+            self,
+            self.release_slot_helm_release_name(slot),
+            format!("{}/{}", self.workspace_directory(), slot.as_str()),
+            self.release_slot_tera_context(target, slot, canary_weight_percent)?,
+            Some(self.release_slot_selector(slot)),
+        )
+    }
+
+    fn release_slot_tera_context(
+        &self,
+        target: &DeploymentTarget,
+        slot: ReleaseSlot,
+        canary_weight_percent: Option<u8>,
+    ) -> Result<TeraContext, EngineError> {
+        let mut context = self.tera_context(target)?;
+        context.insert("workload_name", &workload_name(&self.sanitized_name(), Some(slot)));
+        context.insert("render_workloads", &true);
+        context.insert("render_network", &false);
+        context.insert("release_slot", slot.as_str());
+        context.insert("canary_weight_percent", &canary_weight_percent);
+        let canary_ingresses = match canary_weight_percent {
+            Some(_) => self.canary_ingresses(target.environment, slot),
+            None => vec![],
+        };
+        context.insert("canary_ingresses", &canary_ingresses);
+
+        Ok(context)
+    }
+
+    /// Services and network policies of the application, selecting the pods of `slot`
+    fn switch_to_release_slot(&self, target: &DeploymentTarget, slot: ReleaseSlot) -> Result<(), EngineError> {
+        let mut context = self.tera_context(target)?;
+        context.insert("render_workloads", &false);
+        context.insert("render_network", &true);
+        context.insert("selector_release_slot", slot.as_str());
+
+        deploy_stateless_service_release(
+            target,
+            self,
+            self.helm_release_name(),
+            self.workspace_directory(),
+            context,
+            Some(self.release_slot_selector(slot)),
+        )
+    }
+
+    /// One canary Ingress per router serving the application, on the same hosts and paths
+    fn canary_ingresses(&self, environment: &Environment, slot: ReleaseSlot) -> Vec<CanaryIngressDataTemplate> {
+        environment
+            .routers
+            .iter()
+            .filter_map(|router| {
+                let routes = router
+                    .routes()
+                    .iter()
+                    .filter(|route| route.application_name == self.name())
+                    .filter_map(|route| {
+                        route.application_port(&self.ports).map(|port| CanaryRouteDataTemplate {
+                            path: route.path.clone(),
+                            port,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                if routes.is_empty() {
+                    return None;
+                }
+
+                Some(CanaryIngressDataTemplate {
+                    name: format!("{}-{}-canary-{}", self.sanitized_name(), slot.as_str(), router.id()),
+                    ingress_class: router.ingress_class().to_string(),
+                    hosts: router.domains().iter().map(|domain| domain.to_string()).collect(),
+                    routes,
+                })
+            })
+            .collect()
+    }
+
+    /// Waits for the analysis duration then checks all pods of the release are ready and did not restart too much
+    fn analyse_release_slot(
+        &self,
+        target: &DeploymentTarget,
+        slot: ReleaseSlot,
+        analysis: &ReleaseAnalysis,
+        event_details: &EventDetails,
+    ) -> Result<(), EngineError> {
+        self.log_release_info(
+            event_details,
+            format!(
+                "Analysing release `{}` of application `{}` during {} seconds",
+                slot.as_str(),
+                self.name(),
+                analysis.duration_sec
+            ),
+        );
+        let analysis_started_at = Instant::now();
+        while analysis_started_at.elapsed() < Duration::from_secs(analysis.duration_sec as u64) {
+            if target.environment.is_canceled() {
+                return Err(EngineError::new_task_cancellation_requested(event_details.clone()));
+            }
+            thread::sleep(ANALYSIS_CANCELLATION_CHECK_INTERVAL);
+        }
+
+        let kubernetes = target.kubernetes;
+        let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+        let namespace = target.environment.namespace();
+        let envs = kubernetes.cloud_provider().credentials_environment_variables();
+        let abort = |reason: String, raw_error: Option<CommandError>| {
+            EngineError::new_application_release_aborted(
+                event_details.clone(),
+                self.name().to_string(),
+                slot.as_str().to_string(),
+                reason,
+                raw_error,
+            )
+        };
+
+        let pods = kubectl_exec_get_pods(
+            &kubernetes_config_file_path,
+            Some(namespace),
+            Some(&self.release_slot_selector(slot)),
+            envs.clone(),
+        )
+        .map_err(|e| abort("cannot get its pods".to_string(), Some(e)))?;
+        if pods.items.is_empty() {
+            return Err(abort("no pod is running".to_string(), None));
+        }
+
+        for pod in pods.items {
+            let is_ready = pod.status.phase == KubernetesPodStatusPhase::Running
+                && pod
+                    .status
+                    .container_statuses
+                    .as_ref()
+                    .map(|statuses| statuses.iter().all(|status| status.ready))
+                    .unwrap_or(false);
+            if !is_ready {
+                return Err(abort(format!("pod `{}` is not ready", pod.metadata.name), None));
+            }
+
+            let restarts = kubectl_exec_get_number_of_restart(
+                &kubernetes_config_file_path,
+                namespace,
+                &pod.metadata.name,
+                envs.clone(),
+            )
+            .map_err(|e| abort(format!("cannot get restarts of pod `{}`", pod.metadata.name), Some(e)))?;
+            let restarts = total_restart_count(&restarts);
+            if restarts > analysis.max_restarts {
+                return Err(abort(
+                    format!(
+                        "pod `{}` restarted {} times, {} allowed",
+                        pod.metadata.name, restarts, analysis.max_restarts
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn log_release_info(&self, event_details: &EventDetails, message: String) {
+        self.logger()
+            .log(EngineEvent::Info(event_details.clone(), EventMessage::new_from_safe(message)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application_release::{total_restart_count, workload_name, ReleaseSlot};
+
+    #[test]
+    fn test_release_slot() {
+        assert_eq!(ReleaseSlot::Blue.other(), ReleaseSlot::Green);
+        assert_eq!(ReleaseSlot::Green.other(), ReleaseSlot::Blue);
+        assert_eq!(ReleaseSlot::from_label(ReleaseSlot::Green.as_str()), Some(ReleaseSlot::Green));
+        assert_eq!(ReleaseSlot::from_label("red"), None);
+    }
+
+    #[test]
+    fn test_workload_name() {
+        assert_eq!(workload_name("app-abcd", None), "app-abcd");
+        assert_eq!(workload_name("app-abcd", Some(ReleaseSlot::Green)), "app-abcd-green");
+    }
+
+    #[test]
+    fn test_total_restart_count() {
+        assert_eq!(total_restart_count(""), 0);
+        assert_eq!(total_restart_count("3"), 3);
+        assert_eq!(total_restart_count("1,2"), 3);
+        assert_eq!(total_restart_count(" 0,4\n"), 4);
+    }
+}
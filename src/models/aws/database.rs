@@ -3,7 +3,6 @@ use crate::cloud_provider::service::{
     ServiceVersionCheckResult,
 };
 use crate::cloud_provider::{service, DeploymentTarget};
-use crate::cmd::kubectl;
 use crate::errors::EngineError;
 use crate::events::{EnvironmentStep, EventDetails, Stage};
 use crate::models::aws::database_utils::{
@@ -169,12 +168,6 @@ where
         let kube_config_file_path = kubernetes.get_kubeconfig_file_path()?;
         context.insert("kubeconfig_path", &kube_config_file_path);
 
-        kubectl::kubectl_exec_create_namespace_without_labels(
-            environment.namespace(),
-            kube_config_file_path.as_str(),
-            kubernetes.cloud_provider().credentials_environment_variables(),
-        );
-
         context.insert("namespace", environment.namespace());
 
         let version = self
@@ -236,6 +229,7 @@ where
         context.insert("final_snapshot_name", &format!("qovery-{}-final-snap", self.id));
         context.insert("delete_automated_backups", &self.context().is_test_cluster());
         context.insert("publicly_accessible", &options.publicly_accessible);
+        self.insert_read_replicas_into_tera_context(target, options, &mut context);
 
         if self.context.resource_expiration_in_seconds().is_some() {
             context.insert("resource_expiration_in_seconds", &self.context.resource_expiration_in_seconds())
@@ -1,11 +1,14 @@
+use crate::change_set::ServiceChangeSet;
+use crate::cloud_provider::models::DatabaseConnectionInfos;
 use crate::cloud_provider::service::{
-    check_service_version, default_tera_context, delete_stateful_service, deploy_stateful_service, get_tfstate_name,
-    get_tfstate_suffix, scale_down_database, send_progress_on_long_task, Action, Create, DatabaseOptions,
+    check_service_version, default_tera_context, delete_stateful_service, deploy_stateful_service,
+    detect_managed_stateful_service_drift, get_tfstate_name, get_tfstate_suffix, plan_helm_service,
+    plan_managed_stateful_service, scale_down_database, send_progress_on_long_task, Action, Create, DatabaseOptions,
     DatabaseService, Delete, Helm, Pause, Service, ServiceType, ServiceVersionCheckResult, StatefulService, Terraform,
 };
 use crate::cloud_provider::utilities::{check_domain_for, managed_db_name_sanitizer, print_action};
 use crate::cloud_provider::{service, DeploymentTarget};
-use crate::cmd::kubectl;
+use crate::cmd::terraform::TerraformDrift;
 use crate::errors::EngineError;
 use crate::events::{EnvironmentStep, EventDetails, Stage, ToTransmitter, Transmitter};
 use crate::io_models::{Context, Listen, Listener, Listeners, ListenersHelper};
@@ -58,6 +61,14 @@ pub trait DatabaseType<T: CloudProvider, M: DatabaseMode> {
     fn short_name() -> &'static str;
     fn lib_directory_name() -> &'static str;
     fn db_type() -> service::DatabaseType;
+    /// clients must connect with TLS, managed databases enforcing it override it
+    fn is_tls_required() -> bool {
+        false
+    }
+    /// port imposed by the cloud provider, the requested one is ignored when it is set
+    fn forced_port() -> Option<u16> {
+        None
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -209,7 +220,7 @@ where
     }
 
     fn private_port(&self) -> Option<u16> {
-        Some(self.private_port)
+        Some(T::forced_port().unwrap_or(self.private_port))
     }
 
     fn total_cpus(&self) -> String {
@@ -430,14 +441,71 @@ where
         self
     }
 
+    fn plan_action(&self, target: &DeploymentTarget) -> Result<ServiceChangeSet, EngineError> {
+        let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
+        if M::is_managed() {
+            plan_managed_stateful_service(target, self, event_details)
+        } else {
+            plan_helm_service(
+                target,
+                self,
+                &[self.helm_chart_dir(), self.helm_chart_values_dir()],
+                event_details,
+            )
+        }
+    }
+
     fn is_managed_service(&self) -> bool {
         M::is_managed()
     }
 }
 
-impl<C: CloudProvider, M: DatabaseMode, T: DatabaseType<C, M>> DatabaseService for Database<C, M, T> where
-    Database<C, M, T>: ToTeraContext
+impl<C: CloudProvider, M: DatabaseMode, T: DatabaseType<C, M, DatabaseOptions = DatabaseOptions>> DatabaseService
+    for Database<C, M, T>
+where
+    Database<C, M, T>: ToTeraContext,
 {
+    fn connection_infos(&self, target: &DeploymentTarget) -> DatabaseConnectionInfos {
+        // database created along the instance, see chart values and terraform templates
+        let database_name = match (M::is_managed(), T::db_type()) {
+            (_, service::DatabaseType::MySQL) | (true, service::DatabaseType::PostgreSQL) => {
+                Some(self.sanitized_name())
+            }
+            (false, service::DatabaseType::PostgreSQL) | (_, service::DatabaseType::MongoDB) => Some(self.name.clone()),
+            (_, service::DatabaseType::Redis) => None,
+        };
+
+        DatabaseConnectionInfos {
+            db_type: T::db_type(),
+            host: self.fqdn(target, &self.fqdn, M::is_managed()),
+            read_host: self.read_fqdn(target, &self.options),
+            port: T::forced_port().unwrap_or(self.private_port),
+            login: self.options.login.clone(),
+            password: self.options.password.clone(),
+            database_name,
+            tls: T::is_tls_required(),
+        }
+    }
+
+    fn detect_drift(&self, target: &DeploymentTarget) -> Result<Vec<TerraformDrift>, EngineError> {
+        if !M::is_managed() {
+            // container databases are not deployed with terraform
+            return Ok(vec![]);
+        }
+
+        detect_managed_stateful_service_drift(target, self, self.logger())
+    }
+}
+
+fn read_replicas_fqdn(options: &DatabaseOptions, read_service_name: &str, namespace: &str) -> Option<String> {
+    if options.read_replicas_count == 0 {
+        return None;
+    }
+
+    match (&options.read_host, options.publicly_accessible) {
+        (Some(read_host), true) => Some(read_host.to_string()),
+        _ => Some(format!("{}.{}.svc.cluster.local", read_service_name, namespace)),
+    }
 }
 
 impl<C: CloudProvider, M: DatabaseMode, T: DatabaseType<C, M>> Database<C, M, T>
@@ -455,6 +523,34 @@ where
         check_service_version(fn_version(self.version.to_string()), self, event_details, self.logger())
     }
 
+    /// Returns the fqdn of the endpoint serving the read replicas, or None if there is no replica
+    pub(super) fn read_fqdn(&self, target: &DeploymentTarget, options: &DatabaseOptions) -> Option<String> {
+        // read services are named after the ones created by the helm charts or the external name service
+        let read_service_name = match (M::is_managed(), T::db_type()) {
+            (true, _) => format!("{}-read", self.fqdn_id),
+            (false, service::DatabaseType::MySQL) => format!("{}-slave", self.sanitized_name()),
+            (false, _) => format!("{}-read", self.sanitized_name()),
+        };
+
+        read_replicas_fqdn(options, &read_service_name, target.environment.namespace())
+    }
+
+    pub(super) fn insert_read_replicas_into_tera_context(
+        &self,
+        target: &DeploymentTarget,
+        options: &DatabaseOptions,
+        context: &mut TeraContext,
+    ) {
+        let read_fqdn = self.read_fqdn(target, options);
+
+        context.insert("read_replicas_enabled", &read_fqdn.is_some());
+        context.insert("read_replicas_count", &options.read_replicas_count);
+        if let Some(read_fqdn) = read_fqdn {
+            context.insert("database_read_fqdn", options.read_host.as_deref().unwrap_or(read_fqdn.as_str()));
+            context.insert("read_fqdn", read_fqdn.as_str());
+        }
+    }
+
     pub(super) fn to_tera_context_for_container(
         &self,
         target: &DeploymentTarget,
@@ -469,12 +565,6 @@ where
         let kube_config_file_path = kubernetes.get_kubeconfig_file_path()?;
         context.insert("kubeconfig_path", &kube_config_file_path);
 
-        kubectl::kubectl_exec_create_namespace_without_labels(
-            environment.namespace(),
-            kube_config_file_path.as_str(),
-            kubernetes.cloud_provider().credentials_environment_variables(),
-        );
-
         context.insert("namespace", environment.namespace());
 
         let version = self.get_version(event_details)?.matched_version().to_string();
@@ -504,6 +594,7 @@ where
         context.insert("tfstate_suffix_name", &get_tfstate_suffix(self));
         context.insert("tfstate_name", &get_tfstate_name(self));
         context.insert("publicly_accessible", &self.publicly_accessible);
+        self.insert_read_replicas_into_tera_context(target, options, &mut context);
 
         if self.context.resource_expiration_in_seconds().is_some() {
             context.insert("resource_expiration_in_seconds", &self.context.resource_expiration_in_seconds())
@@ -512,3 +603,48 @@ where
         Ok(context)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud_provider::service::DatabaseOptions;
+    use crate::io_models::DatabaseMode;
+    use crate::models::database::read_replicas_fqdn;
+
+    #[test]
+    fn test_read_replicas_fqdn() {
+        let options = DatabaseOptions {
+            login: "superuser".to_string(),
+            password: "password".to_string(),
+            host: "postgresql-abcd.example.com".to_string(),
+            port: 5432,
+            mode: DatabaseMode::MANAGED,
+            disk_size_in_gib: 10,
+            database_disk_type: "gp2".to_string(),
+            encrypt_disk: true,
+            activate_high_availability: false,
+            activate_backups: false,
+            read_replicas_count: 0,
+            read_host: Some("postgresql-abcd-read.example.com".to_string()),
+            publicly_accessible: false,
+        };
+        assert_eq!(read_replicas_fqdn(&options, "postgresql-abcd-read", "ns"), None);
+
+        let options = DatabaseOptions {
+            read_replicas_count: 2,
+            ..options
+        };
+        assert_eq!(
+            read_replicas_fqdn(&options, "postgresql-abcd-read", "ns"),
+            Some("postgresql-abcd-read.ns.svc.cluster.local".to_string())
+        );
+
+        let options = DatabaseOptions {
+            publicly_accessible: true,
+            ..options
+        };
+        assert_eq!(
+            read_replicas_fqdn(&options, "postgresql-abcd-read", "ns"),
+            Some("postgresql-abcd-read.example.com".to_string())
+        );
+    }
+}
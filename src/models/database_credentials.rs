@@ -0,0 +1,834 @@
+use crate::cloud_provider::environment::Environment;
+use crate::cloud_provider::kubernetes::Kubernetes;
+use crate::cloud_provider::models::DatabaseConnectionInfos;
+use crate::cloud_provider::service::{DatabaseService, DatabaseType};
+use crate::cloud_provider::DeploymentTarget;
+use crate::cmd::kubectl::{
+    kubectl_exec_apply_secret, kubectl_exec_delete_secret, kubectl_exec_get_secrets, kubectl_exec_patch_secret,
+    kubectl_exec_rollout_restart_deployment, kubectl_exec_rollout_restart_statefulset, kubectl_exec_rollout_status,
+    kubectl_exec_run_pod, ScalingKind,
+};
+use crate::errors::{CommandError, EngineError};
+use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, Stage};
+use crate::logger::Logger;
+use crate::models::application::ApplicationService;
+use std::fmt::{Display, Formatter};
+use uuid::Uuid;
+
+const ROLLOUT_TIMEOUT_IN_SECONDS: u32 = 600;
+const ROTATED_CREDENTIALS_LABEL: &str = "credentialsRotation";
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct DatabaseCredentials {
+    pub login: String,
+    pub password: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RotationStep {
+    CreateNewCredentials,
+    PersistNewCredentials,
+    UpdateApplicationsSecrets,
+    RestartApplications,
+    DropOldCredentials,
+}
+
+impl Display for RotationStep {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match &self {
+                RotationStep::CreateNewCredentials => "create-new-credentials",
+                RotationStep::PersistNewCredentials => "persist-new-credentials",
+                RotationStep::UpdateApplicationsSecrets => "update-applications-secrets",
+                RotationStep::RestartApplications => "restart-applications",
+                RotationStep::DropOldCredentials => "drop-old-credentials",
+            },
+        )
+    }
+}
+
+struct LinkedApplication<'a> {
+    application: &'a dyn ApplicationService,
+    old_variables: Vec<(String, String)>,
+    new_variables: Vec<(String, String)>,
+}
+
+fn rotated_credentials_secret_name(database: &dyn DatabaseService) -> String {
+    format!("{}-rotated-credentials", database.sanitized_name())
+}
+
+/// Credentials of the database applications are using: the rotated ones if any, the original ones otherwise.
+/// Original credentials are managed by the chart or the cloud provider and are never dropped by a rotation.
+pub fn current_connection_infos(
+    target: &DeploymentTarget,
+    database: &dyn DatabaseService,
+) -> Result<DatabaseConnectionInfos, CommandError> {
+    let original_infos = database.connection_infos(target);
+    let kubernetes_config_file_path = target
+        .kubernetes
+        .get_kubeconfig_file_path()
+        .map_err(|e| e.underlying_error().unwrap_or_default())?;
+
+    let secrets = kubectl_exec_get_secrets(
+        kubernetes_config_file_path,
+        target.environment.namespace(),
+        &format!("{}={}", ROTATED_CREDENTIALS_LABEL, database.id()),
+        target.kubernetes.cloud_provider().credentials_environment_variables(),
+    )?;
+    let rotated_credentials = secrets
+        .items
+        .iter()
+        .find(|secret| secret.metadata.name == rotated_credentials_secret_name(database))
+        .map(|secret| -> Result<DatabaseCredentials, CommandError> {
+            let decode = |key: &str| {
+                secret
+                    .data
+                    .get(key)
+                    .and_then(|value| base64::decode(value).ok())
+                    .and_then(|value| String::from_utf8(value).ok())
+                    .ok_or_else(|| {
+                        CommandError::new_from_safe_message(format!(
+                            "Rotated credentials secret of database `{}` has no valid `{}`.",
+                            database.name(),
+                            key
+                        ))
+                    })
+            };
+
+            Ok(DatabaseCredentials {
+                login: decode("login")?,
+                password: decode("password")?,
+            })
+        })
+        .transpose()?;
+
+    Ok(match rotated_credentials {
+        Some(credentials) => DatabaseConnectionInfos {
+            login: credentials.login,
+            password: credentials.password,
+            ..original_infos
+        },
+        None => original_infos,
+    })
+}
+
+/// Rotates credentials of a database without downtime for the applications linked to it:
+/// new credentials are created and persisted, linked applications secrets are updated and restarted,
+/// then previously rotated credentials are dropped. The original credentials are always kept.
+/// Each step is reverted if a following one fails.
+pub fn rotate_database_credentials(
+    kubernetes: &dyn Kubernetes,
+    environment: &Environment,
+    database_long_id: &Uuid,
+    new_credentials: &DatabaseCredentials,
+    logger: &dyn Logger,
+) -> Result<(), EngineError> {
+    let database = match environment.databases.iter().find(|db| db.long_id() == database_long_id) {
+        Some(database) => database,
+        None => {
+            let message = format!(
+                "Cannot rotate credentials, database `{}` is not part of the environment.",
+                database_long_id
+            );
+            return Err(EngineError::new_unknown(
+                kubernetes.get_event_details(Stage::Environment(EnvironmentStep::Update)),
+                message.to_string(),
+                message,
+                None,
+                None,
+                None,
+            ));
+        }
+    };
+    let event_details = database.get_event_details(Stage::Environment(EnvironmentStep::Update));
+
+    let target = DeploymentTarget {
+        kubernetes,
+        environment,
+    };
+    let original_infos = database.connection_infos(&target);
+    if database_client(&original_infos, "").is_none() {
+        return Err(EngineError::new_database_credentials_rotation_not_supported(
+            event_details,
+            original_infos.db_type.to_string(),
+        ));
+    }
+
+    let old_infos = current_connection_infos(&target, database.as_ref()).map_err(|err| {
+        EngineError::new_database_credentials_rotation_error(
+            event_details.clone(),
+            database.name().to_string(),
+            RotationStep::CreateNewCredentials.to_string(),
+            err,
+            true,
+        )
+    })?;
+
+    if new_credentials.login.is_empty()
+        || new_credentials.login == old_infos.login
+        || new_credentials.login == original_infos.login
+    {
+        return Err(EngineError::new_database_credentials_rotation_error(
+            event_details,
+            database.name().to_string(),
+            RotationStep::CreateNewCredentials.to_string(),
+            CommandError::new_from_safe_message(
+                "New login must not be empty and must differ from the current and the original ones.".to_string(),
+            ),
+            true,
+        ));
+    }
+
+    let new_infos = DatabaseConnectionInfos {
+        login: new_credentials.login.clone(),
+        password: new_credentials.password.clone(),
+        ..database.connection_infos(&target)
+    };
+
+    let to_secret_data = |variables: Vec<crate::cloud_provider::models::EnvironmentVariable>| {
+        variables.into_iter().map(|ev| (ev.key, ev.value)).collect::<Vec<_>>()
+    };
+    let linked_applications = environment
+        .applications
+        .iter()
+        .map(|application| LinkedApplication {
+            application: application.as_ref(),
+            old_variables: to_secret_data(application.database_environment_variables(database_long_id, &old_infos)),
+            new_variables: to_secret_data(application.database_environment_variables(database_long_id, &new_infos)),
+        })
+        .filter(|linked| !linked.new_variables.is_empty())
+        .collect::<Vec<_>>();
+
+    let rotation = CredentialsRotation {
+        kubernetes,
+        environment,
+        database: database.as_ref(),
+        original_login: original_infos.login,
+        old_infos,
+        new_infos,
+        linked_applications,
+        event_details,
+        logger,
+    };
+
+    let mut executed_steps: Vec<RotationStep> = vec![];
+    for step in [
+        RotationStep::CreateNewCredentials,
+        RotationStep::PersistNewCredentials,
+        RotationStep::UpdateApplicationsSecrets,
+        RotationStep::RestartApplications,
+        RotationStep::DropOldCredentials,
+    ] {
+        rotation.log(format!("Credentials rotation step `{}` in progress.", step));
+
+        if let Err(err) = rotation.execute(step) {
+            rotation.log_warning(format!("Credentials rotation step `{}` has failed, reverting.", step));
+            let is_reverted = rotation.revert(&executed_steps);
+
+            return Err(EngineError::new_database_credentials_rotation_error(
+                rotation.event_details.clone(),
+                rotation.database.name().to_string(),
+                step.to_string(),
+                err,
+                is_reverted,
+            ));
+        }
+
+        executed_steps.push(step);
+    }
+
+    rotation.log("Credentials rotation is done.".to_string());
+
+    Ok(())
+}
+
+struct CredentialsRotation<'a> {
+    kubernetes: &'a dyn Kubernetes,
+    environment: &'a Environment,
+    database: &'a dyn DatabaseService,
+    original_login: String,
+    old_infos: DatabaseConnectionInfos,
+    new_infos: DatabaseConnectionInfos,
+    linked_applications: Vec<LinkedApplication<'a>>,
+    event_details: EventDetails,
+    logger: &'a dyn Logger,
+}
+
+impl<'a> CredentialsRotation<'a> {
+    fn log(&self, message: String) {
+        self.logger.log(EngineEvent::Info(
+            self.event_details.clone(),
+            EventMessage::new_from_safe(format!("Database `{}`: {}", self.database.name(), message)),
+        ));
+    }
+
+    fn log_warning(&self, message: String) {
+        self.logger.log(EngineEvent::Warning(
+            self.event_details.clone(),
+            EventMessage::new_from_safe(format!("Database `{}`: {}", self.database.name(), message)),
+        ));
+    }
+
+    fn execute(&self, step: RotationStep) -> Result<(), CommandError> {
+        match step {
+            RotationStep::CreateNewCredentials => self.run_database_script(
+                "create",
+                &self.old_infos,
+                &create_credentials_script(&self.old_infos, &self.new_infos, &self.original_login),
+            ),
+            RotationStep::PersistNewCredentials => self.persist_credentials(&self.new_infos),
+            RotationStep::UpdateApplicationsSecrets => self.update_applications_secrets(true),
+            RotationStep::RestartApplications => self.restart_applications(),
+            // original credentials are used by the chart or the cloud provider, they can't be dropped
+            RotationStep::DropOldCredentials if self.old_infos.login == self.original_login => {
+                self.log("Original credentials are kept.".to_string());
+                Ok(())
+            }
+            RotationStep::DropOldCredentials => self.run_database_script(
+                "drop",
+                &self.new_infos,
+                &drop_credentials_script(&self.new_infos, &self.old_infos.login, &self.original_login),
+            ),
+        }
+    }
+
+    /// Reverts executed steps, in reverse order. Errors are logged, as there is nothing more to do.
+    /// Returns whether all steps have been reverted.
+    fn revert(&self, executed_steps: &[RotationStep]) -> bool {
+        let mut is_reverted = true;
+        for step in executed_steps.iter().rev() {
+            let result = match step {
+                RotationStep::CreateNewCredentials => self.run_database_script(
+                    "revert",
+                    &self.old_infos,
+                    &revert_credentials_script(&self.old_infos, &self.new_infos.login),
+                ),
+                RotationStep::PersistNewCredentials => self.persist_credentials(&self.old_infos),
+                RotationStep::UpdateApplicationsSecrets => self
+                    .update_applications_secrets(false)
+                    .and_then(|_| self.restart_applications()),
+                // applications are restarted once their secrets have been restored
+                RotationStep::RestartApplications => Ok(()),
+                // old credentials are dropped at the very last step, there is nothing to revert after it
+                RotationStep::DropOldCredentials => Ok(()),
+            };
+
+            match result {
+                Ok(_) => self.log(format!("Credentials rotation step `{}` has been reverted.", step)),
+                Err(err) => {
+                    is_reverted = false;
+                    self.logger.log(EngineEvent::Error(
+                        EngineError::new_database_credentials_rotation_error(
+                            self.event_details.clone(),
+                            self.database.name().to_string(),
+                            step.to_string(),
+                            err,
+                            false,
+                        ),
+                        Some(EventMessage::new_from_safe(format!(
+                            "Credentials rotation step `{}` cannot be reverted.",
+                            step
+                        ))),
+                    ))
+                }
+            }
+        }
+
+        is_reverted
+    }
+
+    fn run_database_script(
+        &self,
+        action: &str,
+        connection_infos: &DatabaseConnectionInfos,
+        script: &str,
+    ) -> Result<(), CommandError> {
+        let kubernetes_config_file_path = self
+            .kubernetes
+            .get_kubeconfig_file_path()
+            .map_err(|e| e.underlying_error().unwrap_or_default())?;
+        let client = match database_client(connection_infos, script) {
+            Some(client) => client,
+            None => {
+                return Err(CommandError::new_from_safe_message(format!(
+                    "Credentials rotation is not supported for `{}` databases.",
+                    connection_infos.db_type.to_string()
+                )))
+            }
+        };
+        let pod_envs = client
+            .pod_envs
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect::<Vec<_>>();
+        let command = client.command.iter().map(|arg| arg.as_str()).collect::<Vec<_>>();
+
+        let _ = kubectl_exec_run_pod(
+            kubernetes_config_file_path,
+            self.environment.namespace(),
+            &format!("credentials-rotation-{}-{}", self.database.id(), action),
+            client.image,
+            &pod_envs,
+            &command,
+            &client.stdin,
+            self.kubernetes.cloud_provider().credentials_environment_variables(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Stores credentials used by applications, so next deployments keep injecting them.
+    /// Original credentials are not stored, they are provided along the database.
+    fn persist_credentials(&self, infos: &DatabaseConnectionInfos) -> Result<(), CommandError> {
+        let kubernetes_config_file_path = self
+            .kubernetes
+            .get_kubeconfig_file_path()
+            .map_err(|e| e.underlying_error().unwrap_or_default())?;
+        let secret_name = rotated_credentials_secret_name(self.database);
+        let envs = self.kubernetes.cloud_provider().credentials_environment_variables();
+
+        if infos.login == self.original_login {
+            return kubectl_exec_delete_secret(
+                &kubernetes_config_file_path,
+                self.environment.namespace(),
+                &secret_name,
+                envs,
+            );
+        }
+
+        kubectl_exec_apply_secret(
+            &kubernetes_config_file_path,
+            self.environment.namespace(),
+            &secret_name,
+            &[(ROTATED_CREDENTIALS_LABEL, self.database.id())],
+            &[
+                ("login".to_string(), base64::encode(&infos.login)),
+                ("password".to_string(), base64::encode(&infos.password)),
+            ],
+            envs,
+        )
+    }
+
+    /// Blue-green and canary applications run in the workloads of their live slot, with their own secret
+    fn live_workload_name(&self, linked: &LinkedApplication) -> Result<String, CommandError> {
+        let target = DeploymentTarget {
+            kubernetes: self.kubernetes,
+            environment: self.environment,
+        };
+
+        linked
+            .application
+            .live_workload_name(&target)
+            .map_err(|e| e.underlying_error().unwrap_or_default())
+    }
+
+    fn update_applications_secrets(&self, use_new_credentials: bool) -> Result<(), CommandError> {
+        let kubernetes_config_file_path = self
+            .kubernetes
+            .get_kubeconfig_file_path()
+            .map_err(|e| e.underlying_error().unwrap_or_default())?;
+
+        for linked in &self.linked_applications {
+            let data = match use_new_credentials {
+                true => &linked.new_variables,
+                false => &linked.old_variables,
+            };
+
+            kubectl_exec_patch_secret(
+                &kubernetes_config_file_path,
+                self.environment.namespace(),
+                &self.live_workload_name(linked)?,
+                data,
+                self.kubernetes.cloud_provider().credentials_environment_variables(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn restart_applications(&self) -> Result<(), CommandError> {
+        let kubernetes_config_file_path = self
+            .kubernetes
+            .get_kubeconfig_file_path()
+            .map_err(|e| e.underlying_error().unwrap_or_default())?;
+        let envs = self.kubernetes.cloud_provider().credentials_environment_variables();
+
+        for linked in &self.linked_applications {
+            let name = self.live_workload_name(linked)?;
+            let kind = match linked.application.is_stateful() {
+                true => {
+                    kubectl_exec_rollout_restart_statefulset(
+                        &kubernetes_config_file_path,
+                        &name,
+                        self.environment.namespace(),
+                        &envs,
+                    )?;
+                    ScalingKind::Statefulset
+                }
+                false => {
+                    kubectl_exec_rollout_restart_deployment(
+                        &kubernetes_config_file_path,
+                        &name,
+                        self.environment.namespace(),
+                        &envs,
+                    )?;
+                    ScalingKind::Deployment
+                }
+            };
+
+            // old credentials must not be dropped while some instances are still using them
+            kubectl_exec_rollout_status(
+                &kubernetes_config_file_path,
+                kind,
+                &name,
+                self.environment.namespace(),
+                ROLLOUT_TIMEOUT_IN_SECONDS,
+                &envs,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+struct DatabaseClient {
+    image: &'static str,
+    pod_envs: Vec<(String, String)>,
+    command: Vec<String>,
+    stdin: String,
+}
+
+/// One shot client running the given script against the database, authenticated with the given connection infos.
+/// The password and the script are sent through the client input, so they never appear in the pod spec.
+/// Returns None if credentials rotation is not supported for the database type.
+fn database_client(infos: &DatabaseConnectionInfos, script: &str) -> Option<DatabaseClient> {
+    let to_strings = |args: Vec<&str>| args.into_iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    match infos.db_type {
+        DatabaseType::PostgreSQL => {
+            let mut pod_envs = vec![
+                ("PGHOST".to_string(), infos.host.clone()),
+                ("PGPORT".to_string(), infos.port.to_string()),
+                ("PGUSER".to_string(), infos.login.clone()),
+                (
+                    "PGDATABASE".to_string(),
+                    infos.database_name.clone().unwrap_or_else(|| "postgres".to_string()),
+                ),
+            ];
+            if infos.tls {
+                pod_envs.push(("PGSSLMODE".to_string(), "require".to_string()));
+            }
+
+            Some(DatabaseClient {
+                image: "postgres:13-alpine",
+                pod_envs,
+                command: to_strings(vec![
+                    "sh",
+                    "-c",
+                    "IFS= read -r PGPASSWORD && export PGPASSWORD && exec psql -v ON_ERROR_STOP=1",
+                ]),
+                stdin: format!("{}\n{}\n", infos.password, script),
+            })
+        }
+        DatabaseType::MySQL => Some(DatabaseClient {
+            image: "mysql:8.0",
+            pod_envs: vec![
+                ("MYSQL_HOST".to_string(), infos.host.clone()),
+                ("MYSQL_TCP_PORT".to_string(), infos.port.to_string()),
+                ("MYSQL_USER".to_string(), infos.login.clone()),
+            ],
+            command: to_strings(vec![
+                "sh",
+                "-c",
+                match infos.tls {
+                    true => "IFS= read -r MYSQL_PWD && export MYSQL_PWD && exec mysql -u \"$MYSQL_USER\" --ssl-mode=REQUIRED",
+                    false => "IFS= read -r MYSQL_PWD && export MYSQL_PWD && exec mysql -u \"$MYSQL_USER\"",
+                },
+            ]),
+            stdin: format!("{}\n{}\n", infos.password, script),
+        }),
+        DatabaseType::MongoDB => {
+            let database = infos.database_name.clone().unwrap_or_else(|| "admin".to_string());
+
+            Some(DatabaseClient {
+                image: "mongo:4.4",
+                pod_envs: vec![],
+                command: vec![
+                    "mongo".to_string(),
+                    "--quiet".to_string(),
+                    format!(
+                        "mongodb://{}:{}/{}{}",
+                        infos.host,
+                        infos.port,
+                        database,
+                        if infos.tls { "?tls=true" } else { "" }
+                    ),
+                ],
+                // errors don't change the shell exit code when reading its input, they are turned into one
+                stdin: format!(
+                    "try {{ if (!db.getSiblingDB({}).auth({}, {})) {{ quit(1); }} {} }} catch (e) {{ print(e); quit(1); }}\n",
+                    js_literal(&database),
+                    js_literal(&infos.login),
+                    js_literal(&infos.password),
+                    script,
+                ),
+            })
+        }
+        // redis only has a single password shared by all clients, it can't be changed without downtime
+        DatabaseType::Redis => None,
+    }
+}
+
+fn sql_identifier(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// PostgreSQL has `standard_conforming_strings` on, backslashes are taken literally
+fn postgresql_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// MySQL treats backslashes in string literals as escape characters
+fn mysql_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+fn js_literal(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+/// Script creating new credentials, with the same privileges as the current ones.
+/// PostgreSQL roles are members of the original role, which owns objects of dropped credentials,
+/// and get the role memberships of the current credentials.
+fn create_credentials_script(
+    current: &DatabaseConnectionInfos,
+    new: &DatabaseConnectionInfos,
+    original_login: &str,
+) -> String {
+    match current.db_type {
+        DatabaseType::PostgreSQL => {
+            let mut script = format!(
+                "CREATE ROLE {} WITH LOGIN CREATEDB CREATEROLE PASSWORD {} IN ROLE {};",
+                sql_identifier(&new.login),
+                postgresql_literal(&new.password),
+                sql_identifier(original_login)
+            );
+            // current credentials will be dropped, objects they own are reassigned by the new ones
+            if current.login != original_login {
+                script.push_str(&format!(
+                    " GRANT {} TO {};",
+                    sql_identifier(&current.login),
+                    sql_identifier(&new.login)
+                ));
+            }
+            script.push_str(&format!(
+                " DO $$ DECLARE membership record; BEGIN FOR membership IN SELECT granted.rolname FROM pg_auth_members m JOIN pg_roles granted ON m.roleid = granted.oid JOIN pg_roles member ON m.member = member.oid WHERE member.rolname = {current} AND granted.rolname <> {original} LOOP EXECUTE format('GRANT %I TO %I', membership.rolname, {new}); END LOOP; END $$;",
+                current = postgresql_literal(&current.login),
+                original = postgresql_literal(original_login),
+                new = postgresql_literal(&new.login),
+            ));
+
+            script
+        }
+        DatabaseType::MySQL => format!(
+            "CREATE USER {login}@'%' IDENTIFIED BY {password}; GRANT ALL PRIVILEGES ON `{database}`.* TO {login}@'%' WITH GRANT OPTION; GRANT CREATE USER ON *.* TO {login}@'%';",
+            login = mysql_literal(&new.login),
+            password = mysql_literal(&new.password),
+            database = current.database_name.clone().unwrap_or_default().replace('`', "``"),
+        ),
+        DatabaseType::MongoDB => format!(
+            "var target = db.getSiblingDB({database}); target.createUser({{user: {login}, pwd: {password}, roles: target.getUser({current}).roles}});",
+            database = js_literal(&current.database_name.clone().unwrap_or_else(|| "admin".to_string())),
+            login = js_literal(&new.login),
+            password = js_literal(&new.password),
+            current = js_literal(&current.login),
+        ),
+        // not supported, see `database_client`
+        DatabaseType::Redis => String::new(),
+    }
+}
+
+/// Script dropping just created credentials, executed by the current ones.
+/// The new PostgreSQL role owns nothing yet and the current one is not member of it to reassign its objects.
+fn revert_credentials_script(current: &DatabaseConnectionInfos, new_login: &str) -> String {
+    match current.db_type {
+        DatabaseType::PostgreSQL => format!("DROP ROLE {};", sql_identifier(new_login)),
+        DatabaseType::MySQL | DatabaseType::MongoDB | DatabaseType::Redis => {
+            drop_credentials_script(current, new_login, &current.login)
+        }
+    }
+}
+
+/// Script dropping the given login, executed by the kept credentials.
+/// PostgreSQL objects owned by the dropped login are given to the original role.
+fn drop_credentials_script(kept: &DatabaseConnectionInfos, dropped_login: &str, original_login: &str) -> String {
+    match kept.db_type {
+        DatabaseType::PostgreSQL => format!(
+            "REASSIGN OWNED BY {dropped} TO {original}; DROP OWNED BY {dropped}; DROP ROLE {dropped};",
+            dropped = sql_identifier(dropped_login),
+            original = sql_identifier(original_login),
+        ),
+        DatabaseType::MySQL => format!("DROP USER {}@'%';", mysql_literal(dropped_login)),
+        DatabaseType::MongoDB => format!(
+            "db.getSiblingDB({}).dropUser({});",
+            js_literal(&kept.database_name.clone().unwrap_or_else(|| "admin".to_string())),
+            js_literal(dropped_login),
+        ),
+        // not supported, see `database_client`
+        DatabaseType::Redis => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud_provider::models::DatabaseConnectionInfos;
+    use crate::cloud_provider::service::DatabaseType;
+    use crate::models::database_credentials::{
+        create_credentials_script, database_client, drop_credentials_script, revert_credentials_script, DatabaseClient,
+    };
+
+    fn connection_infos(db_type: DatabaseType, login: &str, password: &str) -> DatabaseConnectionInfos {
+        DatabaseConnectionInfos {
+            db_type,
+            host: "database-abcd".to_string(),
+            read_host: None,
+            port: 5432,
+            login: login.to_string(),
+            password: password.to_string(),
+            database_name: Some("abcd".to_string()),
+            tls: false,
+        }
+    }
+
+    #[test]
+    fn test_postgresql_credentials_rotation_scripts() {
+        // setup:
+        let original = connection_infos(DatabaseType::PostgreSQL, "superuser", "password");
+        let new = connection_infos(DatabaseType::PostgreSQL, "superuser_v2", "pass'wo\\rd");
+        let next = connection_infos(DatabaseType::PostgreSQL, "superuser_v3", "password");
+        let memberships = |current: &str, new: &str| {
+            format!(" DO $$ DECLARE membership record; BEGIN FOR membership IN SELECT granted.rolname FROM pg_auth_members m JOIN pg_roles granted ON m.roleid = granted.oid JOIN pg_roles member ON m.member = member.oid WHERE member.rolname = '{}' AND granted.rolname <> 'superuser' LOOP EXECUTE format('GRANT %I TO %I', membership.rolname, '{}'); END LOOP; END $$;", current, new)
+        };
+
+        // execute & verify:
+        assert_eq!(
+            format!(
+                "CREATE ROLE \"superuser_v2\" WITH LOGIN CREATEDB CREATEROLE PASSWORD 'pass''wo\\rd' IN ROLE \"superuser\";{}",
+                memberships("superuser", "superuser_v2")
+            ),
+            create_credentials_script(&original, &new, &original.login)
+        );
+        assert_eq!("DROP ROLE \"superuser_v2\";", revert_credentials_script(&original, &new.login));
+
+        // previously rotated credentials are dropped, their objects are given to the original role
+        assert_eq!(
+            format!(
+                "CREATE ROLE \"superuser_v3\" WITH LOGIN CREATEDB CREATEROLE PASSWORD 'password' IN ROLE \"superuser\"; GRANT \"superuser_v2\" TO \"superuser_v3\";{}",
+                memberships("superuser_v2", "superuser_v3")
+            ),
+            create_credentials_script(&new, &next, &original.login)
+        );
+        assert_eq!(
+            "REASSIGN OWNED BY \"superuser_v2\" TO \"superuser\"; DROP OWNED BY \"superuser_v2\"; DROP ROLE \"superuser_v2\";",
+            drop_credentials_script(&next, &new.login, &original.login)
+        );
+    }
+
+    #[test]
+    fn test_mysql_credentials_rotation_scripts() {
+        // setup:
+        let current = connection_infos(DatabaseType::MySQL, "superuser", "password");
+        let new = connection_infos(DatabaseType::MySQL, "superuser_v2", "pass'wo\\rd");
+
+        // execute & verify:
+        assert_eq!(
+            "CREATE USER 'superuser_v2'@'%' IDENTIFIED BY 'pass''wo\\\\rd'; GRANT ALL PRIVILEGES ON `abcd`.* TO 'superuser_v2'@'%' WITH GRANT OPTION; GRANT CREATE USER ON *.* TO 'superuser_v2'@'%';",
+            create_credentials_script(&current, &new, &current.login)
+        );
+        assert_eq!(
+            "DROP USER 'superuser'@'%';",
+            drop_credentials_script(&new, &current.login, &current.login)
+        );
+    }
+
+    #[test]
+    fn test_mongodb_credentials_rotation_scripts() {
+        // setup:
+        let current = connection_infos(DatabaseType::MongoDB, "superuser", "password");
+        let new = connection_infos(DatabaseType::MongoDB, "superuser_v2", "pass\"word");
+
+        // execute & verify:
+        assert_eq!(
+            "var target = db.getSiblingDB(\"abcd\"); target.createUser({user: \"superuser_v2\", pwd: \"pass\\\"word\", roles: target.getUser(\"superuser\").roles});",
+            create_credentials_script(&current, &new, &current.login)
+        );
+        assert_eq!(
+            "db.getSiblingDB(\"abcd\").dropUser(\"superuser\");",
+            drop_credentials_script(&new, &current.login, &current.login)
+        );
+    }
+
+    #[test]
+    fn test_database_client_keeps_secrets_out_of_pod_spec() {
+        for db_type in [DatabaseType::PostgreSQL, DatabaseType::MySQL, DatabaseType::MongoDB] {
+            // setup:
+            let infos = connection_infos(db_type, "superuser", "s3cr3t");
+            let script = create_credentials_script(
+                &infos,
+                &connection_infos(db_type, "superuser_v2", "n3w_s3cr3t"),
+                &infos.login,
+            );
+
+            // execute:
+            let client = database_client(&infos, &script).expect("client should be supported");
+
+            // verify:
+            for value in client
+                .command
+                .iter()
+                .chain(client.pod_envs.iter().map(|(_, value)| value))
+            {
+                assert!(!value.contains("s3cr3t"), "`{}` leaks a password", value);
+            }
+            assert!(client.stdin.contains("s3cr3t"));
+            assert!(client.stdin.contains("n3w_s3cr3t"));
+        }
+
+        assert!(database_client(&connection_infos(DatabaseType::Redis, "default", "s3cr3t"), "").is_none());
+    }
+    #[test]
+    fn test_database_client_requires_tls() {
+        for (db_type, tls_setting) in [
+            (DatabaseType::PostgreSQL, "PGSSLMODE=require"),
+            (DatabaseType::MySQL, "--ssl-mode=REQUIRED"),
+            (DatabaseType::MongoDB, "tls=true"),
+        ] {
+            // setup:
+            let mut infos = connection_infos(db_type, "superuser", "s3cr3t");
+            let settings = |client: &DatabaseClient| {
+                client
+                    .command
+                    .iter()
+                    .cloned()
+                    .chain(
+                        client
+                            .pod_envs
+                            .iter()
+                            .map(|(name, value)| format!("{}={}", name, value)),
+                    )
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+
+            // execute & verify:
+            let client = database_client(&infos, "").expect("client should be supported");
+            assert!(!settings(&client).contains(tls_setting));
+
+            infos.tls = true;
+            let client = database_client(&infos, "").expect("client should be supported");
+            assert!(settings(&client).contains(tls_setting));
+        }
+    }
+}
@@ -1,11 +1,20 @@
-use crate::cloud_provider::service::{check_service_version, DatabaseOptions, Service};
+use crate::cloud_provider::service::{
+    check_service_version, default_tera_context, get_tfstate_name, get_tfstate_suffix, DatabaseOptions, Service,
+    ServiceVersionCheckResult,
+};
 use crate::cloud_provider::{service, DeploymentTarget};
 use crate::errors::EngineError;
-use crate::models::database::{Container, Database, DatabaseType, MongoDB, MySQL, PostgresSQL, Redis};
+use crate::events::{EnvironmentStep, EventDetails, Stage};
+use crate::models::database::{
+    Container, Database, DatabaseMode, DatabaseType, Managed, MongoDB, MySQL, PostgresSQL, Redis,
+};
 use crate::models::database_utils::{
     get_self_hosted_mongodb_version, get_self_hosted_mysql_version, get_self_hosted_postgres_version,
     get_self_hosted_redis_version,
 };
+use crate::models::digital_ocean::database_utils::{
+    pick_managed_mongodb_version, pick_managed_mysql_version, pick_managed_postgres_version,
+};
 use crate::models::types::{ToTeraContext, DO};
 use tera::Context as TeraContext;
 
@@ -72,10 +81,153 @@ impl DatabaseType<DO, Container> for MongoDB {
 
 /////////////////////////////////////////////////////////////////
 // MANAGED
-// DO don't support managed databases for now
+impl DatabaseType<DO, Managed> for PostgresSQL {
+    type DatabaseOptions = DatabaseOptions;
+
+    fn short_name() -> &'static str {
+        "Postgres Managed"
+    }
+    fn lib_directory_name() -> &'static str {
+        "postgresql"
+    }
+
+    fn db_type() -> service::DatabaseType {
+        service::DatabaseType::PostgreSQL
+    }
+
+    fn is_tls_required() -> bool {
+        true
+    }
+
+    fn forced_port() -> Option<u16> {
+        Some(25060)
+    }
+}
+
+impl DatabaseType<DO, Managed> for MySQL {
+    type DatabaseOptions = DatabaseOptions;
+
+    fn short_name() -> &'static str {
+        "MySQL Managed"
+    }
+    fn lib_directory_name() -> &'static str {
+        "mysql"
+    }
+
+    fn db_type() -> service::DatabaseType {
+        service::DatabaseType::MySQL
+    }
+
+    fn is_tls_required() -> bool {
+        true
+    }
+
+    fn forced_port() -> Option<u16> {
+        Some(25060)
+    }
+}
+
+impl DatabaseType<DO, Managed> for MongoDB {
+    type DatabaseOptions = DatabaseOptions;
+
+    fn short_name() -> &'static str {
+        "MongoDB Managed"
+    }
+    fn lib_directory_name() -> &'static str {
+        "mongodb"
+    }
+
+    fn db_type() -> service::DatabaseType {
+        service::DatabaseType::MongoDB
+    }
+
+    fn is_tls_required() -> bool {
+        true
+    }
+
+    fn forced_port() -> Option<u16> {
+        Some(27017)
+    }
+}
+
+// Redis is not supported as managed db: DO generates its password, which can't be set to the requested one
+
+impl<M: DatabaseMode, T: DatabaseType<DO, M>> Database<DO, M, T> {
+    fn to_tera_context_for_do_managed(
+        &self,
+        target: &DeploymentTarget,
+        options: &DatabaseOptions,
+        get_version: &dyn Fn(EventDetails) -> Result<ServiceVersionCheckResult, EngineError>,
+    ) -> Result<TeraContext, EngineError>
+    where
+        Database<DO, M, T>: Service,
+    {
+        let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::LoadConfiguration));
+        let kubernetes = target.kubernetes;
+        let environment = target.environment;
+
+        let mut context = default_tera_context(self, kubernetes, environment);
+
+        // we need the kubernetes config file to store tfstates file in kube secrets
+        let kube_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+        context.insert("kubeconfig_path", &kube_config_file_path);
+
+        context.insert("namespace", environment.namespace());
+
+        let version = get_version(event_details)?.matched_version();
+        context.insert("version", &version.to_major_version_string()); // DO only needs the major version
+
+        for (k, v) in kubernetes.cloud_provider().tera_context_environment_variables() {
+            context.insert(k, v);
+        }
+
+        context.insert("kubernetes_cluster_id", kubernetes.id());
+        context.insert("kubernetes_cluster_name", kubernetes.name());
+
+        context.insert("fqdn_id", self.fqdn_id.as_str());
+        context.insert("fqdn", self.fqdn(target, &self.fqdn, M::is_managed()).as_str());
+        context.insert("service_name", self.fqdn_id.as_str());
+        context.insert("database_name", self.sanitized_name().as_str());
+        context.insert("database_db_name", self.name());
+        context.insert("database_login", options.login.as_str());
+        context.insert("database_password", options.password.as_str());
+        context.insert("database_port", &self.private_port());
+        context.insert("database_instance_type", &self.database_instance_type);
+        context.insert("database_fqdn", &options.host.as_str());
+        context.insert("database_id", &self.id());
+        context.insert("tfstate_suffix_name", &get_tfstate_suffix(self));
+        context.insert("tfstate_name", &get_tfstate_name(self));
+
+        context.insert("publicly_accessible", &options.publicly_accessible);
+        context.insert("activate_high_availability", &options.activate_high_availability);
+        self.insert_read_replicas_into_tera_context(target, options, &mut context);
+        if self.context.resource_expiration_in_seconds().is_some() {
+            context.insert("resource_expiration_in_seconds", &self.context.resource_expiration_in_seconds())
+        }
+
+        Ok(context)
+    }
+}
 
 ////////////////////////////////////////////////////////////////////////:
 // POSTGRES SQL
+impl ToTeraContext for Database<DO, Managed, PostgresSQL>
+where
+    PostgresSQL: DatabaseType<DO, Managed>,
+{
+    fn to_tera_context(&self, target: &DeploymentTarget) -> Result<TeraContext, EngineError> {
+        let check_version = |event_details| {
+            check_service_version(
+                pick_managed_postgres_version(self.version.to_string()),
+                self,
+                event_details,
+                self.logger(),
+            )
+        };
+        self.to_tera_context_for_do_managed(target, &self.options, &check_version)
+    }
+}
+
 impl ToTeraContext for Database<DO, Container, PostgresSQL>
 where
     PostgresSQL: DatabaseType<DO, Container>,
@@ -95,6 +247,23 @@ where
 
 ////////////////////////////////////////////////////////////////////////:
 // MySQL
+impl ToTeraContext for Database<DO, Managed, MySQL>
+where
+    MySQL: DatabaseType<DO, Managed>,
+{
+    fn to_tera_context(&self, target: &DeploymentTarget) -> Result<TeraContext, EngineError> {
+        let check_version = |event_details| {
+            check_service_version(
+                pick_managed_mysql_version(self.version.to_string()),
+                self,
+                event_details,
+                self.logger(),
+            )
+        };
+        self.to_tera_context_for_do_managed(target, &self.options, &check_version)
+    }
+}
+
 impl ToTeraContext for Database<DO, Container, MySQL>
 where
     MySQL: DatabaseType<DO, Container>,
@@ -114,6 +283,23 @@ where
 
 ////////////////////////////////////////////////////////////////////////:
 // MongoDB
+impl ToTeraContext for Database<DO, Managed, MongoDB>
+where
+    MongoDB: DatabaseType<DO, Managed>,
+{
+    fn to_tera_context(&self, target: &DeploymentTarget) -> Result<TeraContext, EngineError> {
+        let check_version = |event_details| {
+            check_service_version(
+                pick_managed_mongodb_version(self.version.to_string()),
+                self,
+                event_details,
+                self.logger(),
+            )
+        };
+        self.to_tera_context_for_do_managed(target, &self.options, &check_version)
+    }
+}
+
 impl ToTeraContext for Database<DO, Container, MongoDB>
 where
     MongoDB: DatabaseType<DO, Container>,
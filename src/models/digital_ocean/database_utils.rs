@@ -0,0 +1,65 @@
+use crate::errors::CommandError;
+use crate::models::database_utils::get_supported_version_to_use;
+use std::collections::HashMap;
+
+pub(super) fn pick_managed_postgres_version(requested_version: String) -> Result<String, CommandError> {
+    // DigitalOcean supported postgres versions
+    // https://docs.digitalocean.com/products/databases/postgresql/details/supported-versions/
+    let mut supported_postgres_versions = HashMap::new();
+
+    supported_postgres_versions.insert("12".to_string(), "12".to_string());
+    supported_postgres_versions.insert("12.0".to_string(), "12".to_string());
+    supported_postgres_versions.insert("13".to_string(), "13".to_string());
+    supported_postgres_versions.insert("13.0".to_string(), "13".to_string());
+    supported_postgres_versions.insert("14".to_string(), "14".to_string());
+    supported_postgres_versions.insert("14.0".to_string(), "14".to_string());
+
+    get_supported_version_to_use("DO postgres", supported_postgres_versions, requested_version)
+}
+
+pub(super) fn pick_managed_mysql_version(requested_version: String) -> Result<String, CommandError> {
+    // DigitalOcean supported MySQL versions
+    // https://docs.digitalocean.com/products/databases/mysql/details/supported-versions/
+    let mut supported_mysql_versions = HashMap::new();
+
+    supported_mysql_versions.insert("8".to_string(), "8".to_string());
+    supported_mysql_versions.insert("8.0".to_string(), "8".to_string());
+
+    get_supported_version_to_use("DO MySQL", supported_mysql_versions, requested_version)
+}
+
+pub(super) fn pick_managed_mongodb_version(requested_version: String) -> Result<String, CommandError> {
+    // DigitalOcean supported MongoDB versions
+    // https://docs.digitalocean.com/products/databases/mongodb/details/supported-versions/
+    let mut supported_mongodb_versions = HashMap::new();
+
+    supported_mongodb_versions.insert("4".to_string(), "4".to_string());
+    supported_mongodb_versions.insert("4.4".to_string(), "4".to_string());
+    supported_mongodb_versions.insert("5".to_string(), "5".to_string());
+    supported_mongodb_versions.insert("5.0".to_string(), "5".to_string());
+
+    get_supported_version_to_use("DO MongoDB", supported_mongodb_versions, requested_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::ErrorMessageVerbosity::SafeOnly;
+    use crate::models::digital_ocean::database_utils::{
+        pick_managed_mongodb_version, pick_managed_mysql_version, pick_managed_postgres_version,
+    };
+
+    #[test]
+    fn check_managed_versions() {
+        assert_eq!(pick_managed_postgres_version("13".to_string()).unwrap(), "13");
+        assert_eq!(pick_managed_postgres_version("14.0".to_string()).unwrap(), "14");
+        assert_eq!(
+            pick_managed_postgres_version("10".to_string())
+                .unwrap_err()
+                .message(SafeOnly)
+                .as_str(),
+            "DO postgres 10 version is not supported"
+        );
+        assert_eq!(pick_managed_mysql_version("8.0".to_string()).unwrap(), "8");
+        assert_eq!(pick_managed_mongodb_version("4.4".to_string()).unwrap(), "4");
+    }
+}
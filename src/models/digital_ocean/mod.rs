@@ -1,5 +1,6 @@
 mod application;
 mod database;
+mod database_utils;
 mod router;
 
 use crate::errors::CommandError;
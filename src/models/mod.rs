@@ -1,6 +1,8 @@
 pub mod application;
+pub(crate) mod application_release;
 pub mod aws;
 pub mod database;
+pub mod database_credentials;
 pub(crate) mod database_utils;
 pub mod digital_ocean;
 pub mod router;
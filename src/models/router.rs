@@ -1,26 +1,50 @@
+use crate::change_set::ServiceChangeSet;
 use crate::cloud_provider::helm::ChartInfo;
-use crate::cloud_provider::models::{CustomDomain, CustomDomainDataTemplate, Route, RouteDataTemplate};
+use crate::cloud_provider::models::{
+    CustomDomain, CustomDomainCertificate, CustomDomainDataTemplate, IngressDataTemplate, IngressTlsDataTemplate,
+    PermanentRedirectDataTemplate, Route, RouteDataTemplate, RouterAccessControl,
+};
 use crate::cloud_provider::service::{
-    default_tera_context, delete_stateless_service, deploy_stateless_service_error, send_progress_on_long_task, Action,
-    Create, Delete, Helm, Pause, RouterService, Service, ServiceType, StatelessService,
+    default_tera_context, delete_stateless_service, deploy_stateless_service_error, plan_helm_service,
+    send_progress_on_long_task, Action, Create, Delete, Helm, Pause, RouterService, Service, ServiceType,
+    StatelessService,
 };
-use crate::cloud_provider::utilities::{check_cname_for, print_action, sanitize_name};
+use crate::cloud_provider::utilities::{check_cname_for, print_action, resolve_cname, sanitize_name};
 use crate::cloud_provider::DeploymentTarget;
 use crate::cmd::helm;
 use crate::cmd::helm::to_engine_error;
+use crate::cmd::kubectl::{
+    kubectl_create_tls_secret, kubectl_delete_secret, kubectl_exec_get_certificate,
+    kubectl_exec_get_certificate_orders, kubectl_exec_get_secret_data,
+};
+use crate::cmd::structs::{CertManagerCertificate, CertManagerOrder};
+use crate::dns_provider::errors::DnsProviderError;
+use crate::dns_provider::{DnsRecord, DnsRecordType};
 use crate::errors::EngineError;
-use crate::events::{EngineEvent, EnvironmentStep, EventMessage, Stage, ToTransmitter, Transmitter};
-use crate::io_models::{Context, Listen, Listener, Listeners};
+use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, Stage, ToTransmitter, Transmitter};
+use crate::io_models::{
+    Context, CustomDomainStatus, CustomDomainVerification, Listen, Listener, Listeners, ListenersHelper, ProgressInfo,
+    ProgressLevel, RouteProtocol, RouterAdvancedSettings, RouterVisibility,
+};
 use crate::logger::Logger;
 use crate::models::types::CloudProvider;
 use crate::models::types::ToTeraContext;
 use crate::utilities::to_short_id;
+use chrono::{DateTime, Duration, Utc};
 use function_name::named;
 use std::borrow::Borrow;
 use std::marker::PhantomData;
+use std::thread;
+use std::time::Instant;
 use tera::Context as TeraContext;
 use uuid::Uuid;
 
+const DNS_RECORDS_VERIFICATION_RETRIES: u32 = 3;
+const DNS_RECORDS_VERIFICATION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+const CUSTOM_DOMAINS_STATUS_RETRIES: u32 = 3;
+const CUSTOM_DOMAINS_STATUS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+const CANCELLATION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
 #[derive(thiserror::Error, Debug)]
 pub enum RouterError {
     #[error("Router invalid configuration: {0}")]
@@ -38,6 +62,9 @@ pub struct Router<T: CloudProvider> {
     pub(crate) custom_domains: Vec<CustomDomain>,
     pub(crate) sticky_sessions_enabled: bool,
     pub(crate) routes: Vec<Route>,
+    pub(crate) access_control: RouterAccessControl,
+    pub(crate) advanced_settings: RouterAdvancedSettings,
+    pub(crate) visibility: RouterVisibility,
     pub(crate) listeners: Listeners,
     pub(crate) logger: Box<dyn Logger>,
     pub(crate) _extra_settings: T::RouterExtraSettings,
@@ -53,10 +80,23 @@ impl<T: CloudProvider> Router<T> {
         custom_domains: Vec<CustomDomain>,
         routes: Vec<Route>,
         sticky_sessions_enabled: bool,
+        access_control: RouterAccessControl,
+        advanced_settings: RouterAdvancedSettings,
+        visibility: RouterVisibility,
         extra_settings: T::RouterExtraSettings,
         listeners: Listeners,
         logger: Box<dyn Logger>,
     ) -> Result<Self, RouterError> {
+        // Let's Encrypt can't reach internal routers to validate the certificates it issues
+        if visibility == RouterVisibility::Internal {
+            if let Some(custom_domain) = custom_domains.iter().find(|cd| cd.certificate.is_none()) {
+                return Err(RouterError::InvalidConfig(format!(
+                    "custom domain {} of an internal router must bring its certificate",
+                    custom_domain.domain
+                )));
+            }
+        }
+
         Ok(Self {
             _marker: PhantomData,
             context,
@@ -68,6 +108,9 @@ impl<T: CloudProvider> Router<T> {
             custom_domains,
             sticky_sessions_enabled,
             routes,
+            access_control,
+            advanced_settings,
+            visibility,
             listeners,
             logger,
             _extra_settings: extra_settings,
@@ -78,6 +121,293 @@ impl<T: CloudProvider> Router<T> {
         Some(format!("routerId={}", self.id))
     }
 
+    /// Service of the ingress controller serving the router, deployed with the cluster
+    fn ingress_controller_service_name(&self) -> &str {
+        match self.visibility {
+            RouterVisibility::Public => "nginx-ingress-ingress-nginx-controller",
+            RouterVisibility::Internal => "nginx-ingress-internal-ingress-nginx-controller",
+        }
+    }
+
+    /// Secret holding the certificate of a custom domain, `None` when cert-manager requests it
+    fn custom_domain_tls_secret_name(&self, custom_domain: &CustomDomain) -> Option<String> {
+        match &custom_domain.certificate {
+            None => None,
+            Some(CustomDomainCertificate::Provided { .. }) => Some(format!(
+                "router-tls-{}-{}",
+                self.id,
+                crate::crypto::to_sha1_truncate_16(&custom_domain.domain)
+            )),
+            Some(CustomDomainCertificate::ExistingSecret { secret_name }) => Some(secret_name.clone()),
+        }
+    }
+
+    /// TLS of the hosts served with a certificate requested by cert-manager, then with a certificate brought by the user
+    fn tls_data_templates(&self) -> (Vec<String>, Vec<IngressTlsDataTemplate>, Vec<IngressTlsDataTemplate>) {
+        let mut acme_hosts = vec![self.default_domain.clone()];
+        let mut custom_certificate_tls: Vec<IngressTlsDataTemplate> = vec![];
+        for custom_domain in &self.custom_domains {
+            match self.custom_domain_tls_secret_name(custom_domain) {
+                None => acme_hosts.push(custom_domain.domain.clone()),
+                // a wildcard certificate secret can be shared by several domains
+                Some(secret_name) => match custom_certificate_tls
+                    .iter_mut()
+                    .find(|tls| tls.secret_name == secret_name)
+                {
+                    Some(tls) => tls.hosts.push(custom_domain.domain.clone()),
+                    None => custom_certificate_tls.push(IngressTlsDataTemplate {
+                        secret_name,
+                        hosts: vec![custom_domain.domain.clone()],
+                    }),
+                },
+            }
+        }
+
+        // the default domain is served with the wildcard certificate of the ingress controller
+        let acme_tls = match acme_hosts.len() > 1 {
+            true => vec![IngressTlsDataTemplate {
+                secret_name: format!("router-tls-{}", self.id),
+                hosts: acme_hosts[1..].to_vec(),
+            }],
+            false => vec![],
+        };
+
+        (acme_hosts, acme_tls, custom_certificate_tls)
+    }
+
+    /// Creates or updates the secrets of the certificates brought by the user and warns about the expiring ones
+    fn deploy_custom_domain_certificates(
+        &self,
+        target: &DeploymentTarget,
+        event_details: &EventDetails,
+    ) -> Result<(), EngineError>
+    where
+        Self: Service,
+    {
+        let kubernetes = target.kubernetes;
+        let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+        let namespace = target.environment.namespace();
+        let envs = kubernetes.cloud_provider().credentials_environment_variables();
+
+        for custom_domain in &self.custom_domains {
+            let secret_name = match self.custom_domain_tls_secret_name(custom_domain) {
+                Some(secret_name) => secret_name,
+                None => continue,
+            };
+
+            let expiration_date = match &custom_domain.certificate {
+                Some(CustomDomainCertificate::Provided {
+                    certificate,
+                    private_key,
+                    expiration_date,
+                }) => {
+                    kubectl_create_tls_secret(
+                        &kubernetes_config_file_path,
+                        envs.clone(),
+                        namespace,
+                        &secret_name,
+                        certificate,
+                        private_key,
+                        &self.workspace_directory(),
+                    )
+                    .map_err(|e| {
+                        EngineError::new_k8s_cannot_create_tls_secret(
+                            event_details.clone(),
+                            secret_name.clone(),
+                            custom_domain.domain.clone(),
+                            e,
+                        )
+                    })?;
+                    Some(*expiration_date)
+                }
+                _ => match kubectl_exec_get_secret_data(
+                    &kubernetes_config_file_path,
+                    namespace,
+                    &secret_name,
+                    envs.clone(),
+                ) {
+                    Ok(Some(data)) => data
+                        .get("tls.crt")
+                        .and_then(|certificate| base64::decode(certificate).ok())
+                        .and_then(|certificate| {
+                            crate::crypto::to_certificate_expiration_date(&String::from_utf8_lossy(&certificate)).ok()
+                        }),
+                    Ok(None) => {
+                        self.log_warning(
+                            event_details,
+                            format!(
+                                "TLS secret `{}` of domain `{}` does not exist, the default certificate will be served until it is created",
+                                secret_name, custom_domain.domain
+                            ),
+                        );
+                        continue;
+                    }
+                    Err(_) => None,
+                },
+            };
+
+            match expiration_date {
+                Some(expiration_date) => {
+                    if let Some(warning) =
+                        certificate_expiration_warning(&custom_domain.domain, expiration_date, Utc::now())
+                    {
+                        self.log_warning(event_details, warning);
+                    }
+                }
+                None => self.log_warning(
+                    event_details,
+                    format!(
+                        "Cannot read the expiration date of the certificate of domain `{}` from TLS secret `{}`",
+                        custom_domain.domain, secret_name
+                    ),
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends the verification status of every custom domain, waiting a bit for the certificates being issued.
+    /// The status is reported as is once the wait is over or as soon as the deployment is canceled.
+    fn report_custom_domains_status(&self, target: &DeploymentTarget, event_details: &EventDetails)
+    where
+        Self: Service,
+    {
+        let mut attempts = 0;
+        let custom_domains_status = loop {
+            match self.custom_domains_status(target) {
+                Ok(custom_domains_status)
+                    if attempts < CUSTOM_DOMAINS_STATUS_RETRIES
+                        && custom_domains_status.iter().any(|custom_domain| {
+                            matches!(custom_domain.verification, CustomDomainVerification::PendingAcme { .. })
+                        }) =>
+                {
+                    attempts += 1;
+                    if !wait_unless_canceled(target, CUSTOM_DOMAINS_STATUS_INTERVAL) {
+                        break Ok(custom_domains_status);
+                    }
+                }
+                result => break result,
+            }
+        };
+
+        let custom_domains_status = match custom_domains_status {
+            Ok(custom_domains_status) => custom_domains_status,
+            Err(err) => {
+                // domains are served anyway, their status will be known on next deployment
+                self.logger.log(EngineEvent::Warning(
+                    event_details.clone(),
+                    EventMessage::new_from_safe(err.user_log_message().to_string()),
+                ));
+                return;
+            }
+        };
+
+        let listeners_helper = ListenersHelper::new(self.listeners());
+        for custom_domain in custom_domains_status {
+            let (level, message) = custom_domain_status_message(&custom_domain);
+            listeners_helper.deployment_in_progress(
+                ProgressInfo::new(self.progress_scope(), level, Some(message), self.context.execution_id())
+                    .with_custom_domain(custom_domain),
+            );
+        }
+    }
+
+    /// external-dns registers the default domain asynchronously, once the Ingress has an address.
+    /// The verification is informative only, it gives up quickly and as soon as the deployment is canceled.
+    fn verify_dns_records(&self, target: &DeploymentTarget, event_details: &EventDetails) {
+        let dns_provider = target.kubernetes.dns_provider();
+        let mut attempts = 0;
+        let records = loop {
+            match dns_provider.get_records(&self.default_domain, None) {
+                Ok(records)
+                    if attempts < DNS_RECORDS_VERIFICATION_RETRIES && !records.iter().any(is_address_record) =>
+                {
+                    attempts += 1;
+                    if !wait_unless_canceled(target, DNS_RECORDS_VERIFICATION_INTERVAL) {
+                        return;
+                    }
+                }
+                result => break result,
+            }
+        };
+
+        match records {
+            Ok(records) => match records.iter().find(|record| is_address_record(record)) {
+                Some(record) => self.logger.log(EngineEvent::Info(
+                    event_details.clone(),
+                    EventMessage::new_from_safe(format!(
+                        "Domain `{}` points to `{}` in {}",
+                        self.default_domain,
+                        record.value,
+                        dns_provider.name_with_id()
+                    )),
+                )),
+                None => self.log_warning(
+                    event_details,
+                    format!(
+                        "No DNS record of domain `{}` found in {} yet, it will be reachable once external-dns creates it",
+                        self.default_domain,
+                        dns_provider.name_with_id()
+                    ),
+                ),
+            },
+            // records of this provider are only managed by external-dns
+            Err(DnsProviderError::RecordsManagementNotSupported { .. }) => {}
+            Err(err) => self.log_dns_provider_error(event_details, err),
+        }
+    }
+
+    /// external-dns doesn't get the chance to remove the records of the default domain when the cluster goes away
+    /// with the environment, only the records it created from this cluster are deleted
+    fn delete_stale_dns_records(&self, target: &DeploymentTarget, event_details: &EventDetails) {
+        let dns_provider = target.kubernetes.dns_provider();
+        let cluster_id = target.kubernetes.id();
+        let ownership_record_name = format!("qvy-{}-{}", cluster_id, self.default_domain);
+        let records = dns_provider
+            .get_records(&self.default_domain, None)
+            .and_then(|records| {
+                Ok((
+                    records,
+                    dns_provider.get_records(&ownership_record_name, Some(DnsRecordType::TXT))?,
+                ))
+            });
+
+        let stale_records = match records {
+            Ok((records, ownership_records)) => stale_dns_records(cluster_id, records, ownership_records),
+            Err(DnsProviderError::RecordsManagementNotSupported { .. }) => return,
+            Err(err) => return self.log_dns_provider_error(event_details, err),
+        };
+        for record in stale_records {
+            if let Err(err) = dns_provider.delete_record(&record) {
+                return self.log_dns_provider_error(event_details, err);
+            }
+        }
+    }
+
+    /// DNS records are managed by external-dns, the router is served anyway
+    fn log_dns_provider_error(&self, event_details: &EventDetails, err: DnsProviderError) {
+        let err = EngineError::new_dns_provider_cannot_manage_records(
+            event_details.clone(),
+            self.default_domain.clone(),
+            err,
+        );
+        self.logger.log(EngineEvent::Warning(
+            event_details.clone(),
+            EventMessage::new(
+                err.user_log_message().to_string(),
+                err.underlying_error().map(|raw_error| raw_error.message_safe()),
+            ),
+        ));
+    }
+
+    fn log_warning(&self, event_details: &EventDetails, message: String) {
+        self.logger.log(EngineEvent::Warning(
+            event_details.clone(),
+            EventMessage::new_from_safe(message),
+        ));
+    }
+
     pub(crate) fn default_tera_context(&self, target: &DeploymentTarget) -> Result<TeraContext, EngineError>
     where
         Self: Service,
@@ -87,12 +417,6 @@ impl<T: CloudProvider> Router<T> {
         let environment = target.environment;
         let mut context = default_tera_context(self, kubernetes, environment);
 
-        let applications = environment
-            .stateless_services()
-            .into_iter()
-            .filter(|x| x.service_type() == ServiceType::Application)
-            .collect::<Vec<_>>();
-
         let custom_domain_data_templates = self
             .custom_domains
             .iter()
@@ -106,23 +430,49 @@ impl<T: CloudProvider> Router<T> {
             })
             .collect::<Vec<_>>();
 
-        let route_data_templates = self
-            .routes
-            .iter()
-            .filter_map(|r| {
-                match applications
-                    .iter()
-                    .find(|app| app.name() == r.application_name.as_str())
-                {
-                    Some(application) => application.private_port().map(|private_port| RouteDataTemplate {
-                        path: r.path.clone(),
-                        application_name: application.sanitized_name(),
-                        application_port: private_port,
-                    }),
-                    _ => None,
+        let mut route_data_templates = Vec::with_capacity(self.routes.len());
+        for route in &self.routes {
+            let application = match environment
+                .applications
+                .iter()
+                .find(|app| app.name() == route.application_name.as_str())
+            {
+                Some(application) => application,
+                None => continue,
+            };
+
+            let application_port = match (route.application_port(application.ports()), &route.port) {
+                (Some(application_port), _) => application_port,
+                (None, None) => continue,
+                (None, Some(port)) => {
+                    return Err(EngineError::new_router_route_port_not_found(
+                        event_details,
+                        route.path.clone(),
+                        application.name().to_string(),
+                        port.clone(),
+                    ))
                 }
-            })
-            .collect::<Vec<_>>();
+            };
+
+            route_data_templates.push((
+                route,
+                RouteDataTemplate {
+                    path: route.path.clone(),
+                    application_name: application.sanitized_name(),
+                    application_port,
+                },
+            ));
+        }
+        let (acme_hosts, acme_tls, custom_certificate_tls) = self.tls_data_templates();
+        let has_acme_custom_domains = !acme_tls.is_empty();
+        let ingress_data_templates = ingresses_data_templates(
+            &self.sanitized_name(),
+            &self.long_id.to_string(),
+            &self.access_control,
+            route_data_templates,
+            (acme_hosts, acme_tls),
+            custom_certificate_tls,
+        );
 
         // autoscaler
         context.insert("nginx_enable_horizontal_autoscaler", "false");
@@ -140,7 +490,7 @@ impl<T: CloudProvider> Router<T> {
         match crate::cmd::kubectl::kubectl_exec_get_external_ingress_hostname(
             kubernetes_config_file_path,
             "nginx-ingress",
-            "nginx-ingress-ingress-nginx-controller",
+            self.ingress_controller_service_name(),
             kubernetes.cloud_provider().credentials_environment_variables(),
         ) {
             Ok(external_ingress_hostname_default) => match external_ingress_hostname_default {
@@ -172,7 +522,58 @@ impl<T: CloudProvider> Router<T> {
         context.insert("router_default_domain", self.default_domain.as_str());
         context.insert("router_default_domain_hash", router_default_domain_hash.as_str());
         context.insert("custom_domains", &custom_domain_data_templates);
-        context.insert("routes", &route_data_templates);
+        context.insert("has_acme_custom_domains", &has_acme_custom_domains);
+        context.insert("ingresses", &ingress_data_templates);
+        context.insert("ingress_class", self.ingress_class());
+        // internal routers are not registered in the public zone
+        context.insert("publish_dns", &(self.visibility == RouterVisibility::Public));
+
+        // advanced settings
+        let settings = &self.advanced_settings;
+        context.insert("cors_enabled", &settings.network_ingress_enable_cors);
+        context.insert("cors_allow_origin", &settings.network_ingress_cors_allow_origin);
+        context.insert("cors_allow_methods", &settings.network_ingress_cors_allow_methods);
+        context.insert("cors_allow_headers", &settings.network_ingress_cors_allow_headers);
+        let response_headers = settings
+            .network_ingress_custom_headers
+            .iter()
+            .map(|(name, value)| format!("more_set_headers \"{}: {}\"", name, value))
+            .collect::<Vec<_>>();
+        context.insert("response_headers", &response_headers);
+        context.insert("rewrite_target", &settings.network_ingress_rewrite_target);
+        context.insert("ssl_redirect", &settings.network_ingress_ssl_redirect);
+        let permanent_redirects = settings
+            .network_ingress_permanent_redirects
+            .iter()
+            .map(|(domain, url)| PermanentRedirectDataTemplate {
+                domain: domain.clone(),
+                // the request uri is appended to the url
+                url: url.trim_end_matches('/').to_string(),
+            })
+            .collect::<Vec<_>>();
+        context.insert("permanent_redirects", &permanent_redirects);
+        context.insert("proxy_body_size_mb", &settings.network_ingress_proxy_body_size_mb);
+        context.insert(
+            "proxy_connect_timeout_seconds",
+            &settings.network_ingress_proxy_connect_timeout_seconds,
+        );
+        context.insert(
+            "proxy_send_timeout_seconds",
+            &settings.network_ingress_proxy_send_timeout_seconds,
+        );
+        context.insert(
+            "proxy_read_timeout_seconds",
+            &settings.network_ingress_proxy_read_timeout_seconds,
+        );
+        // nginx closes websocket connections on which nothing was sent nor received within these timeouts
+        context.insert(
+            "websocket_proxy_send_timeout_seconds",
+            &settings.network_ingress_proxy_send_timeout_seconds.max(3600),
+        );
+        context.insert(
+            "websocket_proxy_read_timeout_seconds",
+            &settings.network_ingress_proxy_read_timeout_seconds.max(3600),
+        );
         context.insert("spec_acme_email", "tls@qovery.com"); // TODO CHANGE ME
         context.insert("metadata_annotations_cert_manager_cluster_issuer", "letsencrypt-qovery");
 
@@ -189,6 +590,262 @@ impl<T: CloudProvider> Router<T> {
     }
 }
 
+/// Routes replacing the router access control get their own Ingress, the other ones share the router Ingress of
+/// their protocol, the HTTP one being the main Ingress. Hosts with a certificate brought by the user are served by twin Ingresses, cert-manager would otherwise
+/// replace their certificate with the one it requests for the Ingress annotated with the issuer.
+fn ingresses_data_templates(
+    router_sanitized_name: &str,
+    router_long_id: &str,
+    router_access_control: &RouterAccessControl,
+    routes: Vec<(&Route, RouteDataTemplate)>,
+    (acme_hosts, acme_tls): (Vec<String>, Vec<IngressTlsDataTemplate>),
+    custom_certificate_tls: Vec<IngressTlsDataTemplate>,
+) -> Vec<IngressDataTemplate> {
+    let mut router_routes: Vec<(RouteProtocol, Vec<RouteDataTemplate>)> = vec![];
+    let mut groups = vec![];
+    for (route, route_data_template) in routes {
+        match &route.access_control {
+            None => match router_routes
+                .iter_mut()
+                .find(|(protocol, _)| *protocol == route.protocol)
+            {
+                Some((_, routes)) => routes.push(route_data_template),
+                None => router_routes.push((route.protocol, vec![route_data_template])),
+            },
+            // named after the path, an Ingress must not move to another route when routes are reordered
+            Some(access_control) => groups.push((
+                format!(
+                    "{}-route-{}",
+                    router_sanitized_name,
+                    crate::crypto::to_sha1_truncate_16(&route.path)
+                ),
+                access_control,
+                format!("{}/{}", router_long_id, route.path),
+                route.protocol,
+                vec![route_data_template],
+            )),
+        }
+    }
+    router_routes.sort_by_key(|(protocol, _)| *protocol != RouteProtocol::Http);
+    for (position, (protocol, routes)) in router_routes.into_iter().enumerate() {
+        let name = match protocol {
+            RouteProtocol::Http => router_sanitized_name.to_string(),
+            RouteProtocol::Https => format!("{}-https", router_sanitized_name),
+            RouteProtocol::Grpc => format!("{}-grpc", router_sanitized_name),
+            RouteProtocol::WebSocket => format!("{}-websocket", router_sanitized_name),
+        };
+        groups.insert(
+            position,
+            (name, router_access_control, router_long_id.to_string(), protocol, routes),
+        );
+    }
+
+    let custom_certificate_hosts = custom_certificate_tls
+        .iter()
+        .flat_map(|tls| tls.hosts.clone())
+        .collect::<Vec<_>>();
+    let mut ingresses = vec![];
+    for (idx, (name, access_control, salt_seed, protocol, routes)) in groups.into_iter().enumerate() {
+        let backend_protocol = match protocol {
+            RouteProtocol::Https => Some("HTTPS".to_string()),
+            RouteProtocol::Grpc => Some("GRPC".to_string()),
+            RouteProtocol::Http | RouteProtocol::WebSocket => None,
+        };
+        let is_websocket = protocol == RouteProtocol::WebSocket;
+        let twin = match custom_certificate_hosts.is_empty() {
+            true => None,
+            false => {
+                let twin_name = format!("{}-custom-certificate", name);
+                Some(IngressDataTemplate {
+                    access_control: access_control.to_data_template(format!("{}-basic-auth", twin_name), &salt_seed),
+                    name: twin_name,
+                    is_main: false,
+                    has_server_snippet: idx == 0,
+                    hosts: custom_certificate_hosts.clone(),
+                    tls: custom_certificate_tls.clone(),
+                    routes: routes.clone(),
+                    backend_protocol: backend_protocol.clone(),
+                    is_websocket,
+                })
+            }
+        };
+
+        ingresses.push(IngressDataTemplate {
+            access_control: access_control.to_data_template(format!("{}-basic-auth", name), &salt_seed),
+            name,
+            is_main: idx == 0,
+            has_server_snippet: idx == 0,
+            hosts: acme_hosts.clone(),
+            tls: acme_tls.clone(),
+            routes,
+            backend_protocol,
+            is_websocket,
+        });
+        ingresses.extend(twin);
+    }
+
+    ingresses
+}
+
+/// Returns false as soon as the deployment is canceled instead of waiting for `duration`
+fn wait_unless_canceled(target: &DeploymentTarget, duration: std::time::Duration) -> bool {
+    let waiting_started_at = Instant::now();
+    while waiting_started_at.elapsed() < duration {
+        if target.environment.is_canceled() {
+            return false;
+        }
+        thread::sleep(CANCELLATION_CHECK_INTERVAL);
+    }
+
+    true
+}
+
+fn is_address_record(record: &DnsRecord) -> bool {
+    matches!(
+        record.record_type,
+        DnsRecordType::A | DnsRecordType::AAAA | DnsRecordType::CNAME
+    )
+}
+
+/// Records of a domain registered by the external-dns of the given cluster, which owns them through a TXT record
+fn stale_dns_records(cluster_id: &str, records: Vec<DnsRecord>, ownership_records: Vec<DnsRecord>) -> Vec<DnsRecord> {
+    let owner = format!("external-dns/owner={}", cluster_id);
+    let owned_records = ownership_records
+        .into_iter()
+        .filter(|record| {
+            record.record_type == DnsRecordType::TXT
+                && record.value.split(',').any(|label| label == "heritage=external-dns")
+                && record.value.split(',').any(|label| label == owner)
+        })
+        .collect::<Vec<_>>();
+    if owned_records.is_empty() {
+        return vec![];
+    }
+
+    records
+        .into_iter()
+        .filter(is_address_record)
+        .chain(owned_records)
+        .collect()
+}
+
+/// Warning for certificates which expired or expire within 30 days, they are renewed by their owner
+fn certificate_expiration_warning(domain: &str, expiration_date: DateTime<Utc>, now: DateTime<Utc>) -> Option<String> {
+    let remaining = expiration_date - now;
+    if remaining < Duration::zero() {
+        Some(format!(
+            "Certificate of domain `{}` expired on {}, browsers will refuse to connect until it is renewed",
+            domain,
+            expiration_date.format("%Y-%m-%d")
+        ))
+    } else if remaining < Duration::days(30) {
+        Some(format!(
+            "Certificate of domain `{}` expires on {}, in {} days, it has to be renewed",
+            domain,
+            expiration_date.format("%Y-%m-%d"),
+            remaining.num_days()
+        ))
+    } else {
+        None
+    }
+}
+
+/// A certificate being issued proves the domain reaches the router, even behind a CDN hiding the CNAME
+fn custom_domain_verification(
+    custom_domain: &CustomDomain,
+    current_target: Option<String>,
+    certificate: Option<&CertManagerCertificate>,
+    orders: &[CertManagerOrder],
+    custom_certificate_secret_exists: bool,
+) -> CustomDomainVerification {
+    let is_dns_ready = current_target.as_deref().map(|target| target.trim_end_matches('.'))
+        == Some(custom_domain.target_domain.trim_end_matches('.'));
+    let pending_dns = CustomDomainVerification::PendingDns {
+        expected_target: custom_domain.target_domain.clone(),
+        current_target,
+    };
+
+    if custom_domain.certificate.is_some() {
+        return match (is_dns_ready, custom_certificate_secret_exists) {
+            (false, _) => pending_dns,
+            (true, true) => CustomDomainVerification::Issued,
+            (true, false) => CustomDomainVerification::Failed {
+                reason: "the TLS secret holding its certificate does not exist".to_string(),
+            },
+        };
+    }
+
+    let domain = &custom_domain.domain;
+    let ready_condition = certificate
+        .filter(|certificate| certificate.spec.dns_names.contains(domain))
+        .and_then(|certificate| {
+            certificate
+                .status
+                .conditions
+                .iter()
+                .find(|condition| condition.condition_type == "Ready")
+        });
+    if ready_condition.map(|condition| condition.status == "True") == Some(true) {
+        return CustomDomainVerification::Issued;
+    }
+    if !is_dns_ready {
+        return pending_dns;
+    }
+
+    // orders are sorted by creation, the last one is the current attempt
+    let order_state = orders
+        .iter()
+        .rev()
+        .find(|order| order.spec.dns_names.contains(domain))
+        .and_then(|order| order.status.state.as_deref().map(|state| (state, &order.status.reason)));
+    match order_state {
+        Some((state @ ("invalid" | "errored" | "expired"), reason)) => CustomDomainVerification::Failed {
+            reason: reason
+                .clone()
+                .unwrap_or_else(|| format!("certificate order is {}", state)),
+        },
+        _ => CustomDomainVerification::PendingAcme {
+            reason: ready_condition.and_then(|condition| condition.message.clone()),
+        },
+    }
+}
+
+fn custom_domain_status_message(custom_domain: &CustomDomainStatus) -> (ProgressLevel, String) {
+    let domain = &custom_domain.domain;
+    match &custom_domain.verification {
+        CustomDomainVerification::PendingDns {
+            expected_target,
+            current_target,
+        } => (
+            ProgressLevel::Warn,
+            match current_target {
+                Some(current_target) => format!(
+                    "Domain `{}` is a CNAME targeting `{}` instead of `{}`. Its certificate can't be issued until it is fixed, unless you are using a CDN",
+                    domain, current_target, expected_target
+                ),
+                None => format!(
+                    "Domain `{}` has to be a CNAME targeting `{}` for its certificate to be issued",
+                    domain, expected_target
+                ),
+            },
+        ),
+        CustomDomainVerification::PendingAcme { reason } => (
+            ProgressLevel::Info,
+            match reason {
+                Some(reason) => format!("Certificate of domain `{}` is being issued: {}", domain, reason),
+                None => format!("Certificate of domain `{}` is being issued", domain),
+            },
+        ),
+        CustomDomainVerification::Issued => {
+            (ProgressLevel::Info, format!("Domain `{}` is served with its certificate", domain))
+        }
+        CustomDomainVerification::Failed { reason } => (
+            ProgressLevel::Warn,
+            format!("Certificate of domain `{}` cannot be issued: {}", domain, reason),
+        ),
+    }
+}
+
 impl<T: CloudProvider> ToTransmitter for Router<T> {
     fn to_transmitter(&self) -> Transmitter {
         Transmitter::Router(self.id.to_string(), self.name.to_string())
@@ -350,6 +1007,9 @@ where
             ));
         }
 
+        // the certificates have to be served as soon as the Ingresses exist
+        self.deploy_custom_domain_certificates(target, &event_details)?;
+
         // do exec helm upgrade and return the last deployment status
         let helm = helm::Helm::new(
             &kubernetes_config_file_path,
@@ -371,7 +1031,17 @@ where
         );
 
         helm.upgrade(&chart, &[])
-            .map_err(|e| EngineError::new_helm_error(event_details.clone(), e))
+            .map_err(|e| EngineError::new_helm_error(event_details.clone(), e))?;
+
+        if self.has_custom_domains() {
+            self.report_custom_domains_status(target, &event_details);
+        }
+
+        if self.visibility == RouterVisibility::Public {
+            self.verify_dns_records(target, &event_details);
+        }
+
+        Ok(())
     }
 
     #[named]
@@ -503,7 +1173,28 @@ where
 
         send_progress_on_long_task(self, Action::Delete, || {
             delete_stateless_service(target, self, event_details.clone())
-        })
+        })?;
+
+        // secrets created from the certificates brought by the user are not part of the helm release
+        let kubernetes_config_file_path = target.kubernetes.get_kubeconfig_file_path()?;
+        for custom_domain in &self.custom_domains {
+            if let (Some(CustomDomainCertificate::Provided { .. }), Some(secret_name)) =
+                (&custom_domain.certificate, self.custom_domain_tls_secret_name(custom_domain))
+            {
+                let _ = kubectl_delete_secret(
+                    &kubernetes_config_file_path,
+                    target.kubernetes.cloud_provider().credentials_environment_variables(),
+                    Some(target.environment.namespace()),
+                    secret_name,
+                );
+            }
+        }
+
+        if self.visibility == RouterVisibility::Public {
+            self.delete_stale_dns_records(target, &event_details);
+        }
+
+        Ok(())
     }
 
     #[named]
@@ -546,6 +1237,16 @@ where
     fn as_stateless_service(&self) -> &dyn StatelessService {
         self
     }
+
+    fn plan_action(&self, target: &DeploymentTarget) -> Result<ServiceChangeSet, EngineError> {
+        let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
+        let chart_dir = format!(
+            "{}/{}/charts/q-ingress-tls",
+            self.context.lib_root_dir(),
+            T::lib_directory_name()
+        );
+        plan_helm_service(target, self, &[chart_dir], event_details)
+    }
 }
 
 impl<T: CloudProvider> RouterService for Router<T>
@@ -566,4 +1267,375 @@ where
     fn has_custom_domains(&self) -> bool {
         !self.custom_domains.is_empty()
     }
+
+    fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    fn ingress_class(&self) -> &str {
+        match self.visibility {
+            RouterVisibility::Public => "nginx-qovery",
+            RouterVisibility::Internal => "nginx-qovery-internal",
+        }
+    }
+
+    fn custom_domains_status(&self, target: &DeploymentTarget) -> Result<Vec<CustomDomainStatus>, EngineError> {
+        let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
+        let kubernetes = target.kubernetes;
+        let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+        let namespace = target.environment.namespace();
+        let envs = kubernetes.cloud_provider().credentials_environment_variables();
+        let to_engine_error =
+            |e| EngineError::new_k8s_cannot_get_certificate_status(event_details.clone(), self.name().to_string(), e);
+
+        // cert-manager names the certificate after its secret, and the orders after the certificate
+        let certificate_name = format!("router-tls-{}", self.id);
+        let (certificate, orders) = match self.custom_domains.iter().any(|cd| cd.certificate.is_none()) {
+            true => (
+                kubectl_exec_get_certificate(&kubernetes_config_file_path, namespace, &certificate_name, envs.clone())
+                    .map_err(to_engine_error)?,
+                kubectl_exec_get_certificate_orders(&kubernetes_config_file_path, namespace, envs.clone())
+                    .map_err(to_engine_error)?
+                    .into_iter()
+                    .filter(|order| order.metadata.name.starts_with(&format!("{}-", certificate_name)))
+                    .collect(),
+            ),
+            false => (None, vec![]),
+        };
+
+        let mut custom_domains_status = Vec::with_capacity(self.custom_domains.len());
+        for custom_domain in &self.custom_domains {
+            let custom_certificate_secret_exists = match &custom_domain.certificate {
+                Some(CustomDomainCertificate::ExistingSecret { secret_name }) => {
+                    kubectl_exec_get_secret_data(&kubernetes_config_file_path, namespace, secret_name, envs.clone())
+                        .map_err(to_engine_error)?
+                        .is_some()
+                }
+                // the engine created it before deploying the router
+                Some(CustomDomainCertificate::Provided { .. }) => true,
+                None => false,
+            };
+
+            custom_domains_status.push(CustomDomainStatus {
+                domain: custom_domain.domain.clone(),
+                verification: custom_domain_verification(
+                    custom_domain,
+                    resolve_cname(&custom_domain.domain),
+                    certificate.as_ref(),
+                    &orders,
+                    custom_certificate_secret_exists,
+                ),
+            });
+        }
+
+        Ok(custom_domains_status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud_provider::models::{
+        CustomDomain, CustomDomainCertificate, IngressTlsDataTemplate, Route, RouteDataTemplate, RouterAccessControl,
+    };
+    use crate::cmd::structs::{
+        CertManagerCertificate, CertManagerCertificateSpec, CertManagerCertificateStatus, CertManagerCondition,
+        CertManagerOrder, CertManagerOrderSpec, CertManagerOrderStatus,
+    };
+    use crate::crypto::to_sha1_truncate_16;
+    use crate::dns_provider::{DnsRecord, DnsRecordType};
+    use crate::io_models::{CustomDomainVerification, RouteProtocol};
+    use crate::models::router::{
+        certificate_expiration_warning, custom_domain_verification, ingresses_data_templates, stale_dns_records,
+    };
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn test_certificate_expiration_warning() {
+        let now = Utc.ymd(2022, 6, 1).and_hms(0, 0, 0);
+
+        assert!(certificate_expiration_warning("example.com", now + Duration::days(90), now).is_none());
+        assert_eq!(
+            certificate_expiration_warning("example.com", now + Duration::days(10), now),
+            Some(
+                "Certificate of domain `example.com` expires on 2022-06-11, in 10 days, it has to be renewed"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            certificate_expiration_warning("example.com", now - Duration::days(1), now),
+            Some("Certificate of domain `example.com` expired on 2022-05-31, browsers will refuse to connect until it is renewed".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ingresses_data_templates_with_custom_certificates() {
+        let route = |path: &str, access_control: Option<RouterAccessControl>| Route {
+            path: path.to_string(),
+            application_name: "app".to_string(),
+            access_control,
+            protocol: RouteProtocol::Http,
+            port: None,
+        };
+        let route_data_template = |path: &str| RouteDataTemplate {
+            path: path.to_string(),
+            application_name: "app".to_string(),
+            application_port: 8080,
+        };
+        let routes = vec![
+            route("/", None),
+            route(
+                "/admin",
+                Some(RouterAccessControl {
+                    ip_allowlist: vec!["10.0.0.0/8".to_string()],
+                    ..Default::default()
+                }),
+            ),
+        ];
+        let acme_tls = IngressTlsDataTemplate {
+            secret_name: "router-tls-id".to_string(),
+            hosts: vec!["acme.example.com".to_string()],
+        };
+        let custom_certificate_tls = IngressTlsDataTemplate {
+            secret_name: "example-tls".to_string(),
+            hosts: vec!["www.example.com".to_string(), "api.example.com".to_string()],
+        };
+
+        let ingresses = ingresses_data_templates(
+            "router",
+            "long-id",
+            &RouterAccessControl::default(),
+            routes
+                .iter()
+                .map(|route| (route, route_data_template(&route.path)))
+                .collect(),
+            (
+                vec!["router.qovery.io".to_string(), "acme.example.com".to_string()],
+                vec![acme_tls],
+            ),
+            vec![custom_certificate_tls],
+        );
+
+        let route_name = format!("router-route-{}", to_sha1_truncate_16("/admin"));
+        let names = ingresses
+            .iter()
+            .map(|ingress| ingress.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "router",
+                "router-custom-certificate",
+                route_name.as_str(),
+                format!("{}-custom-certificate", route_name).as_str(),
+            ]
+        );
+        // only the Ingress annotated with the cert-manager issuer has the hosts it requests certificates for
+        assert!(ingresses[0].is_main && ingresses[0].has_server_snippet);
+        assert_eq!(ingresses[0].hosts, vec!["router.qovery.io", "acme.example.com"]);
+        assert_eq!(ingresses[0].tls[0].secret_name, "router-tls-id");
+        assert!(!ingresses[1].is_main && ingresses[1].has_server_snippet);
+        assert_eq!(ingresses[1].hosts, vec!["www.example.com", "api.example.com"]);
+        assert_eq!(ingresses[1].tls[0].secret_name, "example-tls");
+        assert!(ingresses[2..]
+            .iter()
+            .all(|ingress| !ingress.is_main && !ingress.has_server_snippet));
+        assert_eq!(ingresses[3].routes[0].path, "/admin");
+        assert_eq!(ingresses[3].access_control.ip_rules, ingresses[2].access_control.ip_rules);
+    }
+
+    #[test]
+    fn test_ingresses_data_templates_with_protocols() {
+        let route = |path: &str, protocol: RouteProtocol, access_control: Option<RouterAccessControl>| Route {
+            path: path.to_string(),
+            application_name: "app".to_string(),
+            access_control,
+            protocol,
+            port: None,
+        };
+        let routes = vec![
+            route("/grpc", RouteProtocol::Grpc, None),
+            route("/", RouteProtocol::Http, None),
+            route("/ws", RouteProtocol::WebSocket, None),
+            route("/grpc-admin", RouteProtocol::Grpc, None),
+            route(
+                "/ws-admin",
+                RouteProtocol::WebSocket,
+                Some(RouterAccessControl {
+                    ip_allowlist: vec!["10.0.0.0/8".to_string()],
+                    ..Default::default()
+                }),
+            ),
+        ];
+
+        let ingresses = ingresses_data_templates(
+            "router",
+            "long-id",
+            &RouterAccessControl::default(),
+            routes
+                .iter()
+                .map(|route| {
+                    (
+                        route,
+                        RouteDataTemplate {
+                            path: route.path.clone(),
+                            application_name: "app".to_string(),
+                            application_port: 8080,
+                        },
+                    )
+                })
+                .collect(),
+            (vec!["router.qovery.io".to_string()], vec![]),
+            vec![],
+        );
+
+        let names = ingresses
+            .iter()
+            .map(|ingress| ingress.name.as_str())
+            .collect::<Vec<_>>();
+        let route_name = format!("router-route-{}", to_sha1_truncate_16("/ws-admin"));
+        assert_eq!(names, vec!["router", "router-grpc", "router-websocket", route_name.as_str()]);
+        // the HTTP Ingress stays the main one whatever the order of the routes
+        assert!(ingresses[0].is_main && ingresses[0].backend_protocol.is_none() && !ingresses[0].is_websocket);
+        assert!(!ingresses[1].is_main);
+        assert_eq!(ingresses[1].backend_protocol.as_deref(), Some("GRPC"));
+        assert_eq!(
+            ingresses[1]
+                .routes
+                .iter()
+                .map(|route| route.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/grpc", "/grpc-admin"]
+        );
+        assert!(ingresses[2].is_websocket && ingresses[2].backend_protocol.is_none());
+        assert!(ingresses[3].is_websocket && !ingresses[3].access_control.ip_rules.is_empty());
+    }
+
+    #[test]
+    fn test_stale_dns_records() {
+        let record = |name: &str, record_type: DnsRecordType, value: &str| DnsRecord {
+            name: name.to_string(),
+            record_type,
+            value: value.to_string(),
+            ttl: 300,
+        };
+        let records = vec![
+            record("app.example.com", DnsRecordType::CNAME, "lb.example.com"),
+            record("app.example.com", DnsRecordType::TXT, "site-verification=abcd"),
+        ];
+        let ownership_record = |owner: &str| {
+            record(
+                "qvy-cluster-app.example.com",
+                DnsRecordType::TXT,
+                &format!(
+                    "heritage=external-dns,external-dns/owner={},external-dns/resource=ingress/ns/router",
+                    owner
+                ),
+            )
+        };
+
+        assert_eq!(
+            stale_dns_records("cluster", records.clone(), vec![ownership_record("cluster")]),
+            vec![records[0].clone(), ownership_record("cluster")]
+        );
+        // records registered by another cluster, or by hand, are left untouched
+        assert!(stale_dns_records("cluster", records.clone(), vec![ownership_record("other-cluster")]).is_empty());
+        assert!(stale_dns_records("cluster", records, vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_custom_domain_verification() {
+        let custom_domain = CustomDomain {
+            domain: "www.example.com".to_string(),
+            target_domain: "router.qovery.io".to_string(),
+            certificate: None,
+        };
+        let cname = Some("router.qovery.io.".to_string());
+        let certificate = |ready: &str| CertManagerCertificate {
+            spec: CertManagerCertificateSpec {
+                dns_names: vec!["www.example.com".to_string()],
+            },
+            status: CertManagerCertificateStatus {
+                conditions: vec![CertManagerCondition {
+                    condition_type: "Ready".to_string(),
+                    status: ready.to_string(),
+                    reason: None,
+                    message: Some("Waiting for CertificateRequest to complete".to_string()),
+                }],
+            },
+            ..Default::default()
+        };
+        let order = |state: &str| CertManagerOrder {
+            spec: CertManagerOrderSpec {
+                dns_names: vec!["www.example.com".to_string()],
+            },
+            status: CertManagerOrderStatus {
+                state: Some(state.to_string()),
+                reason: None,
+            },
+            ..Default::default()
+        };
+
+        // an issued certificate wins, the CNAME can be hidden by a CDN
+        assert_eq!(
+            custom_domain_verification(&custom_domain, None, Some(&certificate("True")), &[], false),
+            CustomDomainVerification::Issued
+        );
+        assert_eq!(
+            custom_domain_verification(
+                &custom_domain,
+                Some("cdn.example.net".to_string()),
+                Some(&certificate("False")),
+                &[order("invalid")],
+                false
+            ),
+            CustomDomainVerification::PendingDns {
+                expected_target: "router.qovery.io".to_string(),
+                current_target: Some("cdn.example.net".to_string()),
+            }
+        );
+        assert_eq!(
+            custom_domain_verification(
+                &custom_domain,
+                cname.clone(),
+                Some(&certificate("False")),
+                &[order("pending"), order("invalid")],
+                false
+            ),
+            CustomDomainVerification::Failed {
+                reason: "certificate order is invalid".to_string()
+            }
+        );
+        // a new attempt is made after the failed one
+        assert_eq!(
+            custom_domain_verification(
+                &custom_domain,
+                cname.clone(),
+                Some(&certificate("False")),
+                &[order("invalid"), order("pending")],
+                false
+            ),
+            CustomDomainVerification::PendingAcme {
+                reason: Some("Waiting for CertificateRequest to complete".to_string())
+            }
+        );
+        assert_eq!(
+            custom_domain_verification(&custom_domain, cname.clone(), None, &[], false),
+            CustomDomainVerification::PendingAcme { reason: None }
+        );
+
+        let custom_domain = CustomDomain {
+            certificate: Some(CustomDomainCertificate::ExistingSecret {
+                secret_name: "example-tls".to_string(),
+            }),
+            ..custom_domain
+        };
+        assert_eq!(
+            custom_domain_verification(&custom_domain, cname.clone(), None, &[], true),
+            CustomDomainVerification::Issued
+        );
+        assert!(matches!(
+            custom_domain_verification(&custom_domain, cname, None, &[], false),
+            CustomDomainVerification::Failed { .. }
+        ));
+    }
 }
@@ -3,7 +3,6 @@ use crate::cloud_provider::service::{
     ServiceVersionCheckResult,
 };
 use crate::cloud_provider::{service, DeploymentTarget};
-use crate::cmd::kubectl;
 use crate::errors::EngineError;
 use crate::events::{EnvironmentStep, EventDetails, Stage};
 use crate::models::database::{
@@ -13,7 +12,9 @@ use crate::models::database_utils::{
     get_self_hosted_mongodb_version, get_self_hosted_mysql_version, get_self_hosted_postgres_version,
     get_self_hosted_redis_version,
 };
-use crate::models::scaleway::database_utils::{pick_managed_mysql_version, pick_managed_postgres_version};
+use crate::models::scaleway::database_utils::{
+    pick_managed_mysql_version, pick_managed_postgres_version, pick_managed_redis_version,
+};
 use crate::models::types::{ToTeraContext, SCW};
 use tera::Context as TeraContext;
 
@@ -110,7 +111,26 @@ impl DatabaseType<SCW, Managed> for MySQL {
     }
 }
 
-// Redis and MongoDB are not supported managed db yet
+impl DatabaseType<SCW, Managed> for Redis {
+    type DatabaseOptions = DatabaseOptions;
+
+    fn short_name() -> &'static str {
+        "Redis Managed"
+    }
+    fn lib_directory_name() -> &'static str {
+        "redis"
+    }
+
+    fn db_type() -> service::DatabaseType {
+        service::DatabaseType::Redis
+    }
+
+    fn is_tls_required() -> bool {
+        true
+    }
+}
+
+// MongoDB is not supported managed db yet
 
 impl<M: DatabaseMode, T: DatabaseType<SCW, M>> Database<SCW, M, T> {
     fn to_tera_context_for_scaleway_managed(
@@ -132,12 +152,6 @@ impl<M: DatabaseMode, T: DatabaseType<SCW, M>> Database<SCW, M, T> {
         let kube_config_file_path = kubernetes.get_kubeconfig_file_path()?;
         context.insert("kubeconfig_path", &kube_config_file_path);
 
-        kubectl::kubectl_exec_create_namespace_without_labels(
-            environment.namespace(),
-            kube_config_file_path.as_str(),
-            kubernetes.cloud_provider().credentials_environment_variables(),
-        );
-
         context.insert("namespace", environment.namespace());
 
         let version = get_version(event_details)?.matched_version();
@@ -172,6 +186,7 @@ impl<M: DatabaseMode, T: DatabaseType<SCW, M>> Database<SCW, M, T> {
         context.insert("publicly_accessible", &options.publicly_accessible);
         context.insert("activate_high_availability", &options.activate_high_availability);
         context.insert("activate_backups", &options.activate_backups);
+        self.insert_read_replicas_into_tera_context(target, options, &mut context);
         context.insert("delete_automated_backups", &self.context().is_test_cluster());
         if self.context.resource_expiration_in_seconds().is_some() {
             context.insert("resource_expiration_in_seconds", &self.context.resource_expiration_in_seconds())
@@ -275,6 +290,23 @@ where
 
 ////////////////////////////////////////////////////////////////////////:
 // Redis
+impl ToTeraContext for Database<SCW, Managed, Redis>
+where
+    Redis: DatabaseType<SCW, Managed>,
+{
+    fn to_tera_context(&self, target: &DeploymentTarget) -> Result<TeraContext, EngineError> {
+        let check_version = |event_details| {
+            check_service_version(
+                pick_managed_redis_version(self.version.to_string()),
+                self,
+                event_details,
+                self.logger(),
+            )
+        };
+        self.to_tera_context_for_scaleway_managed(target, &self.options, &check_version)
+    }
+}
+
 impl ToTeraContext for Database<SCW, Container, Redis>
 where
     Redis: DatabaseType<SCW, Container>,
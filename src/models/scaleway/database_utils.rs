@@ -34,3 +34,39 @@ pub(super) fn pick_managed_mysql_version(requested_version: String) -> Result<St
 
     get_supported_version_to_use("RDB MySQL", supported_mysql_versions, requested_version)
 }
+
+pub(super) fn pick_managed_redis_version(requested_version: String) -> Result<String, CommandError> {
+    // Scaleway supported Redis versions
+    // https://api.scaleway.com/redis/v1/zones/fr-par-1/cluster-versions
+    let mut supported_redis_versions = HashMap::new();
+
+    // {"version":"7.0.5","end_of_life_at":null}
+    // {"version":"6.2.7","end_of_life_at":null}
+    supported_redis_versions.insert("6".to_string(), "6.2.7".to_string());
+    supported_redis_versions.insert("6.2".to_string(), "6.2.7".to_string());
+    supported_redis_versions.insert("6.2.7".to_string(), "6.2.7".to_string());
+    supported_redis_versions.insert("7".to_string(), "7.0.5".to_string());
+    supported_redis_versions.insert("7.0".to_string(), "7.0.5".to_string());
+    supported_redis_versions.insert("7.0.5".to_string(), "7.0.5".to_string());
+
+    get_supported_version_to_use("Managed Redis", supported_redis_versions, requested_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::ErrorMessageVerbosity::SafeOnly;
+    use crate::models::scaleway::database_utils::pick_managed_redis_version;
+
+    #[test]
+    fn check_redis_version() {
+        assert_eq!(pick_managed_redis_version("6".to_string()).unwrap(), "6.2.7");
+        assert_eq!(pick_managed_redis_version("7.0".to_string()).unwrap(), "7.0.5");
+        assert_eq!(
+            pick_managed_redis_version("5".to_string())
+                .unwrap_err()
+                .message(SafeOnly)
+                .as_str(),
+            "Managed Redis 5 version is not supported"
+        );
+    }
+}
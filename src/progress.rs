@@ -0,0 +1,164 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use fs2::FileExt;
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::io_models::StepName;
+
+const STEP_DURATIONS_FILE_NAME: &str = "step-durations.json";
+const MAX_DURATIONS_PER_STEP: usize = 10;
+
+/// Durations of the last successful steps, kept across executions in the workspace root directory
+/// to estimate when a running step will end
+pub struct StepDurationHistory {
+    file_path: PathBuf,
+    // key is `<cluster kind>/<step name>` as durations vary a lot from one cloud provider to another
+    durations_in_seconds: BTreeMap<String, Vec<u64>>,
+}
+
+impl StepDurationHistory {
+    /// A missing or unreadable history starts empty, estimations are best effort only
+    pub fn load<P: AsRef<Path>>(workspace_root_dir: P) -> Self {
+        let file_path = workspace_root_dir
+            .as_ref()
+            .join(".qovery-workspace")
+            .join(STEP_DURATIONS_FILE_NAME);
+        let durations_in_seconds = Self::read(&file_path);
+
+        StepDurationHistory {
+            file_path,
+            durations_in_seconds,
+        }
+    }
+
+    pub fn estimate(&self, cluster_kind: &str, step: &StepName) -> Option<Duration> {
+        let durations = self.durations_in_seconds.get(&Self::key(cluster_kind, step))?;
+        if durations.is_empty() {
+            return None;
+        }
+
+        Some(Duration::from_secs(durations.iter().sum::<u64>() / durations.len() as u64))
+    }
+
+    pub fn eta(&self, cluster_kind: &str, step: &StepName, started_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let estimate = self.estimate(cluster_kind, step)?;
+        ChronoDuration::from_std(estimate)
+            .ok()
+            .map(|estimate| started_at + estimate)
+    }
+
+    /// Several engines may share the workspace root directory, the file is locked while it is updated
+    pub fn record(&mut self, cluster_kind: &str, step: &StepName, duration: Duration) -> Result<(), std::io::Error> {
+        if let Some(parent) = self.file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(self.file_path.with_extension("json.lock"))?;
+        lock_file.lock_exclusive()?;
+
+        // durations recorded by other executions since it has been loaded are kept
+        let mut durations_in_seconds = Self::read(&self.file_path);
+        let durations = durations_in_seconds.entry(Self::key(cluster_kind, step)).or_default();
+        durations.push(duration.as_secs());
+        if durations.len() > MAX_DURATIONS_PER_STEP {
+            durations.remove(0);
+        }
+
+        // readers don't lock the file, it is replaced at once
+        let tmp_file_path = self.file_path.with_extension("json.tmp");
+        fs::write(&tmp_file_path, serde_json::to_string(&durations_in_seconds)?)?;
+        fs::rename(&tmp_file_path, &self.file_path)?;
+        self.durations_in_seconds = durations_in_seconds;
+
+        lock_file.unlock()
+    }
+
+    fn read(file_path: &Path) -> BTreeMap<String, Vec<u64>> {
+        fs::read_to_string(file_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn key(cluster_kind: &str, step: &StepName) -> String {
+        format!("{}/{}", cluster_kind, step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io_models::StepName;
+    use crate::progress::{StepDurationHistory, MAX_DURATIONS_PER_STEP};
+    use std::time::Duration;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_step_duration_history() {
+        let tmp_dir = TempDir::new("step_durations").unwrap();
+
+        let mut history = StepDurationHistory::load(tmp_dir.path());
+        assert!(history.estimate("EKS", &StepName::CreateKubernetes).is_none());
+
+        history
+            .record("EKS", &StepName::CreateKubernetes, Duration::from_secs(1000))
+            .unwrap();
+        history
+            .record("EKS", &StepName::CreateKubernetes, Duration::from_secs(1200))
+            .unwrap();
+        history
+            .record("DOKS", &StepName::CreateKubernetes, Duration::from_secs(300))
+            .unwrap();
+
+        // history is kept across executions
+        let mut history = StepDurationHistory::load(tmp_dir.path());
+        assert_eq!(
+            history.estimate("EKS", &StepName::CreateKubernetes),
+            Some(Duration::from_secs(1100))
+        );
+        assert_eq!(
+            history.estimate("DOKS", &StepName::CreateKubernetes),
+            Some(Duration::from_secs(300))
+        );
+        assert!(history.estimate("EKS", &StepName::DeployEnvironment).is_none());
+
+        // only last durations are used
+        for _ in 0..MAX_DURATIONS_PER_STEP {
+            history
+                .record("DOKS", &StepName::CreateKubernetes, Duration::from_secs(600))
+                .unwrap();
+        }
+        assert_eq!(
+            history.estimate("DOKS", &StepName::CreateKubernetes),
+            Some(Duration::from_secs(600))
+        );
+    }
+
+    #[test]
+    fn test_step_duration_history_keeps_concurrent_records() {
+        let tmp_dir = TempDir::new("step_durations").unwrap();
+        let mut first_execution = StepDurationHistory::load(tmp_dir.path());
+        let mut second_execution = StepDurationHistory::load(tmp_dir.path());
+
+        first_execution
+            .record("EKS", &StepName::CreateKubernetes, Duration::from_secs(1000))
+            .unwrap();
+        second_execution
+            .record("EKS", &StepName::DeployEnvironment, Duration::from_secs(60))
+            .unwrap();
+
+        let history = StepDurationHistory::load(tmp_dir.path());
+        assert_eq!(
+            history.estimate("EKS", &StepName::CreateKubernetes),
+            Some(Duration::from_secs(1000))
+        );
+        assert_eq!(
+            history.estimate("EKS", &StepName::DeployEnvironment),
+            Some(Duration::from_secs(60))
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use lazy_static::lazy_static;
 use std::future::Future;
+use std::panic::Location;
 use std::sync::Mutex;
 use tokio::runtime::{Builder, Runtime};
 
@@ -13,6 +14,11 @@ lazy_static! {
     });
 }
 
+/// Only used to call cloud provider APIs, so each call gets its own span pointing to the caller
+#[track_caller]
 pub fn block_on<F: Future>(future: F) -> F::Output {
+    let caller = Location::caller();
+    let _span = info_span!("cloud_api_call", caller = %caller).entered();
+
     TOKIO_RUNTIME.lock().unwrap().block_on(future)
 }
@@ -0,0 +1,41 @@
+//! Spans are opened for each transaction step, service action, external command and cloud provider API call.
+//! With the `otlp` feature, they can be exported to any OpenTelemetry collector by registering
+//! [`otlp_layer`] on the tracing subscriber.
+
+#[cfg(feature = "otlp")]
+pub use self::otlp::{otlp_layer, shutdown_otlp};
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use opentelemetry::sdk::trace::{config, Tracer};
+    use opentelemetry::sdk::Resource;
+    use opentelemetry::trace::TraceError;
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use tracing::Subscriber;
+    use tracing_opentelemetry::OpenTelemetryLayer;
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Layer exporting spans to an OTLP collector over HTTP (i.e: http://localhost:4318/v1/traces).
+    /// Spans are sent one by one from a dedicated thread, so the engine tokio runtime is never involved.
+    /// Must be called outside of any tokio runtime.
+    pub fn otlp_layer<S>(endpoint: &str, service_name: &str) -> Result<OpenTelemetryLayer<S, Tracer>, TraceError>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(opentelemetry_otlp::new_exporter().http().with_endpoint(endpoint))
+            .with_trace_config(
+                config().with_resource(Resource::new(vec![KeyValue::new("service.name", service_name.to_string())])),
+            )
+            .install_simple()?;
+
+        Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+    }
+
+    /// Flush remaining spans, to call before the process exits
+    pub fn shutdown_otlp() {
+        opentelemetry::global::shutdown_tracer_provider();
+    }
+}
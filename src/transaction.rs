@@ -1,20 +1,29 @@
 use crate::build_platform::BuildError;
+use crate::change_set::{ImageBuildReason, ImageToBuild, TransactionChangeSet};
 use crate::cloud_provider::environment::Environment;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Instant;
 
-use crate::cloud_provider::kubernetes::Kubernetes;
+use crate::cloud_provider::kubernetes::{plan_environment, Kubernetes};
 use crate::cloud_provider::service::{Action, Service};
+use crate::cloud_provider::DeploymentTarget;
 use crate::container_registry::errors::ContainerRegistryError;
 use crate::container_registry::to_engine_error;
 use crate::engine::{EngineConfig, EngineConfigError};
 use crate::errors::{EngineError, Tag};
-use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, Stage, Transmitter};
+use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, GeneralStep, Stage, Transmitter};
 use crate::io_models::{
-    EnvironmentError, ListenersHelper, ProgressInfo, ProgressLevel, ProgressScope, QoveryIdentifier,
+    EnvironmentError, ListenersHelper, ProgressCounter, ProgressInfo, ProgressLevel, ProgressScope, ProgressSubTask,
+    QoveryIdentifier, StepName,
 };
 use crate::logger::Logger;
+use crate::metrics;
+use crate::metrics::{BUILD_CACHE_TOTAL, BUILD_DURATION_SECONDS, TRANSACTION_OUTCOME_TOTAL};
 use crate::models::application::ApplicationService;
+use crate::models::database_credentials::{rotate_database_credentials, DatabaseCredentials};
+use crate::progress::StepDurationHistory;
+use uuid::Uuid;
 
 pub struct Transaction<'a> {
     engine: &'a EngineConfig,
@@ -22,8 +31,9 @@ pub struct Transaction<'a> {
     steps: Vec<Step>,
     executed_steps: Vec<Step>,
     current_step: StepName,
-    is_transaction_aborted: Box<dyn Fn() -> bool>,
+    is_transaction_aborted: Rc<dyn Fn() -> bool>,
     on_step_change: Box<dyn Fn(&StepName)>,
+    step_durations: StepDurationHistory,
 }
 
 impl<'a> Transaction<'a> {
@@ -44,8 +54,9 @@ impl<'a> Transaction<'a> {
             steps: vec![],
             executed_steps: vec![],
             current_step: StepName::Waiting,
-            is_transaction_aborted,
+            is_transaction_aborted: Rc::from(is_transaction_aborted),
             on_step_change,
+            step_durations: StepDurationHistory::load(engine.context().workspace_root_dir()),
         };
         tx.set_current_step(StepName::Waiting);
 
@@ -129,6 +140,20 @@ impl<'a> Transaction<'a> {
         Ok(())
     }
 
+    pub fn rotate_database_credentials(
+        &mut self,
+        environment: &Rc<RefCell<Environment>>,
+        database_long_id: Uuid,
+        new_credentials: DatabaseCredentials,
+    ) -> Result<(), EnvironmentError> {
+        self.steps.push(Step::RotateDatabaseCredentials(
+            environment.clone(),
+            database_long_id,
+            new_credentials,
+        ));
+        Ok(())
+    }
+
     fn build_and_push_applications(
         &self,
         applications: &mut [Box<dyn ApplicationService>],
@@ -172,7 +197,10 @@ impl<'a> Transaction<'a> {
         let cr_registry = self.engine.container_registry();
         let _ = cr_registry.create_registry().map_err(cr_to_engine_error)?;
 
-        for app in apps_to_build.iter_mut() {
+        let apps_count = apps_to_build.len();
+        for (app_idx, app) in apps_to_build.iter_mut().enumerate() {
+            let build_sub_task = ProgressSubTask::new(app.id(), Some(ProgressCounter::new(app_idx + 1, apps_count)));
+
             // If image already exist in the registry, skip the build
             if !option.force_build && cr_registry.does_image_exists(&app.get_build().image) {
                 continue;
@@ -185,11 +213,43 @@ impl<'a> Transaction<'a> {
                 .create_repository(app.get_build().image.repository_name())
                 .map_err(cr_to_engine_error)?;
 
+            ListenersHelper::new(self.engine.build_platform().listeners()).deployment_in_progress(
+                ProgressInfo::new(
+                    ProgressScope::Application {
+                        id: app.id().to_string(),
+                    },
+                    ProgressLevel::Info,
+                    Some(format!("Building application {}/{}", app_idx + 1, apps_count)),
+                    self.engine.context().execution_id(),
+                )
+                .with_sub_task(build_sub_task.clone()),
+            );
+
             // Ok now everything is setup, we can try to build the app
+            let started_at = Instant::now();
             let build_result = self
                 .engine
                 .build_platform()
-                .build(app.get_build_mut(), &self.is_transaction_aborted);
+                .build(app.get_build_mut(), &*self.is_transaction_aborted);
+
+            let build_platform_kind = format!("{:?}", self.engine.build_platform().kind());
+            metrics::observe_duration(
+                &BUILD_DURATION_SECONDS,
+                &[
+                    ("build_platform", build_platform_kind.as_str()),
+                    ("outcome", metrics::outcome_label(&build_result)),
+                ],
+                started_at.elapsed(),
+            );
+            if let Ok(result) = &build_result {
+                metrics::increment_counter(
+                    &BUILD_CACHE_TOTAL,
+                    &[
+                        ("build_platform", build_platform_kind.as_str()),
+                        ("cache", result.cache_status()),
+                    ],
+                );
+            }
 
             // logging
             let image_name = app.get_build().image.full_image_name_with_tag();
@@ -209,7 +269,8 @@ impl<'a> Transaction<'a> {
                 },
                 Some(msg.to_string()),
                 self.engine.context().execution_id(),
-            );
+            )
+            .with_sub_task(build_sub_task);
             ListenersHelper::new(self.engine.build_platform().listeners()).deployment_in_progress(progress_info);
 
             let event_details = build_event_details();
@@ -223,6 +284,91 @@ impl<'a> Transaction<'a> {
         Ok(())
     }
 
+    fn images_to_build(
+        &self,
+        applications: &[Box<dyn ApplicationService>],
+        option: &DeploymentOption,
+    ) -> Vec<ImageToBuild> {
+        let cr_registry = self.engine.container_registry();
+
+        applications
+            .iter()
+            // build only applications that are set with Action: Create
+            .filter(|app| *app.action() == Action::Create)
+            .filter_map(|app| {
+                let reason = if option.force_build {
+                    ImageBuildReason::ForceBuild
+                } else if !cr_registry.does_image_exists(&app.get_build().image) {
+                    ImageBuildReason::NotInRegistry
+                } else {
+                    return None;
+                };
+
+                Some(ImageToBuild {
+                    application_id: app.id().to_string(),
+                    image_name: app.get_build().image.full_image_name_with_tag(),
+                    reason,
+                })
+            })
+            .collect()
+    }
+
+    /// Compute what committing the transaction would change, without building, deploying or deleting anything.
+    /// Infrastructure and database credentials rotation steps are not part of the change set.
+    pub fn plan(mut self) -> Result<TransactionChangeSet, EngineError> {
+        let mut change_set = TransactionChangeSet::default();
+
+        for step in self.steps.clone().into_iter() {
+            if (self.is_transaction_aborted)() {
+                return Err(EngineError::new_task_cancellation_requested(self.get_event_details(
+                    Stage::Environment(EnvironmentStep::Deploy),
+                    Transmitter::Kubernetes(
+                        self.engine.kubernetes().id().to_string(),
+                        self.engine.kubernetes().name().to_string(),
+                    ),
+                )));
+            }
+
+            self.set_current_step(step.step_name());
+
+            match step {
+                Step::BuildEnvironment(environment, option) => {
+                    let environment = environment.as_ref().borrow();
+                    let images_to_build = self.images_to_build(&environment.applications, &option);
+                    change_set
+                        .environment_mut(&environment.id)
+                        .images_to_build
+                        .extend(images_to_build);
+                }
+                Step::DeployEnvironment(environment)
+                | Step::PauseEnvironment(environment)
+                | Step::DeleteEnvironment(environment) => {
+                    let environment = environment.as_ref().borrow();
+                    let event_details = self.get_event_details(
+                        Stage::Environment(EnvironmentStep::LoadConfiguration),
+                        Transmitter::Kubernetes(
+                            self.engine.kubernetes().id().to_string(),
+                            self.engine.kubernetes().name().to_string(),
+                        ),
+                    );
+                    let environment_change_set =
+                        plan_environment(self.engine.kubernetes(), &environment, event_details, self.logger.as_ref())?;
+
+                    let planned = change_set.environment_mut(&environment.id);
+                    planned.namespaces = environment_change_set.namespaces;
+                    planned.services = environment_change_set.services;
+                }
+                Step::CreateKubernetes
+                | Step::DeleteKubernetes
+                | Step::PauseKubernetes
+                | Step::RotateDatabaseCredentials(_, _, _) => {}
+            }
+        }
+
+        self.set_current_step(StepName::Waiting);
+        Ok(change_set)
+    }
+
     pub fn rollback(&self) -> Result<(), RollbackError> {
         for step in self.executed_steps.iter() {
             match step {
@@ -257,6 +403,9 @@ impl<'a> Transaction<'a> {
                 Step::DeleteEnvironment(environment_action) => {
                     self.rollback_environment(&(environment_action.as_ref().borrow()))?;
                 }
+                Step::RotateDatabaseCredentials(_environment_action, _database_long_id, _new_credentials) => {
+                    // credentials rotation reverts its own steps on failure
+                }
             }
         }
 
@@ -281,11 +430,145 @@ impl<'a> Transaction<'a> {
         Err(RollbackError::NoFailoverEnvironment)
     }
 
+    /// Run all steps, on failure the redacted workspace is uploaded to the cluster object storage
     pub fn commit(mut self) -> TransactionResult {
-        for step in self.steps.clone().into_iter() {
+        let context = self.engine.context();
+        let span = info_span!(
+            "transaction",
+            execution_id = context.execution_id(),
+            organization_id = context.organization_id(),
+            cluster_id = context.cluster_id(),
+        );
+        let _enter = span.enter();
+
+        let result = match self.commit_steps() {
+            TransactionResult::Rollback(err, _) => TransactionResult::Rollback(err, self.persist_workspace_archive()),
+            TransactionResult::UnrecoverableError(err, rollback_err, _) => {
+                TransactionResult::UnrecoverableError(err, rollback_err, self.persist_workspace_archive())
+            }
+            result => result,
+        };
+
+        metrics::increment_counter(&TRANSACTION_OUTCOME_TOTAL, &[("outcome", result.outcome())]);
+
+        result
+    }
+
+    /// Databases credentials and urls of the transaction environments, as they are rendered in the workspace
+    fn environments_secrets(&self) -> Vec<String> {
+        let kubernetes = self.engine.kubernetes();
+        let mut secrets = vec![];
+
+        for step in self.steps.iter().chain(self.executed_steps.iter()) {
+            let (environment, new_credentials) = match step {
+                Step::BuildEnvironment(environment, _)
+                | Step::DeployEnvironment(environment)
+                | Step::PauseEnvironment(environment)
+                | Step::DeleteEnvironment(environment) => (environment, None),
+                Step::RotateDatabaseCredentials(environment, _, credentials) => (environment, Some(credentials)),
+                Step::CreateKubernetes | Step::DeleteKubernetes | Step::PauseKubernetes => continue,
+            };
+
+            let environment = environment.borrow();
+            let target = DeploymentTarget {
+                kubernetes,
+                environment: &environment,
+            };
+            for database in &environment.databases {
+                let connection_infos = database.connection_infos(&target);
+                secrets.push(connection_infos.url(&connection_infos.host));
+                secrets.push(connection_infos.password);
+            }
+            if let Some(credentials) = new_credentials {
+                secrets.push(credentials.password.clone());
+            }
+        }
+
+        // urls hold encoded passwords and kubernetes secrets base64 encoded values
+        let encoded_secrets = secrets
+            .iter()
+            .flat_map(|secret| vec![urlencoding::encode(secret).into_owned(), base64::encode(secret)])
+            .collect::<Vec<_>>();
+        secrets.extend(encoded_secrets);
+
+        secrets
+    }
+
+    fn persist_workspace_archive(&self) -> Option<WorkspaceArchive> {
+        let context = self.engine.context();
+        let cloud_provider = self.engine.cloud_provider();
+        let object_storage = self.engine.kubernetes().config_file_store();
+        let event_details = self.get_event_details(
+            Stage::General(GeneralStep::ArchiveWorkspace),
+            Transmitter::ObjectStorage(object_storage.id().to_string(), object_storage.name().to_string()),
+        );
+
+        let access_key_id = cloud_provider.access_key_id();
+        let secret_access_key = cloud_provider.secret_access_key();
+        let mut secrets = vec![
+            access_key_id.as_str(),
+            secret_access_key.as_str(),
+            cloud_provider.token(),
+        ];
+        secrets.extend(
+            cloud_provider
+                .credentials_environment_variables()
+                .into_iter()
+                .chain(cloud_provider.tera_context_environment_variables())
+                .map(|(_, value)| value),
+        );
+        let environments_secrets = self.environments_secrets();
+        secrets.extend(environments_secrets.iter().map(|secret| secret.as_str()));
+
+        let archive_path =
+            match crate::fs::create_workspace_archive(context.workspace_root_dir(), context.execution_id(), &secrets) {
+                Ok(archive_path) => archive_path,
+                Err(err) => {
+                    self.logger.log(EngineEvent::Warning(
+                        event_details,
+                        EventMessage::new("Cannot archive execution workspace.".to_string(), Some(err.to_string())),
+                    ));
+                    return None;
+                }
+            };
+
+        let archive = WorkspaceArchive {
+            bucket_name: self.engine.kubernetes().kubeconfig_bucket_name(),
+            object_key: format!("workspace-archives/{}.tgz", context.execution_id()),
+        };
+
+        match object_storage.put(&archive.bucket_name, &archive.object_key, &archive_path) {
+            Ok(_) => {
+                self.logger.log(EngineEvent::Info(
+                    event_details,
+                    EventMessage::new_from_safe(format!(
+                        "Execution workspace has been archived to {}/{}",
+                        archive.bucket_name, archive.object_key
+                    )),
+                ));
+                Some(archive)
+            }
+            Err(err) => {
+                self.logger.log(EngineEvent::Warning(
+                    event_details,
+                    EventMessage::new("Cannot upload execution workspace archive.".to_string(), Some(err.to_string())),
+                ));
+                None
+            }
+        }
+    }
+
+    fn commit_steps(&mut self) -> TransactionResult {
+        let steps_count = self.steps.len();
+        for (step_idx, step) in self.steps.clone().into_iter().enumerate() {
             // execution loop
+            let step_name = step.step_name();
             self.executed_steps.push(step.clone());
-            self.set_current_step(step.step_name());
+            self.set_current_step(step_name.clone());
+            let step_span = info_span!("transaction_step", step = %step_name);
+            let _step_span_guard = step_span.enter();
+            let step_started_at = Instant::now();
+            self.send_step_progress(&step, ProgressCounter::new(step_idx + 1, steps_count));
 
             match step {
                 Step::CreateKubernetes => {
@@ -336,7 +619,7 @@ impl<'a> Transaction<'a> {
                             return if engine_err.tag() == &Tag::TaskCancellationRequested {
                                 TransactionResult::Canceled
                             } else {
-                                TransactionResult::Rollback(engine_err)
+                                TransactionResult::Rollback(engine_err, None)
                             };
                         }
                     };
@@ -347,6 +630,9 @@ impl<'a> Transaction<'a> {
                     }
 
                     // deploy complete environment
+                    environment_action
+                        .borrow_mut()
+                        .set_is_canceled(self.is_transaction_aborted.clone());
                     match self.commit_environment(&(environment_action.as_ref().borrow()), |qe_env| {
                         self.engine.kubernetes().deploy_environment(qe_env)
                     }) {
@@ -363,6 +649,9 @@ impl<'a> Transaction<'a> {
                     }
 
                     // pause complete environment
+                    environment_action
+                        .borrow_mut()
+                        .set_is_canceled(self.is_transaction_aborted.clone());
                     match self.commit_environment(&(environment_action.as_ref().borrow()), |qe_env| {
                         self.engine.kubernetes().pause_environment(qe_env)
                     }) {
@@ -379,6 +668,9 @@ impl<'a> Transaction<'a> {
                     }
 
                     // delete complete environment
+                    environment_action
+                        .borrow_mut()
+                        .set_is_canceled(self.is_transaction_aborted.clone());
                     match self.commit_environment(&(environment_action.as_ref().borrow()), |qe_env| {
                         self.engine.kubernetes().delete_environment(qe_env)
                     }) {
@@ -389,12 +681,80 @@ impl<'a> Transaction<'a> {
                         }
                     };
                 }
+                Step::RotateDatabaseCredentials(environment_action, database_long_id, new_credentials) => {
+                    if (self.is_transaction_aborted)() {
+                        return TransactionResult::Canceled;
+                    }
+
+                    if let Err(engine_err) = rotate_database_credentials(
+                        self.engine.kubernetes(),
+                        &(environment_action.as_ref().borrow()),
+                        &database_long_id,
+                        &new_credentials,
+                        self.logger.as_ref(),
+                    ) {
+                        self.logger.log(EngineEvent::Error(
+                            engine_err.clone(),
+                            Some(EventMessage::new_from_safe(
+                                "Database credentials rotation has failed".to_string(),
+                            )),
+                        ));
+
+                        return TransactionResult::Rollback(engine_err, None);
+                    }
+                }
             };
+
+            let cluster_kind = self.engine.kubernetes().kind().to_string();
+            if let Err(err) = self
+                .step_durations
+                .record(&cluster_kind, &step_name, step_started_at.elapsed())
+            {
+                warn!("Cannot save step durations history: {}", err);
+            }
         }
 
         TransactionResult::Ok
     }
 
+    /// Tell listeners which step starts, its position among all the steps and when it should end
+    fn send_step_progress(&self, step: &Step, step_counter: ProgressCounter) {
+        let execution_id = self.engine.context().execution_id();
+        let step_name = step.step_name();
+        let eta = self
+            .step_durations
+            .eta(&self.engine.kubernetes().kind().to_string(), &step_name, chrono::Utc::now());
+
+        let scope = match step {
+            Step::BuildEnvironment(environment, _)
+            | Step::DeployEnvironment(environment)
+            | Step::PauseEnvironment(environment)
+            | Step::DeleteEnvironment(environment)
+            | Step::RotateDatabaseCredentials(environment, _, _) => ProgressScope::Environment {
+                id: environment.borrow().id.clone(),
+            },
+            Step::CreateKubernetes | Step::DeleteKubernetes | Step::PauseKubernetes => ProgressScope::Infrastructure {
+                execution_id: execution_id.to_string(),
+            },
+        };
+
+        let progress_info = ProgressInfo::new(
+            scope,
+            ProgressLevel::Info,
+            Some(format!("Step {}: {}", step_counter, step_name)),
+            execution_id,
+        )
+        .with_step(step_name, step_counter)
+        .with_eta(eta);
+
+        let listeners_helper = ListenersHelper::new(self.engine.kubernetes().listeners());
+        match step {
+            Step::PauseKubernetes | Step::PauseEnvironment(_) => listeners_helper.pause_in_progress(progress_info),
+            Step::DeleteKubernetes | Step::DeleteEnvironment(_) => listeners_helper.delete_in_progress(progress_info),
+            _ => listeners_helper.deployment_in_progress(progress_info),
+        }
+    }
+
     fn commit_infrastructure(&self, action: Action, result: Result<(), EngineError>) -> TransactionResult {
         // send back the right progress status
         fn send_progress(lh: &ListenersHelper, action: Action, execution_id: &str, is_error: bool) {
@@ -435,13 +795,13 @@ impl<'a> Transaction<'a> {
                     Ok(_) => {
                         // an error occurred on infrastructure deployment BUT rolledback is OK
                         send_progress(&lh, action, execution_id, true);
-                        TransactionResult::Rollback(err)
+                        TransactionResult::Rollback(err, None)
                     }
                     Err(e) => {
                         // an error occurred on infrastructure deployment AND rolledback is KO
                         error!("infrastructure ROLLBACK FAILED! fatal error: {:?}", e);
                         send_progress(&lh, action, execution_id, true);
-                        TransactionResult::UnrecoverableError(err, e)
+                        TransactionResult::UnrecoverableError(err, e, None)
                     }
                 }
             }
@@ -494,10 +854,10 @@ impl<'a> Transaction<'a> {
         let _ = match action_fn(environment) {
             Err(err) => {
                 let rollback_result = match self.rollback() {
-                    Ok(_) => TransactionResult::Rollback(err),
+                    Ok(_) => TransactionResult::Rollback(err, None),
                     Err(rollback_err) => {
                         error!("ROLLBACK FAILED! fatal error: {:?}", rollback_err);
-                        TransactionResult::UnrecoverableError(err, rollback_err)
+                        TransactionResult::UnrecoverableError(err, rollback_err, None)
                     }
                 };
 
@@ -535,33 +895,6 @@ pub struct DeploymentOption {
     pub force_push: bool,
 }
 
-#[derive(Clone)]
-pub enum StepName {
-    CreateKubernetes,
-    DeleteKubernetes,
-    PauseKubernetes,
-    BuildEnvironment,
-    DeployEnvironment,
-    PauseEnvironment,
-    DeleteEnvironment,
-    Waiting,
-}
-
-impl StepName {
-    pub fn can_be_canceled(&self) -> bool {
-        match self {
-            StepName::CreateKubernetes => false,
-            StepName::DeleteKubernetes => false,
-            StepName::PauseKubernetes => false,
-            StepName::DeployEnvironment => false,
-            StepName::PauseEnvironment => false,
-            StepName::DeleteEnvironment => false,
-            StepName::BuildEnvironment => true,
-            StepName::Waiting => true,
-        }
-    }
-}
-
 pub enum Step {
     // init and create all the necessary resources (Network, Kubernetes)
     CreateKubernetes,
@@ -571,6 +904,7 @@ pub enum Step {
     DeployEnvironment(Rc<RefCell<Environment>>),
     PauseEnvironment(Rc<RefCell<Environment>>),
     DeleteEnvironment(Rc<RefCell<Environment>>),
+    RotateDatabaseCredentials(Rc<RefCell<Environment>>, Uuid, DatabaseCredentials),
 }
 
 impl Step {
@@ -583,6 +917,7 @@ impl Step {
             Step::DeployEnvironment(_) => StepName::DeployEnvironment,
             Step::PauseEnvironment(_) => StepName::PauseEnvironment,
             Step::DeleteEnvironment(_) => StepName::DeleteEnvironment,
+            Step::RotateDatabaseCredentials(_, _, _) => StepName::RotateDatabaseCredentials,
         }
     }
 }
@@ -597,6 +932,9 @@ impl Clone for Step {
             Step::DeployEnvironment(e) => Step::DeployEnvironment(e.clone()),
             Step::PauseEnvironment(e) => Step::PauseEnvironment(e.clone()),
             Step::DeleteEnvironment(e) => Step::DeleteEnvironment(e.clone()),
+            Step::RotateDatabaseCredentials(e, database_long_id, new_credentials) => {
+                Step::RotateDatabaseCredentials(e.clone(), *database_long_id, new_credentials.clone())
+            }
         }
     }
 }
@@ -608,10 +946,28 @@ pub enum RollbackError {
     Nothing,
 }
 
+/// Object storage location of the redacted workspace of a failed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceArchive {
+    pub bucket_name: String,
+    pub object_key: String,
+}
+
 #[derive(Debug)]
 pub enum TransactionResult {
     Ok,
     Canceled,
-    Rollback(EngineError),
-    UnrecoverableError(EngineError, RollbackError),
+    Rollback(EngineError, Option<WorkspaceArchive>),
+    UnrecoverableError(EngineError, RollbackError, Option<WorkspaceArchive>),
+}
+
+impl TransactionResult {
+    pub fn outcome(&self) -> &'static str {
+        match self {
+            TransactionResult::Ok => "ok",
+            TransactionResult::Canceled => "canceled",
+            TransactionResult::Rollback(_, _) => "rollback",
+            TransactionResult::UnrecoverableError(_, _, _) => "unrecoverable_error",
+        }
+    }
 }
@@ -0,0 +1,584 @@
+use crate::crypto::to_hmac_sha256;
+use crate::events::{io, EngineEvent, EventMessageVerbosity};
+use crate::io_models::StepName;
+use crate::io_models::{
+    CustomDomainStatus, ProgressCounter, ProgressInfo, ProgressLevel, ProgressListener, ProgressScope, ProgressSubTask,
+};
+use crate::logger::Logger;
+use reqwest::header::CONTENT_TYPE;
+use retry::delay::Exponential;
+use retry::OperationResult;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Header holding `sha256=<hex encoded HMAC-SHA256 of "<timestamp>.<request body>">`
+pub const SIGNATURE_HEADER: &str = "X-Qovery-Signature";
+/// Header holding the signed unix timestamp of the request, receivers reject old ones to prevent replays
+pub const TIMESTAMP_HEADER: &str = "X-Qovery-Timestamp";
+
+/// Items waiting in memory to be sent, the on-disk buffer takes over when it's full
+const MEMORY_QUEUE_SIZE: usize = 1000;
+
+#[derive(Clone, Debug)]
+pub struct WebhookConfig {
+    pub endpoint: Url,
+    /// key used to sign request bodies
+    pub secret: String,
+    pub verbosity: EventMessageVerbosity,
+    pub batch_size: usize,
+    pub flush_interval: Duration,
+    pub max_retries: usize,
+    pub request_timeout: Duration,
+    /// items which can't be delivered are kept in this file and sent again on next flush
+    pub buffer_file_path: PathBuf,
+    pub max_buffer_size_in_bytes: u64,
+}
+
+impl WebhookConfig {
+    pub fn new(endpoint: Url, secret: String, buffer_file_path: PathBuf) -> WebhookConfig {
+        WebhookConfig {
+            endpoint,
+            secret,
+            verbosity: EventMessageVerbosity::SafeOnly,
+            batch_size: 50,
+            flush_interval: Duration::from_secs(2),
+            max_retries: 5,
+            request_timeout: Duration::from_secs(10),
+            buffer_file_path,
+            max_buffer_size_in_bytes: 50 * 1024 * 1024,
+        }
+    }
+}
+
+/// Body of webhook requests is a JSON array of those items
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+enum WebhookItem<'a> {
+    EngineEvent {
+        event: io::EngineEvent,
+    },
+    Progress {
+        status: &'a str,
+        created_at: String,
+        scope: ProgressScope,
+        level: ProgressLevel,
+        message: Option<String>,
+        execution_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        step: Option<StepName>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        step_counter: Option<ProgressCounter>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sub_task: Option<ProgressSubTask>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        eta: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        custom_domain: Option<CustomDomainStatus>,
+    },
+}
+
+/// Bounded JSON lines file holding serialized items not delivered yet
+struct DiskBuffer {
+    path: PathBuf,
+    max_size_in_bytes: u64,
+}
+
+impl DiskBuffer {
+    /// Items which don't fit anymore are dropped, returns how many of them
+    fn push(&self, items: &[String]) -> usize {
+        let mut size = std::fs::metadata(&self.path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        let mut file = match OpenOptions::new().create(true).append(true).open(&self.path) {
+            Ok(file) => file,
+            Err(err) => {
+                error!("Cannot open webhook buffer file {:?}: {}", self.path, err);
+                return items.len();
+            }
+        };
+
+        for (idx, item) in items.iter().enumerate() {
+            let item_size = item.len() as u64 + 1;
+            if size + item_size > self.max_size_in_bytes {
+                return items.len() - idx;
+            }
+
+            if let Err(err) = writeln!(file, "{}", item) {
+                error!("Cannot write into webhook buffer file {:?}: {}", self.path, err);
+                return items.len() - idx;
+            }
+            size += item_size;
+        }
+
+        0
+    }
+
+    fn take(&self) -> Vec<String> {
+        let items = match File::open(&self.path) {
+            Ok(file) => BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter(|line| !line.is_empty())
+                .collect(),
+            Err(_) => return vec![],
+        };
+
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            error!("Cannot remove webhook buffer file {:?}: {}", self.path, err);
+        }
+
+        items
+    }
+}
+
+enum WorkerMessage {
+    Item(String),
+    /// deliver every pending item, then notify the sender
+    Flush(SyncSender<()>),
+    Stop,
+}
+
+enum Delivery {
+    Delivered,
+    /// receiver refused the batch, sending it again won't help
+    Rejected(String),
+    Failed(String),
+}
+
+/// Sends engine events and progress to a remote endpoint from a background thread.
+///
+/// Items are batched and POSTed as a signed JSON array, retried with exponential backoff and
+/// kept in a bounded on-disk buffer when the endpoint can't be reached, so callers are never blocked.
+/// Dropping the last handle delivers remaining items and waits for the background thread to stop.
+#[derive(Clone)]
+pub struct WebhookSink {
+    sender: SyncSender<WorkerMessage>,
+    buffer: Arc<Mutex<DiskBuffer>>,
+    verbosity: EventMessageVerbosity,
+    worker: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl WebhookSink {
+    pub fn new(config: WebhookConfig) -> WebhookSink {
+        let (sender, receiver) = sync_channel(MEMORY_QUEUE_SIZE);
+        let buffer = Arc::new(Mutex::new(DiskBuffer {
+            path: config.buffer_file_path.clone(),
+            max_size_in_bytes: config.max_buffer_size_in_bytes,
+        }));
+        let verbosity = config.verbosity;
+
+        let worker_buffer = buffer.clone();
+        let worker = thread::spawn(move || run_worker(config, receiver, worker_buffer));
+
+        WebhookSink {
+            sender,
+            buffer,
+            verbosity,
+            worker: Arc::new(Mutex::new(Some(worker))),
+        }
+    }
+
+    /// Block until every item sent so far is delivered, or buffered on disk if the endpoint can't be reached
+    pub fn flush(&self) {
+        let (ack_sender, ack_receiver) = sync_channel(1);
+        if self.sender.send(WorkerMessage::Flush(ack_sender)).is_ok() {
+            let _ = ack_receiver.recv();
+        }
+    }
+
+    pub fn logger(&self) -> WebhookLogger {
+        WebhookLogger { sink: self.clone() }
+    }
+
+    pub fn progress_listener(&self) -> WebhookProgressListener {
+        WebhookProgressListener { sink: self.clone() }
+    }
+
+    fn send(&self, item: WebhookItem) {
+        let line = match serde_json::to_string(&item) {
+            Ok(line) => line,
+            Err(err) => {
+                error!("Cannot serialize webhook item: {}", err);
+                return;
+            }
+        };
+
+        match self.sender.try_send(WorkerMessage::Item(line)) {
+            Ok(_) => {}
+            Err(TrySendError::Full(WorkerMessage::Item(line))) => {
+                if lock_buffer(&self.buffer).push(&[line]) > 0 {
+                    warn!("Webhook buffer is full, dropping item");
+                }
+            }
+            Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => error!("Webhook sink is stopped, dropping item"),
+        }
+    }
+
+    fn send_progress(&self, status: &str, info: ProgressInfo) {
+        self.send(WebhookItem::Progress {
+            status,
+            created_at: info.created_at.to_rfc3339(),
+            scope: info.scope,
+            level: info.level,
+            message: info.message,
+            execution_id: info.execution_id,
+            step: info.step,
+            step_counter: info.step_counter,
+            sub_task: info.sub_task,
+            eta: info.eta.map(|eta| eta.to_rfc3339()),
+            custom_domain: info.custom_domain,
+        })
+    }
+}
+
+impl Drop for WebhookSink {
+    fn drop(&mut self) {
+        // loggers are cloned all along the execution, only the last handle stops the worker
+        if Arc::strong_count(&self.worker) > 1 {
+            return;
+        }
+
+        let worker = match self.worker.lock() {
+            Ok(mut worker) => worker.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        if let Some(worker) = worker {
+            if self.sender.send(WorkerMessage::Stop).is_ok() && worker.join().is_err() {
+                error!("Webhook worker thread panicked");
+            }
+        }
+    }
+}
+
+// a poisoned lock only means another thread panicked while using the buffer, the file is still usable
+fn lock_buffer(buffer: &Mutex<DiskBuffer>) -> std::sync::MutexGuard<DiskBuffer> {
+    match buffer.lock() {
+        Ok(buffer) => buffer,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+fn run_worker(config: WebhookConfig, receiver: Receiver<WorkerMessage>, buffer: Arc<Mutex<DiskBuffer>>) {
+    let client = match reqwest::blocking::Client::builder()
+        .timeout(config.request_timeout)
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => {
+            error!("Cannot create webhook HTTP client: {}", err);
+            return;
+        }
+    };
+
+    let mut batch: Vec<String> = vec![];
+    let mut last_flush = Instant::now();
+
+    loop {
+        let (is_stopped, flush_ack) = match receiver.recv_timeout(config.flush_interval) {
+            Ok(WorkerMessage::Item(item)) => {
+                batch.push(item);
+                (false, None)
+            }
+            Ok(WorkerMessage::Flush(ack)) => (false, Some(ack)),
+            Ok(WorkerMessage::Stop) | Err(RecvTimeoutError::Disconnected) => (true, None),
+            Err(RecvTimeoutError::Timeout) => (false, None),
+        };
+
+        if is_stopped
+            || flush_ack.is_some()
+            || batch.len() >= config.batch_size
+            || last_flush.elapsed() >= config.flush_interval
+        {
+            flush(&config, &client, &buffer, std::mem::take(&mut batch));
+            last_flush = Instant::now();
+        }
+
+        if let Some(ack) = flush_ack {
+            let _ = ack.send(());
+        }
+
+        if is_stopped {
+            return;
+        }
+    }
+}
+
+fn flush(config: &WebhookConfig, client: &reqwest::blocking::Client, buffer: &Mutex<DiskBuffer>, batch: Vec<String>) {
+    // buffered items are older, send them first to keep ordering
+    let mut items = lock_buffer(buffer).take();
+    items.extend(batch);
+
+    let chunks = items.chunks(config.batch_size.max(1)).collect::<Vec<_>>();
+    for (idx, chunk) in chunks.iter().enumerate() {
+        match deliver(config, client, chunk) {
+            Delivery::Delivered => {}
+            Delivery::Rejected(reason) => error!("Webhook endpoint rejected {} items: {}", chunk.len(), reason),
+            Delivery::Failed(reason) => {
+                warn!("Cannot reach webhook endpoint, buffering items: {}", reason);
+                let remaining = chunks[idx..].concat();
+                let dropped = lock_buffer(buffer).push(&remaining);
+                if dropped > 0 {
+                    error!("Webhook buffer is full, {} items dropped", dropped);
+                }
+                return;
+            }
+        }
+    }
+}
+
+fn deliver(config: &WebhookConfig, client: &reqwest::blocking::Client, items: &[String]) -> Delivery {
+    let body = format!("[{}]", items.join(","));
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+        .to_string();
+    let signature = format!("sha256={}", to_hmac_sha256(&config.secret, &format!("{}.{}", timestamp, body)));
+
+    let result = retry::retry(Exponential::from_millis(200).take(config.max_retries), || {
+        let response = client
+            .post(config.endpoint.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.as_str())
+            .header(SIGNATURE_HEADER, signature.as_str())
+            .body(body.clone())
+            .send();
+
+        match response {
+            Ok(response) if response.status().is_success() => OperationResult::Ok(()),
+            Ok(response) if response.status().is_client_error() && response.status().as_u16() != 429 => {
+                OperationResult::Err(Delivery::Rejected(response.status().to_string()))
+            }
+            Ok(response) => OperationResult::Retry(Delivery::Failed(response.status().to_string())),
+            Err(err) => OperationResult::Retry(Delivery::Failed(err.to_string())),
+        }
+    });
+
+    match result {
+        Ok(_) => Delivery::Delivered,
+        Err(retry::Error::Operation { error, .. }) => error,
+        Err(retry::Error::Internal(err)) => Delivery::Failed(err),
+    }
+}
+
+#[derive(Clone)]
+pub struct WebhookLogger {
+    sink: WebhookSink,
+}
+
+impl Logger for WebhookLogger {
+    fn log(&self, event: EngineEvent) {
+        self.sink.send(WebhookItem::EngineEvent {
+            event: io::EngineEvent::new_with_verbosity(event, self.sink.verbosity),
+        })
+    }
+
+    fn clone_dyn(&self) -> Box<dyn Logger> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct WebhookProgressListener {
+    sink: WebhookSink,
+}
+
+impl ProgressListener for WebhookProgressListener {
+    fn deployment_in_progress(&self, info: ProgressInfo) {
+        self.sink.send_progress("deployment_in_progress", info)
+    }
+
+    fn pause_in_progress(&self, info: ProgressInfo) {
+        self.sink.send_progress("pause_in_progress", info)
+    }
+
+    fn delete_in_progress(&self, info: ProgressInfo) {
+        self.sink.send_progress("delete_in_progress", info)
+    }
+
+    fn error(&self, info: ProgressInfo) {
+        self.sink.send_progress("error", info)
+    }
+
+    fn deployed(&self, info: ProgressInfo) {
+        self.sink.send_progress("deployed", info)
+    }
+
+    fn paused(&self, info: ProgressInfo) {
+        self.sink.send_progress("paused", info)
+    }
+
+    fn deleted(&self, info: ProgressInfo) {
+        self.sink.send_progress("deleted", info)
+    }
+
+    fn deployment_error(&self, info: ProgressInfo) {
+        self.sink.send_progress("deployment_error", info)
+    }
+
+    fn pause_error(&self, info: ProgressInfo) {
+        self.sink.send_progress("pause_error", info)
+    }
+
+    fn delete_error(&self, info: ProgressInfo) {
+        self.sink.send_progress("delete_error", info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud_provider::Kind;
+    use crate::events::{EventDetails, EventMessage, InfrastructureStep, Stage, Transmitter};
+    use crate::io_models::QoveryIdentifier;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use tempdir::TempDir;
+
+    /// Accepts `count` requests and returns their signature and timestamp headers and body
+    fn spawn_http_server(count: usize) -> (Url, Receiver<(String, String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("cannot bind local port");
+        let url = Url::parse(&format!("http://{}/events", listener.local_addr().unwrap())).unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.expect("cannot accept connection");
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut signature = String::new();
+                let mut timestamp = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    let (name, value) = line.split_once(": ").unwrap_or((line, ""));
+                    match name.to_lowercase().as_str() {
+                        "content-length" => content_length = value.parse().unwrap(),
+                        "x-qovery-signature" => signature = value.to_string(),
+                        "x-qovery-timestamp" => timestamp = value.to_string(),
+                        _ => {}
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                // request is reported before answering, it is received once the client gets the response
+                sender
+                    .send((signature, timestamp, String::from_utf8(body).unwrap()))
+                    .unwrap();
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .unwrap();
+            }
+        });
+
+        (url, receiver)
+    }
+
+    #[test]
+    fn test_webhook_sink_sends_signed_batches() {
+        // setup:
+        let tmp_dir = TempDir::new("webhook").expect("error creating temporary dir");
+        let (url, requests) = spawn_http_server(1);
+        let mut config = WebhookConfig::new(url, "secret".to_string(), tmp_dir.path().join("buffer.jsonl"));
+        config.flush_interval = Duration::from_secs(60);
+
+        let sink = WebhookSink::new(config);
+        let logger = sink.logger();
+        let progress_listener = sink.progress_listener();
+        let cluster_id = QoveryIdentifier::new_from_long_id("cluster".to_string());
+
+        // execute:
+        logger.log(EngineEvent::Info(
+            EventDetails::new(
+                Some(Kind::Aws),
+                QoveryIdentifier::new_from_long_id("organization".to_string()),
+                cluster_id.clone(),
+                QoveryIdentifier::new_from_long_id("execution".to_string()),
+                None,
+                Stage::Infrastructure(InfrastructureStep::Create),
+                Transmitter::Kubernetes(cluster_id.to_string(), "cluster".to_string()),
+            ),
+            EventMessage::new("Safe message".to_string(), Some("Raw message".to_string())),
+        ));
+        progress_listener.deployed(ProgressInfo::new(
+            ProgressScope::Application { id: "app".to_string() },
+            ProgressLevel::Info,
+            Some("deployed"),
+            "execution",
+        ));
+        // last handle dropped, remaining items are delivered before it returns
+        drop(logger);
+        drop(progress_listener);
+        drop(sink);
+
+        // verify:
+        let (signature, timestamp, body) = requests.try_recv().expect("no request received");
+        assert_eq!(
+            signature,
+            format!("sha256={}", to_hmac_sha256("secret", &format!("{}.{}", timestamp, body)))
+        );
+        assert!(timestamp.parse::<u64>().is_ok());
+
+        let items: Vec<serde_json::Value> = serde_json::from_str(&body).expect("body is not a json array");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["kind"], "engine_event");
+        assert_eq!(items[0]["event"]["message"]["safe_message"], "Safe message");
+        assert!(!body.contains("Raw message"));
+        assert_eq!(items[1]["kind"], "progress");
+        assert_eq!(items[1]["status"], "deployed");
+        assert_eq!(items[1]["scope"]["id"], "app");
+    }
+
+    #[test]
+    fn test_webhook_sink_flush_delivers_pending_items() {
+        // setup:
+        let tmp_dir = TempDir::new("webhook").expect("error creating temporary dir");
+        let (url, requests) = spawn_http_server(1);
+        let mut config = WebhookConfig::new(url, "secret".to_string(), tmp_dir.path().join("buffer.jsonl"));
+        config.flush_interval = Duration::from_secs(60);
+        let sink = WebhookSink::new(config);
+
+        // execute:
+        sink.progress_listener().deployed(ProgressInfo::new(
+            ProgressScope::Application { id: "app".to_string() },
+            ProgressLevel::Info,
+            Some("deployed"),
+            "execution",
+        ));
+        sink.flush();
+
+        // verify:
+        let (_, _, body) = requests.try_recv().expect("no request received");
+        assert!(body.contains("\"status\":\"deployed\""));
+    }
+
+    #[test]
+    fn test_disk_buffer_is_bounded() {
+        let tmp_dir = TempDir::new("webhook").expect("error creating temporary dir");
+        let buffer = DiskBuffer {
+            path: tmp_dir.path().join("buffer.jsonl"),
+            max_size_in_bytes: 10,
+        };
+
+        assert!(buffer.take().is_empty());
+        assert_eq!(buffer.push(&["abc".to_string(), "def".to_string()]), 0);
+        // 8 bytes are used, only 2 are left
+        assert_eq!(buffer.push(&["ghi".to_string(), "j".to_string()]), 2);
+        assert_eq!(buffer.take(), vec!["abc".to_string(), "def".to_string()]);
+        assert!(buffer.take().is_empty());
+    }
+}
@@ -307,6 +307,7 @@ pub fn environment_3_apps_3_routers_3_databases(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advanced_settings: Default::default(),
+                release_strategy: Default::default(),
             },
             Application {
                 long_id: Uuid::new_v4(),
@@ -354,6 +355,7 @@ pub fn environment_3_apps_3_routers_3_databases(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advanced_settings: Default::default(),
+                release_strategy: Default::default(),
             },
             Application {
                 long_id: Uuid::new_v4(),
@@ -403,6 +405,7 @@ pub fn environment_3_apps_3_routers_3_databases(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advanced_settings: Default::default(),
+                release_strategy: Default::default(),
             },
         ],
         routers: vec![
@@ -416,8 +419,14 @@ pub fn environment_3_apps_3_routers_3_databases(
                 routes: vec![Route {
                     path: "/app1".to_string(),
                     application_name: app_name_1.clone(),
+                    access_control: None,
+                    protocol: Default::default(),
+                    port: None,
                 }],
                 sticky_sessions_enabled: false,
+                access_control: Default::default(),
+                advanced_settings: Default::default(),
+                visibility: Default::default(),
             },
             Router {
                 long_id: Uuid::new_v4(),
@@ -429,8 +438,14 @@ pub fn environment_3_apps_3_routers_3_databases(
                 routes: vec![Route {
                     path: "/app2".to_string(),
                     application_name: app_name_2,
+                    access_control: None,
+                    protocol: Default::default(),
+                    port: None,
                 }],
                 sticky_sessions_enabled: false,
+                access_control: Default::default(),
+                advanced_settings: Default::default(),
+                visibility: Default::default(),
             },
             Router {
                 long_id: Uuid::new_v4(),
@@ -442,8 +457,14 @@ pub fn environment_3_apps_3_routers_3_databases(
                 routes: vec![Route {
                     path: "/app3".to_string(),
                     application_name: app_name_3,
+                    access_control: None,
+                    protocol: Default::default(),
+                    port: None,
                 }],
                 sticky_sessions_enabled: false,
+                access_control: Default::default(),
+                advanced_settings: Default::default(),
+                visibility: Default::default(),
             },
         ],
         databases: vec![
@@ -466,6 +487,8 @@ pub fn environment_3_apps_3_routers_3_databases(
                 encrypt_disk: true,
                 activate_high_availability: false,
                 activate_backups: false,
+                read_replicas_count: 0,
+                read_fqdn: None,
                 publicly_accessible: false,
                 mode: CONTAINER,
             },
@@ -488,6 +511,8 @@ pub fn environment_3_apps_3_routers_3_databases(
                 encrypt_disk: true,
                 activate_high_availability: false,
                 activate_backups: false,
+                read_replicas_count: 0,
+                read_fqdn: None,
                 publicly_accessible: false,
                 mode: CONTAINER,
             },
@@ -510,11 +535,14 @@ pub fn environment_3_apps_3_routers_3_databases(
                 encrypt_disk: true,
                 activate_high_availability: false,
                 activate_backups: false,
+                read_replicas_count: 0,
+                read_fqdn: None,
                 publicly_accessible: false,
                 mode: CONTAINER,
             },
         ],
         clone_from_environment_id: None,
+        database_links: vec![],
     }
 }
 
@@ -561,6 +589,7 @@ pub fn working_minimal_environment(context: &Context, test_domain: &str) -> Envi
             max_instances: 2,
             cpu_burst: "100m".to_string(),
             advanced_settings: Default::default(),
+            release_strategy: Default::default(),
         }],
         routers: vec![Router {
             long_id: Uuid::new_v4(),
@@ -572,11 +601,18 @@ pub fn working_minimal_environment(context: &Context, test_domain: &str) -> Envi
             routes: vec![Route {
                 path: "/".to_string(),
                 application_name: application_name.to_string(),
+                access_control: None,
+                protocol: Default::default(),
+                port: None,
             }],
             sticky_sessions_enabled: false,
+            access_control: Default::default(),
+            advanced_settings: Default::default(),
+            visibility: Default::default(),
         }],
         databases: vec![],
         clone_from_environment_id: None,
+        database_links: vec![],
     }
 }
 
@@ -614,10 +650,12 @@ pub fn database_test_environment(context: &Context) -> EnvironmentRequest {
             max_instances: 1,
             cpu_burst: "100m".to_string(),
             advanced_settings: Default::default(),
+            release_strategy: Default::default(),
         }],
         routers: vec![],
         databases: vec![],
         clone_from_environment_id: None,
+        database_links: vec![],
     }
 }
 
@@ -655,10 +693,12 @@ pub fn database_test_environment_on_upgrade(context: &Context) -> EnvironmentReq
             max_instances: 1,
             cpu_burst: "100m".to_string(),
             advanced_settings: Default::default(),
+            release_strategy: Default::default(),
         }],
         routers: vec![],
         databases: vec![],
         clone_from_environment_id: None,
+        database_links: vec![],
     }
 }
 
@@ -718,6 +758,8 @@ pub fn environnement_2_app_2_routers_1_psql(
             encrypt_disk: true,
             activate_high_availability: false,
             activate_backups: false,
+            read_replicas_count: 0,
+            read_fqdn: None,
             publicly_accessible: false,
             mode: CONTAINER,
         }],
@@ -768,6 +810,7 @@ pub fn environnement_2_app_2_routers_1_psql(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advanced_settings: Default::default(),
+                release_strategy: Default::default(),
             },
             Application {
                 long_id: Uuid::new_v4(),
@@ -815,6 +858,7 @@ pub fn environnement_2_app_2_routers_1_psql(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advanced_settings: Default::default(),
+                release_strategy: Default::default(),
             },
         ],
         routers: vec![
@@ -828,8 +872,14 @@ pub fn environnement_2_app_2_routers_1_psql(
                 routes: vec![Route {
                     path: "/".to_string(),
                     application_name: application_name1,
+                    access_control: None,
+                    protocol: Default::default(),
+                    port: None,
                 }],
                 sticky_sessions_enabled: false,
+                access_control: Default::default(),
+                advanced_settings: Default::default(),
+                visibility: Default::default(),
             },
             Router {
                 long_id: Uuid::new_v4(),
@@ -841,11 +891,18 @@ pub fn environnement_2_app_2_routers_1_psql(
                 routes: vec![Route {
                     path: "/coco".to_string(),
                     application_name: application_name2,
+                    access_control: None,
+                    protocol: Default::default(),
+                    port: None,
                 }],
                 sticky_sessions_enabled: false,
+                access_control: Default::default(),
+                advanced_settings: Default::default(),
+                visibility: Default::default(),
             },
         ],
         clone_from_environment_id: None,
+        database_links: vec![],
     }
 }
 
@@ -911,6 +968,7 @@ pub fn echo_app_environment(context: &Context, test_domain: &str) -> Environment
             max_instances: 2,
             cpu_burst: "100m".to_string(),
             advanced_settings: Default::default(),
+            release_strategy: Default::default(),
         }],
         routers: vec![Router {
             long_id: Uuid::new_v4(),
@@ -922,11 +980,18 @@ pub fn echo_app_environment(context: &Context, test_domain: &str) -> Environment
             routes: vec![Route {
                 path: "/".to_string(),
                 application_name: format!("{}-{}", "echo-app", &suffix),
+                access_control: None,
+                protocol: Default::default(),
+                port: None,
             }],
             sticky_sessions_enabled: false,
+            access_control: Default::default(),
+            advanced_settings: Default::default(),
+            visibility: Default::default(),
         }],
         databases: vec![],
         clone_from_environment_id: None,
+        database_links: vec![],
     }
 }
 
@@ -971,10 +1036,12 @@ pub fn environment_only_http_server(context: &Context) -> EnvironmentRequest {
             max_instances: 2,
             cpu_burst: "100m".to_string(),
             advanced_settings: Default::default(),
+            release_strategy: Default::default(),
         }],
         routers: vec![],
         databases: vec![],
         clone_from_environment_id: None,
+        database_links: vec![],
     }
 }
 
@@ -1020,6 +1087,7 @@ pub fn environment_only_http_server_router(context: &Context, test_domain: &str)
             max_instances: 2,
             cpu_burst: "100m".to_string(),
             advanced_settings: Default::default(),
+            release_strategy: Default::default(),
         }],
         routers: vec![Router {
             long_id: Uuid::new_v4(),
@@ -1031,11 +1099,18 @@ pub fn environment_only_http_server_router(context: &Context, test_domain: &str)
             routes: vec![Route {
                 path: "/".to_string(),
                 application_name: format!("{}-{}", "mini-http", &suffix),
+                access_control: None,
+                protocol: Default::default(),
+                port: None,
             }],
             sticky_sessions_enabled: false,
+            access_control: Default::default(),
+            advanced_settings: Default::default(),
+            visibility: Default::default(),
         }],
         databases: vec![],
         clone_from_environment_id: None,
+        database_links: vec![],
     }
 }
 
@@ -1135,6 +1210,8 @@ pub fn test_db(
         encrypt_disk: true,
         activate_high_availability: false,
         activate_backups: false,
+        read_replicas_count: 0,
+        read_fqdn: None,
         publicly_accessible: is_public,
         mode: database_mode.clone(),
     };
@@ -1827,6 +1904,8 @@ pub fn test_db_on_upgrade(
         encrypt_disk: true,
         activate_high_availability: false,
         activate_backups: false,
+        read_replicas_count: 0,
+        read_fqdn: None,
         publicly_accessible: is_public,
         mode: database_mode.clone(),
     };
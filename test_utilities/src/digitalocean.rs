@@ -25,7 +25,7 @@ pub const DO_KUBERNETES_MINOR_VERSION: u8 = 20;
 pub const DO_KUBERNETES_VERSION: &str = formatcp!("{}.{}", DO_KUBERNETES_MAJOR_VERSION, DO_KUBERNETES_MINOR_VERSION);
 pub const DOCR_ID: &str = "registry-the-one-and-unique";
 pub const DO_TEST_REGION: DoRegion = DoRegion::Amsterdam3;
-pub const DO_MANAGED_DATABASE_INSTANCE_TYPE: &str = "";
+pub const DO_MANAGED_DATABASE_INSTANCE_TYPE: &str = "db-s-1vcpu-1gb";
 pub const DO_MANAGED_DATABASE_DISK_TYPE: &str = "";
 pub const DO_SELF_HOSTED_DATABASE_INSTANCE_TYPE: &str = "";
 pub const DO_SELF_HOSTED_DATABASE_DISK_TYPE: &str = "do-block-storage";
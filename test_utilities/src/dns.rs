@@ -2,6 +2,7 @@ use crate::common::ClusterDomain;
 use crate::utilities::FuncTestsSecrets;
 use qovery_engine::dns_provider::cloudflare::Cloudflare;
 use qovery_engine::dns_provider::qoverydns::QoveryDns;
+use qovery_engine::dns_provider::route53::Route53Dns;
 use qovery_engine::dns_provider::DnsProvider;
 use qovery_engine::io_models::{Context, Domain};
 
@@ -54,3 +55,27 @@ pub fn dns_provider_qoverydns(context: &Context, domain: &ClusterDomain) -> Box<
         domain,
     ))
 }
+
+pub fn dns_provider_route53(context: &Context, domain: &ClusterDomain) -> Box<dyn DnsProvider> {
+    let secrets = FuncTestsSecrets::new();
+    let domain = Domain::new(match domain {
+        ClusterDomain::Custom(domain) => domain.to_string(),
+        ClusterDomain::Default { cluster_id } => {
+            format!("{}.{}", cluster_id, secrets.ROUTE53_DOMAIN.expect("ROUTE53_DOMAIN is not set"))
+        }
+    });
+    Box::new(Route53Dns::new(
+        context.clone(),
+        "qoverytestdnsr53",
+        "Qovery Test Route53",
+        domain,
+        secrets
+            .AWS_ACCESS_KEY_ID
+            .expect("AWS_ACCESS_KEY_ID is not set")
+            .as_str(),
+        secrets
+            .AWS_SECRET_ACCESS_KEY
+            .expect("AWS_SECRET_ACCESS_KEY is not set")
+            .as_str(),
+    ))
+}
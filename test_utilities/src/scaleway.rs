@@ -31,6 +31,7 @@ pub const SCW_KUBERNETES_MINOR_VERSION: u8 = 19;
 pub const SCW_KUBERNETES_VERSION: &str = formatcp!("{}.{}", SCW_KUBERNETES_MAJOR_VERSION, SCW_KUBERNETES_MINOR_VERSION);
 pub const SCW_MANAGED_DATABASE_INSTANCE_TYPE: &str = "db-dev-s";
 pub const SCW_MANAGED_DATABASE_DISK_TYPE: &str = "bssd";
+pub const SCW_MANAGED_REDIS_INSTANCE_TYPE: &str = "RED1-MICRO";
 pub const SCW_SELF_HOSTED_DATABASE_INSTANCE_TYPE: &str = "";
 pub const SCW_SELF_HOSTED_DATABASE_DISK_TYPE: &str = "scw-sbv-ssd-0";
 pub const SCW_RESOURCE_TTL_IN_SECONDS: u32 = 7200;
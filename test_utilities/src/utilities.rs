@@ -24,8 +24,8 @@ use std::str::FromStr;
 use tracing::{info, warn};
 
 use crate::scaleway::{
-    SCW_MANAGED_DATABASE_DISK_TYPE, SCW_MANAGED_DATABASE_INSTANCE_TYPE, SCW_SELF_HOSTED_DATABASE_DISK_TYPE,
-    SCW_SELF_HOSTED_DATABASE_INSTANCE_TYPE,
+    SCW_MANAGED_DATABASE_DISK_TYPE, SCW_MANAGED_DATABASE_INSTANCE_TYPE, SCW_MANAGED_REDIS_INSTANCE_TYPE,
+    SCW_SELF_HOSTED_DATABASE_DISK_TYPE, SCW_SELF_HOSTED_DATABASE_INSTANCE_TYPE,
 };
 use hashicorp_vault;
 use qovery_engine::build_platform::local_docker::LocalDocker;
@@ -136,6 +136,7 @@ pub struct FuncTestsSecrets {
     pub QOVERY_NATS_USERNAME: Option<String>,
     pub QOVERY_NATS_PASSWORD: Option<String>,
     pub QOVERY_SSH_USER: Option<String>,
+    pub ROUTE53_DOMAIN: Option<String>,
     pub RUST_LOG: Option<String>,
     pub SCALEWAY_DEFAULT_PROJECT_ID: Option<String>,
     pub SCALEWAY_ACCESS_KEY: Option<String>,
@@ -231,6 +232,7 @@ impl FuncTestsSecrets {
             QOVERY_NATS_USERNAME: None,
             QOVERY_NATS_PASSWORD: None,
             QOVERY_SSH_USER: None,
+            ROUTE53_DOMAIN: None,
             RUST_LOG: None,
             SCALEWAY_ACCESS_KEY: None,
             SCALEWAY_DEFAULT_PROJECT_ID: None,
@@ -341,6 +343,7 @@ impl FuncTestsSecrets {
             QOVERY_NATS_USERNAME: Self::select_secret("QOVERY_NATS_USERNAME", secrets.QOVERY_NATS_USERNAME),
             QOVERY_NATS_PASSWORD: Self::select_secret("QOVERY_NATS_PASSWORD", secrets.QOVERY_NATS_PASSWORD),
             QOVERY_SSH_USER: Self::select_secret("QOVERY_SSH_USER", secrets.QOVERY_SSH_USER),
+            ROUTE53_DOMAIN: Self::select_secret("ROUTE53_DOMAIN", secrets.ROUTE53_DOMAIN),
             RUST_LOG: Self::select_secret("RUST_LOG", secrets.RUST_LOG),
             SCALEWAY_ACCESS_KEY: Self::select_secret("SCALEWAY_ACCESS_KEY", secrets.SCALEWAY_ACCESS_KEY),
             SCALEWAY_DEFAULT_PROJECT_ID: Self::select_secret(
@@ -1055,9 +1058,10 @@ pub fn db_instance_type(provider_kind: Kind, db_kind: DatabaseKind, database_mod
             DatabaseMode::MANAGED => DO_MANAGED_DATABASE_INSTANCE_TYPE,
             DatabaseMode::CONTAINER => DO_SELF_HOSTED_DATABASE_INSTANCE_TYPE,
         },
-        Kind::Scw => match database_mode {
-            DatabaseMode::MANAGED => SCW_MANAGED_DATABASE_INSTANCE_TYPE,
-            DatabaseMode::CONTAINER => SCW_SELF_HOSTED_DATABASE_INSTANCE_TYPE,
+        Kind::Scw => match (database_mode, db_kind) {
+            (DatabaseMode::MANAGED, DatabaseKind::Redis) => SCW_MANAGED_REDIS_INSTANCE_TYPE,
+            (DatabaseMode::MANAGED, _) => SCW_MANAGED_DATABASE_INSTANCE_TYPE,
+            (DatabaseMode::CONTAINER, _) => SCW_SELF_HOSTED_DATABASE_INSTANCE_TYPE,
         },
     }
     .to_string()
@@ -279,6 +279,8 @@ fn postgresql_deploy_a_working_environment_and_redeploy() {
             encrypt_disk: false,
             activate_high_availability: false,
             activate_backups: false,
+            read_replicas_count: 0,
+            read_fqdn: None,
             publicly_accessible: false,
             mode: CONTAINER,
         }];
@@ -334,7 +336,7 @@ fn postgresql_deploy_a_working_environment_and_redeploy() {
         let ret = environment_delete.delete_environment(&ea_delete, logger, &engine_config_for_delete);
         assert!(matches!(
             ret,
-            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         test_name.to_string()
@@ -335,12 +335,12 @@ fn deploy_a_not_working_environment_with_no_router_on_aws_eks() {
         let ea_delete = environment_delete.clone();
 
         let ret = environment.deploy_environment(&ea, logger.clone(), &engine_config);
-        assert!(matches!(ret, TransactionResult::UnrecoverableError(_, _)));
+        assert!(matches!(ret, TransactionResult::UnrecoverableError(_, _, _)));
 
         let ret = environment_delete.delete_environment(&ea_delete, logger, &engine_config_for_delete);
         assert!(matches!(
             ret,
-            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         test_name.to_string()
@@ -787,7 +787,7 @@ fn deploy_a_not_working_environment_and_after_working_environment() {
             logger.clone(),
             &engine_config_for_not_working,
         );
-        assert!(matches!(ret, TransactionResult::UnrecoverableError(_, _)));
+        assert!(matches!(ret, TransactionResult::UnrecoverableError(_, _, _)));
 
         let ret = environment.deploy_environment(&ea, logger.clone(), &engine_config);
         assert!(matches!(ret, TransactionResult::Ok));
@@ -878,7 +878,7 @@ fn deploy_ok_fail_fail_ok_environment() {
             not_working_env_1.deploy_environment(&ea_not_working_1, logger.clone(), &engine_config_for_not_working_1);
         assert!(matches!(
             ret,
-            TransactionResult::Rollback(_) | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Rollback(_, _) | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         // FAIL and Rollback again
@@ -886,7 +886,7 @@ fn deploy_ok_fail_fail_ok_environment() {
             not_working_env_2.deploy_environment(&ea_not_working_2, logger.clone(), &engine_config_for_not_working_2);
         assert!(matches!(
             ret,
-            TransactionResult::Rollback(_) | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Rollback(_, _) | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         // Should be working
@@ -943,7 +943,7 @@ fn deploy_a_non_working_environment_with_no_failover_on_aws_eks() {
         let ea_delete = delete_env.clone();
 
         let ret = environment.deploy_environment(&ea, logger.clone(), &engine_config);
-        assert!(matches!(ret, TransactionResult::UnrecoverableError(_, _)));
+        assert!(matches!(ret, TransactionResult::UnrecoverableError(_, _, _)));
 
         let ret = delete_env.delete_environment(&ea_delete, logger, &engine_config_for_delete);
         assert!(matches!(ret, TransactionResult::Ok));
@@ -308,6 +308,8 @@ fn postgresql_deploy_a_working_environment_and_redeploy() {
             encrypt_disk: false,
             activate_high_availability: false,
             activate_backups: false,
+            read_replicas_count: 0,
+            read_fqdn: None,
             publicly_accessible: false,
         }];
         environment.applications = environment
@@ -372,7 +374,7 @@ fn postgresql_deploy_a_working_environment_and_redeploy() {
         let ret = environment_delete.delete_environment(&env_action_delete, logger, &engine_config_for_delete);
         assert!(matches!(
             ret,
-            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         // delete images created during test from registries
@@ -482,6 +484,39 @@ fn public_postgresql_v13_deploy_a_working_dev_environment() {
     test_postgresql_configuration("13", function_name!(), CONTAINER, true);
 }
 
+// Postgres production environment
+#[cfg(feature = "test-do-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn private_postgresql_v13_deploy_a_working_prod_environment() {
+    test_postgresql_configuration("13", function_name!(), MANAGED, false);
+}
+
+#[cfg(feature = "test-do-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn public_postgresql_v13_deploy_a_working_prod_environment() {
+    test_postgresql_configuration("13", function_name!(), MANAGED, true);
+}
+
+#[cfg(feature = "test-do-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn private_postgresql_v14_deploy_a_working_prod_environment() {
+    test_postgresql_configuration("14", function_name!(), MANAGED, false);
+}
+
+#[cfg(feature = "test-do-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn public_postgresql_v14_deploy_a_working_prod_environment() {
+    test_postgresql_configuration("14", function_name!(), MANAGED, true);
+}
+
 /**
  **
  ** MongoDB tests
@@ -579,6 +614,23 @@ fn public_mongodb_v4_4_deploy_a_working_dev_environment() {
     test_mongodb_configuration("4.4", function_name!(), CONTAINER, true);
 }
 
+// MongoDB production environment
+#[cfg(feature = "test-do-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn private_mongodb_v4_4_deploy_a_working_prod_environment() {
+    test_mongodb_configuration("4.4", function_name!(), MANAGED, false);
+}
+
+#[cfg(feature = "test-do-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn public_mongodb_v4_4_deploy_a_working_prod_environment() {
+    test_mongodb_configuration("4.4", function_name!(), MANAGED, true);
+}
+
 /**
  **
  ** MySQL tests
@@ -647,6 +699,21 @@ fn public_mysql_v8_deploy_a_working_dev_environment() {
 }
 
 // MySQL production environment
+#[cfg(feature = "test-do-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn private_mysql_v8_deploy_a_working_prod_environment() {
+    test_mysql_configuration("8.0", function_name!(), MANAGED, false);
+}
+
+#[cfg(feature = "test-do-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn public_mysql_v8_deploy_a_working_prod_environment() {
+    test_mysql_configuration("8.0", function_name!(), MANAGED, true);
+}
 
 /**
  **
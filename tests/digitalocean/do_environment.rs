@@ -172,13 +172,13 @@ fn digitalocean_doks_deploy_a_not_working_environment_with_no_router() {
         let env_action_for_delete = environment_for_delete.clone();
 
         let result = environment.deploy_environment(&env_action, logger.clone(), &engine_config);
-        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _)));
+        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _, _)));
 
         let result =
             environment_for_delete.delete_environment(&env_action_for_delete, logger, &engine_config_for_delete);
         assert!(matches!(
             result,
-            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         if let Err(e) = clean_environments(&context, vec![environment], secrets, DO_TEST_REGION) {
@@ -669,7 +669,7 @@ fn digitalocean_doks_deploy_a_not_working_environment_and_then_working_environme
             logger.clone(),
             &engine_config_for_not_working,
         );
-        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _)));
+        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _, _)));
         let result = environment.deploy_environment(&env_action, logger.clone(), &engine_config);
         assert!(matches!(result, TransactionResult::Ok));
         let result = environment_for_delete.delete_environment(&env_action_delete, logger, &engine_config_for_delete);
@@ -763,7 +763,7 @@ fn digitalocean_doks_deploy_ok_fail_fail_ok_environment() {
         );
         assert!(matches!(
             result,
-            TransactionResult::Rollback(_) | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Rollback(_, _) | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         // FAIL and Rollback again
@@ -774,7 +774,7 @@ fn digitalocean_doks_deploy_ok_fail_fail_ok_environment() {
         );
         assert!(matches!(
             result,
-            TransactionResult::Rollback(_) | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Rollback(_, _) | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         // Should be working
@@ -834,7 +834,7 @@ fn digitalocean_doks_deploy_a_non_working_environment_with_no_failover() {
         let env_action_delete = delete_env.clone();
 
         let result = environment.deploy_environment(&env_action, logger.clone(), &engine_config);
-        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _)));
+        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _, _)));
 
         let result = delete_env.delete_environment(&env_action_delete, logger, &engine_config_for_delete);
         assert!(matches!(result, TransactionResult::Ok));
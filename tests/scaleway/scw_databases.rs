@@ -313,6 +313,8 @@ fn postgresql_deploy_a_working_environment_and_redeploy() {
             encrypt_disk: false,
             activate_high_availability: false,
             activate_backups: false,
+            read_replicas_count: 0,
+            read_fqdn: None,
             publicly_accessible: false,
         }];
         environment.applications = environment
@@ -377,7 +379,7 @@ fn postgresql_deploy_a_working_environment_and_redeploy() {
         let result = environment_delete.delete_environment(&env_action_delete, logger, &engine_config_for_delete);
         assert!(matches!(
             result,
-            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         // delete images created during test from registries
@@ -802,3 +804,20 @@ fn private_redis_v6_deploy_a_working_dev_environment() {
 fn public_redis_v6_deploy_a_working_dev_environment() {
     test_redis_configuration("6", function_name!(), CONTAINER, true);
 }
+
+// Redis production environment
+#[cfg(feature = "test-scw-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn private_redis_v6_deploy_a_working_prod_environment() {
+    test_redis_configuration("6", function_name!(), MANAGED, false);
+}
+
+#[cfg(feature = "test-scw-managed-services")]
+#[named]
+#[test]
+#[ignore]
+fn public_redis_v6_deploy_a_working_prod_environment() {
+    test_redis_configuration("6", function_name!(), MANAGED, true);
+}
@@ -178,13 +178,13 @@ fn scaleway_kapsule_deploy_a_not_working_environment_with_no_router() {
         let env_action_for_delete = environment_for_delete.clone();
 
         let result = environment.deploy_environment(&env_action, logger.clone(), &engine_config);
-        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _)));
+        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _, _)));
 
         let result =
             environment_for_delete.delete_environment(&env_action_for_delete, logger, &engine_config_for_delete);
         assert!(matches!(
             result,
-            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Ok | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         if let Err(e) = clean_environments(&context, vec![environment], secrets, SCW_TEST_ZONE) {
@@ -771,7 +771,7 @@ fn scaleway_kapsule_deploy_a_not_working_environment_and_then_working_environmen
             logger.clone(),
             &engine_config_for_not_working,
         );
-        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _)));
+        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _, _)));
 
         let result = environment.deploy_environment(&env_action, logger.clone(), &engine_config);
         assert!(matches!(result, TransactionResult::Ok));
@@ -870,7 +870,7 @@ fn scaleway_kapsule_deploy_ok_fail_fail_ok_environment() {
         );
         assert!(matches!(
             result,
-            TransactionResult::Rollback(_) | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Rollback(_, _) | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         // FAIL and Rollback again
@@ -881,7 +881,7 @@ fn scaleway_kapsule_deploy_ok_fail_fail_ok_environment() {
         );
         assert!(matches!(
             result,
-            TransactionResult::Rollback(_) | TransactionResult::UnrecoverableError(_, _)
+            TransactionResult::Rollback(_, _) | TransactionResult::UnrecoverableError(_, _, _)
         ));
 
         // Should be working
@@ -944,7 +944,7 @@ fn scaleway_kapsule_deploy_a_non_working_environment_with_no_failover() {
         let env_action_delete = delete_env.clone();
 
         let result = environment.deploy_environment(&env_action, logger.clone(), &engine_config);
-        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _)));
+        assert!(matches!(result, TransactionResult::UnrecoverableError(_, _, _)));
 
         let result = delete_env.delete_environment(&env_action_delete, logger, &engine_config_for_delete);
         assert!(matches!(result, TransactionResult::Ok));